@@ -1,5 +1,5 @@
 use anyhow::Result;
-use api_database::Client;
+use api_database::{Client, DatabaseAuth};
 
 use api_core::{api::MutateCategories, Category};
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
@@ -18,8 +18,10 @@ async fn create_client(with_ns: Option<&str>) -> Result<Client> {
 
     let client = Client::try_new(
         &db_host,
-        &username,
-        &password,
+        DatabaseAuth::Root {
+            username: &username,
+            password: &password,
+        },
         with_ns.unwrap_or(&db_namespace),
         &db_name,
         None,
@@ -48,6 +50,7 @@ fn bench(c: &mut Criterion) {
         sub_categories,
         image_url: None,
         parent_id: None,
+        ..Default::default()
     };
 
     c.bench_with_input(
@@ -55,7 +58,7 @@ fn bench(c: &mut Criterion) {
         &size,
         |b, &_s| {
             b.to_async(&rt)
-                .iter(|| black_box(client.create_category(&category)));
+                .iter(|| black_box(client.create_category(&category, None)));
         },
     );
 
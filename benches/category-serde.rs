@@ -19,6 +19,7 @@ fn bench(c: &mut Criterion) {
             sub_categories,
             image_url: None,
             parent_id: None,
+            ..Default::default()
         };
 
         categories.push(category);
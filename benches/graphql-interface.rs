@@ -1,4 +1,4 @@
-use api_interface::DatabaseCredentials;
+use api_interface::{DatabaseAuth, DatabaseCredentials};
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
 
 fn bench(c: &mut Criterion) {
@@ -13,8 +13,10 @@ fn bench(c: &mut Criterion) {
     let db_name = std::env::var("TEST_DATABASE_NAME").expect("TEST_DATABASE_NAME");
     let credentials = DatabaseCredentials {
         db_dsn: &db_host,
-        db_user: &username,
-        db_pass: &password,
+        db_auth: DatabaseAuth::Root {
+            username: &username,
+            password: &password,
+        },
         db_ns: "benchmarks",
         db: &db_name,
     };
@@ -40,7 +42,7 @@ fn bench(c: &mut Criterion) {
                          node{{
                            id,
                            name,
-                           subCategories,
+                           subCategoryIds,
                            imageUrl
                          }}
                        }},
@@ -8,6 +8,29 @@ pub enum CoreError {
     Database(String),
     #[error("`{0}`")]
     Other(String),
+    #[error("category has subcategories: {0}")]
+    HasSubCategories(uuid::Uuid),
+    #[error("category {parent} is {id} or one of its subcategories")]
+    CyclicHierarchy { id: uuid::Uuid, parent: uuid::Uuid },
+    #[error("category hierarchy cannot be deeper than {0} levels")]
+    MaxDepthExceeded(usize),
+    #[error("category {0} was changed by someone else")]
+    VersionConflict(uuid::Uuid),
+    #[error("category {0} already exists")]
+    AlreadyExists(uuid::Uuid),
+    #[error("a category with the slug `{slug}` already exists under the same parent")]
+    DuplicateSlug {
+        slug: String,
+        parent_id: Option<uuid::Uuid>,
+    },
+    #[error("a category name needs a letter or a digit to make a slug from")]
+    EmptySlug,
+    #[error("the new order has to list every subcategory exactly once")]
+    InvalidOrder,
+    #[error("invalid attribute `{key}`: {reason}")]
+    InvalidAttribute { key: String, reason: String },
+    #[error("category {0} has to be deleted before it can be purged")]
+    NotDeleted(uuid::Uuid),
     #[error(transparent)]
     Uuid(#[from] uuid::Error),
     #[error("unknown core error")]
@@ -1,32 +1,174 @@
 mod error;
+mod shared;
 pub use std::fmt::Debug;
 
-use crate::Category;
+use chrono::{DateTime, Utc};
+
+use crate::{
+    Category, CategoryDeletion, CategoryFilter, CategoryOrder, CategoryPatch, CategoryRevision,
+    CategoryStats, CategoryTree, CategoryUpdate, DeleteStrategy, DeletedCategory, KeysetPage,
+    RestoredCategory,
+};
 
 pub use error::*;
 pub use uuid::Uuid;
 
+/// Deleted categories stay in a trash until purged and are left out of queries by default
 #[trait_variant::make(QueryCategories: Send)]
 pub trait LocalQueryCategories {
-    async fn get_categories(&self) -> Result<impl ExactSizeIterator<Item = Category>, CoreError>;
+    async fn get_categories(
+        &self,
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError>;
     async fn get_sub_categories(
         &self,
         id: Option<&Uuid>,
-    ) -> Result<impl ExactSizeIterator<Item = Category>, CoreError>;
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError>;
+    /// Returns the categories matching `filter`, sorted by `order`
+    async fn find_categories(
+        &self,
+        filter: &CategoryFilter,
+        order: Option<&CategoryOrder>,
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError>;
+    /// Returns one [`KeysetPage`] of the categories matching `filter`, sorted by `order`
+    async fn find_categories_page(
+        &self,
+        filter: &CategoryFilter,
+        order: &CategoryOrder,
+        page: &KeysetPage,
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError>;
+    /// Counts the categories matching `filter`
+    async fn count_categories(&self, filter: &CategoryFilter) -> Result<usize, CoreError>;
     async fn search(
         &self,
         query: impl AsRef<str> + Send + Debug,
-    ) -> Result<impl ExactSizeIterator<Item = Category>, CoreError>;
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError>;
     async fn get_category_by_id(&self, id: &Uuid) -> Result<Option<Category>, CoreError>;
+    /// Looks up every one of `ids` in one go, in the order of `ids`, leaving out missing ones
+    async fn get_categories_by_ids(
+        &self,
+        ids: &[Uuid],
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError>;
+    /// Looks a category up by its slug path from the top level, as in `apparel/shoes`
+    async fn get_category_by_slug(&self, path: &str) -> Result<Option<Category>, CoreError>;
+    /// Returns every category in the trash
+    async fn get_deleted_categories(
+        &self,
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError>;
+    /// Returns the parents of `id`, from the top level down to its direct parent
+    async fn get_ancestors(
+        &self,
+        id: &Uuid,
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError>;
+    /// Returns the subtree under `id`, or under the top level when `None`, `max_depth` levels deep
+    async fn get_category_tree(
+        &self,
+        id: Option<&Uuid>,
+        max_depth: usize,
+    ) -> Result<impl ExactSizeIterator<Item = CategoryTree> + Send, CoreError>;
+    /// Returns the [`CategoryStats`] of each of `ids` that exists, deleted categories included
+    async fn get_category_stats(
+        &self,
+        ids: &[Uuid],
+    ) -> Result<impl ExactSizeIterator<Item = CategoryStats> + Send, CoreError>;
+    /// Returns every recorded change to `id`, oldest first
+    async fn get_category_history(
+        &self,
+        id: &Uuid,
+    ) -> Result<impl ExactSizeIterator<Item = CategoryRevision> + Send, CoreError>;
+    /// Returns the category as it was at `timestamp`, `None` if it did not exist then
+    async fn get_category_at(
+        &self,
+        id: &Uuid,
+        timestamp: &DateTime<Utc>,
+    ) -> Result<Option<Category>, CoreError>;
 }
 
+/// Mutations treat deleted categories as missing unless stated otherwise
 #[trait_variant::make(MutateCategories: Send)]
 pub trait LocalMutateCategories {
-    async fn create_category(&self, category: &Category) -> Result<Category, CoreError>;
+    /// Creates a category and appends it to its parent's `sub_categories`. Every mutation records
+    /// `caller`, when given, in `created_by`/`updated_by`
+    async fn create_category(
+        &self,
+        category: &Category,
+        caller: Option<&str>,
+    ) -> Result<Category, CoreError>;
+    /// Replaces the category's content, keeping its `sub_categories`. Fails with
+    /// [`CoreError::VersionConflict`] when `expected_version` no longer matches
     async fn update_category(
         &self,
         id: &Uuid,
         data: &Category,
+        expected_version: Option<u64>,
+        caller: Option<&str>,
     ) -> Result<Option<Category>, CoreError>;
-    async fn delete_category(&self, id: &Uuid) -> Result<Option<Category>, CoreError>;
+    /// Updates only the fields set in `patch`
+    async fn patch_category(
+        &self,
+        id: &Uuid,
+        patch: &CategoryPatch,
+        expected_version: Option<u64>,
+        caller: Option<&str>,
+    ) -> Result<Option<Category>, CoreError>;
+    /// Moves the category to the trash, handling its subcategories according to `strategy`
+    async fn delete_category(
+        &self,
+        id: &Uuid,
+        strategy: DeleteStrategy,
+        expected_version: Option<u64>,
+        caller: Option<&str>,
+    ) -> Result<Option<DeletedCategory>, CoreError>;
+    /// Moves the category and its subtree under `parent`, at `position` or last among its siblings
+    async fn move_category(
+        &self,
+        id: &Uuid,
+        parent: Option<&Uuid>,
+        position: Option<usize>,
+        expected_version: Option<u64>,
+        caller: Option<&str>,
+    ) -> Result<Option<Category>, CoreError>;
+    /// Gives the subcategories of `parent` the order of `ordered`, which lists each of them once
+    async fn reorder_sub_categories(
+        &self,
+        parent: Option<&Uuid>,
+        ordered: &[Uuid],
+        caller: Option<&str>,
+    ) -> Result<Option<Vec<Category>>, CoreError>;
+    /// Adds `delta` to the listings filed under the category, without changing its version
+    async fn adjust_listing_count(&self, id: &Uuid, delta: i64) -> Result<Option<u64>, CoreError>;
+    /// Brings the category back to the content it had at `revision`, keeping its `sub_categories`
+    async fn restore_category(
+        &self,
+        id: &Uuid,
+        revision: u64,
+        caller: Option<&str>,
+    ) -> Result<Option<Category>, CoreError>;
+    /// Takes a deleted category and the subcategories deleted with it out of the trash
+    async fn undelete_category(
+        &self,
+        id: &Uuid,
+        caller: Option<&str>,
+    ) -> Result<Option<RestoredCategory>, CoreError>;
+    /// Permanently removes a category in the trash and its subcategories, keeping their history
+    async fn purge_category(&self, id: &Uuid) -> Result<Option<DeletedCategory>, CoreError>;
+    /// Creates every category in a single write. Categories can use the `id` of others in the
+    /// batch as their `parent_id`; nothing is written when any of them is rejected
+    async fn create_categories(
+        &self,
+        categories: &[Category],
+        caller: Option<&str>,
+    ) -> Result<Vec<Category>, CoreError>;
+    /// Applies every update in order in a single write, nothing being written when one fails
+    async fn update_categories(
+        &self,
+        updates: &[CategoryUpdate],
+        caller: Option<&str>,
+    ) -> Result<Vec<Option<Category>>, CoreError>;
+    /// Deletes every category in order in a single write, nothing being written when one fails
+    async fn delete_categories(
+        &self,
+        deletions: &[CategoryDeletion],
+        strategy: DeleteStrategy,
+        caller: Option<&str>,
+    ) -> Result<Vec<Option<DeletedCategory>>, CoreError>;
 }
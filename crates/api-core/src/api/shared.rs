@@ -0,0 +1,237 @@
+//! Lets a single storage handle be shared between several consumers (schemas, wrappers,
+//! background tasks) without each of them owning a separate connection.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    Category, CategoryDeletion, CategoryFilter, CategoryOrder, CategoryPatch, CategoryRevision,
+    CategoryStats, CategoryTree, CategoryUpdate, DeleteStrategy, DeletedCategory, KeysetPage,
+    RestoredCategory,
+};
+
+use super::{CoreError, Debug, MutateCategories, QueryCategories, Uuid};
+
+impl<T: QueryCategories + Sync> QueryCategories for Arc<T> {
+    async fn get_categories(
+        &self,
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
+        (**self).get_categories().await
+    }
+
+    async fn get_sub_categories(
+        &self,
+        id: Option<&Uuid>,
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
+        (**self).get_sub_categories(id).await
+    }
+
+    async fn find_categories(
+        &self,
+        filter: &CategoryFilter,
+        order: Option<&CategoryOrder>,
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
+        (**self).find_categories(filter, order).await
+    }
+
+    async fn find_categories_page(
+        &self,
+        filter: &CategoryFilter,
+        order: &CategoryOrder,
+        page: &KeysetPage,
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
+        (**self).find_categories_page(filter, order, page).await
+    }
+
+    async fn count_categories(&self, filter: &CategoryFilter) -> Result<usize, CoreError> {
+        (**self).count_categories(filter).await
+    }
+
+    async fn search(
+        &self,
+        query: impl AsRef<str> + Send + Debug,
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
+        (**self).search(query).await
+    }
+
+    async fn get_category_by_id(&self, id: &Uuid) -> Result<Option<Category>, CoreError> {
+        (**self).get_category_by_id(id).await
+    }
+
+    async fn get_categories_by_ids(
+        &self,
+        ids: &[Uuid],
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
+        (**self).get_categories_by_ids(ids).await
+    }
+
+    async fn get_category_by_slug(&self, path: &str) -> Result<Option<Category>, CoreError> {
+        (**self).get_category_by_slug(path).await
+    }
+
+    async fn get_deleted_categories(
+        &self,
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
+        (**self).get_deleted_categories().await
+    }
+
+    async fn get_ancestors(
+        &self,
+        id: &Uuid,
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
+        (**self).get_ancestors(id).await
+    }
+
+    async fn get_category_tree(
+        &self,
+        id: Option<&Uuid>,
+        max_depth: usize,
+    ) -> Result<impl ExactSizeIterator<Item = CategoryTree> + Send, CoreError> {
+        (**self).get_category_tree(id, max_depth).await
+    }
+
+    async fn get_category_stats(
+        &self,
+        ids: &[Uuid],
+    ) -> Result<impl ExactSizeIterator<Item = CategoryStats> + Send, CoreError> {
+        (**self).get_category_stats(ids).await
+    }
+
+    async fn get_category_history(
+        &self,
+        id: &Uuid,
+    ) -> Result<impl ExactSizeIterator<Item = CategoryRevision> + Send, CoreError> {
+        (**self).get_category_history(id).await
+    }
+
+    async fn get_category_at(
+        &self,
+        id: &Uuid,
+        timestamp: &DateTime<Utc>,
+    ) -> Result<Option<Category>, CoreError> {
+        (**self).get_category_at(id, timestamp).await
+    }
+}
+
+impl<T: MutateCategories + Sync> MutateCategories for Arc<T> {
+    async fn create_category(
+        &self,
+        category: &Category,
+        caller: Option<&str>,
+    ) -> Result<Category, CoreError> {
+        (**self).create_category(category, caller).await
+    }
+
+    async fn update_category(
+        &self,
+        id: &Uuid,
+        data: &Category,
+        expected_version: Option<u64>,
+        caller: Option<&str>,
+    ) -> Result<Option<Category>, CoreError> {
+        (**self)
+            .update_category(id, data, expected_version, caller)
+            .await
+    }
+
+    async fn patch_category(
+        &self,
+        id: &Uuid,
+        patch: &CategoryPatch,
+        expected_version: Option<u64>,
+        caller: Option<&str>,
+    ) -> Result<Option<Category>, CoreError> {
+        (**self)
+            .patch_category(id, patch, expected_version, caller)
+            .await
+    }
+
+    async fn delete_category(
+        &self,
+        id: &Uuid,
+        strategy: DeleteStrategy,
+        expected_version: Option<u64>,
+        caller: Option<&str>,
+    ) -> Result<Option<DeletedCategory>, CoreError> {
+        (**self)
+            .delete_category(id, strategy, expected_version, caller)
+            .await
+    }
+
+    async fn move_category(
+        &self,
+        id: &Uuid,
+        parent: Option<&Uuid>,
+        position: Option<usize>,
+        expected_version: Option<u64>,
+        caller: Option<&str>,
+    ) -> Result<Option<Category>, CoreError> {
+        (**self)
+            .move_category(id, parent, position, expected_version, caller)
+            .await
+    }
+
+    async fn reorder_sub_categories(
+        &self,
+        parent: Option<&Uuid>,
+        ordered: &[Uuid],
+        caller: Option<&str>,
+    ) -> Result<Option<Vec<Category>>, CoreError> {
+        (**self)
+            .reorder_sub_categories(parent, ordered, caller)
+            .await
+    }
+
+    async fn adjust_listing_count(&self, id: &Uuid, delta: i64) -> Result<Option<u64>, CoreError> {
+        (**self).adjust_listing_count(id, delta).await
+    }
+
+    async fn restore_category(
+        &self,
+        id: &Uuid,
+        revision: u64,
+        caller: Option<&str>,
+    ) -> Result<Option<Category>, CoreError> {
+        (**self).restore_category(id, revision, caller).await
+    }
+
+    async fn undelete_category(
+        &self,
+        id: &Uuid,
+        caller: Option<&str>,
+    ) -> Result<Option<RestoredCategory>, CoreError> {
+        (**self).undelete_category(id, caller).await
+    }
+
+    async fn purge_category(&self, id: &Uuid) -> Result<Option<DeletedCategory>, CoreError> {
+        (**self).purge_category(id).await
+    }
+
+    async fn create_categories(
+        &self,
+        categories: &[Category],
+        caller: Option<&str>,
+    ) -> Result<Vec<Category>, CoreError> {
+        (**self).create_categories(categories, caller).await
+    }
+
+    async fn update_categories(
+        &self,
+        updates: &[CategoryUpdate],
+        caller: Option<&str>,
+    ) -> Result<Vec<Option<Category>>, CoreError> {
+        (**self).update_categories(updates, caller).await
+    }
+
+    async fn delete_categories(
+        &self,
+        deletions: &[CategoryDeletion],
+        strategy: DeleteStrategy,
+        caller: Option<&str>,
+    ) -> Result<Vec<Option<DeletedCategory>>, CoreError> {
+        (**self)
+            .delete_categories(deletions, strategy, caller)
+            .await
+    }
+}
@@ -0,0 +1,304 @@
+use std::{collections::HashMap, sync::Arc};
+
+use async_graphql::{
+    dataloader::{DataLoader, Loader},
+    ComplexObject, Context,
+};
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::{
+    api::{CoreError, QueryCategories},
+    Category, CategoryStats,
+};
+
+/// Object safe access to storage for the field resolvers on [`Category`], which cannot know
+/// the storage type a schema was built with
+#[async_trait]
+pub trait CategoryResolver: Send + Sync {
+    async fn ancestors(&self, id: &Uuid) -> Result<Vec<Category>, CoreError>;
+    async fn stats(&self, ids: &[Uuid]) -> Result<Vec<CategoryStats>, CoreError>;
+    async fn categories(&self, ids: &[Uuid]) -> Result<Vec<Category>, CoreError>;
+}
+
+#[async_trait]
+impl<T: QueryCategories + Sync> CategoryResolver for T {
+    async fn ancestors(&self, id: &Uuid) -> Result<Vec<Category>, CoreError> {
+        Ok(self.get_ancestors(id).await?.collect())
+    }
+
+    async fn stats(&self, ids: &[Uuid]) -> Result<Vec<CategoryStats>, CoreError> {
+        Ok(self.get_category_stats(ids).await?.collect())
+    }
+
+    async fn categories(&self, ids: &[Uuid]) -> Result<Vec<Category>, CoreError> {
+        Ok(self.get_categories_by_ids(ids).await?.collect())
+    }
+}
+
+/// Loads categories by ID with a single storage call per response
+pub struct CategoryLoader(Arc<dyn CategoryResolver>);
+
+impl CategoryLoader {
+    pub fn new(resolver: Arc<dyn CategoryResolver>) -> Self {
+        Self(resolver)
+    }
+}
+
+impl Loader<Uuid> for CategoryLoader {
+    type Value = Category;
+    type Error = Arc<CoreError>;
+
+    async fn load(&self, keys: &[Uuid]) -> Result<HashMap<Uuid, Self::Value>, Self::Error> {
+        Ok(self
+            .0
+            .categories(keys)
+            .await?
+            .into_iter()
+            .map(|category| (category.id, category))
+            .collect())
+    }
+}
+
+/// Loads the subcategories of each parent ID, in sibling order
+pub struct SubCategoryLoader(Arc<dyn CategoryResolver>);
+
+impl SubCategoryLoader {
+    pub fn new(resolver: Arc<dyn CategoryResolver>) -> Self {
+        Self(resolver)
+    }
+}
+
+impl Loader<Uuid> for SubCategoryLoader {
+    type Value = Vec<Category>;
+    type Error = Arc<CoreError>;
+
+    async fn load(&self, keys: &[Uuid]) -> Result<HashMap<Uuid, Self::Value>, Self::Error> {
+        let parents = self.0.categories(keys).await?;
+        let ids: Vec<_> = parents
+            .iter()
+            .flat_map(|parent| parent.sub_categories.iter().copied())
+            .collect();
+        let mut children: HashMap<_, _> = self
+            .0
+            .categories(&ids)
+            .await?
+            .into_iter()
+            .map(|category| (category.id, category))
+            .collect();
+
+        Ok(parents
+            .into_iter()
+            .map(|parent| {
+                let sub_categories = parent
+                    .sub_categories
+                    .iter()
+                    .filter_map(|id| children.remove(id))
+                    .collect();
+                (parent.id, sub_categories)
+            })
+            .collect())
+    }
+}
+
+/// Loads the [`CategoryStats`] of every category in a response with a single storage call
+pub struct CategoryStatsLoader(Arc<dyn CategoryResolver>);
+
+impl CategoryStatsLoader {
+    pub fn new(resolver: Arc<dyn CategoryResolver>) -> Self {
+        Self(resolver)
+    }
+}
+
+impl Loader<Uuid> for CategoryStatsLoader {
+    type Value = CategoryStats;
+    type Error = Arc<CoreError>;
+
+    async fn load(&self, keys: &[Uuid]) -> Result<HashMap<Uuid, Self::Value>, Self::Error> {
+        Ok(self
+            .0
+            .stats(keys)
+            .await?
+            .into_iter()
+            .map(|stats| (stats.id, stats))
+            .collect())
+    }
+}
+
+/// Locales the client prefers, most preferred first, used to resolve translated fields
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Locales(pub Vec<String>);
+
+impl Locales {
+    /// Reads an `Accept-Language` header, ordering the locales by weight
+    pub fn from_accept_language(header: &str) -> Self {
+        let mut weighted: Vec<_> = header
+            .split(',')
+            .filter_map(|item| {
+                let mut parts = item.split(';');
+                let locale = parts.next()?.trim();
+                let weight = parts
+                    .filter_map(|param| param.trim().strip_prefix("q="))
+                    .find_map(|weight| weight.trim().parse::<f32>().ok())
+                    .unwrap_or(1.0);
+
+                (!locale.is_empty() && locale != "*" && weight > 0.0).then_some((locale, weight))
+            })
+            .collect();
+        // stable, so locales of equal weight keep the order they were sent in
+        weighted.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+        Self(
+            weighted
+                .into_iter()
+                .map(|(locale, _)| locale.to_owned())
+                .collect(),
+        )
+    }
+}
+
+/// The `locale` argument when given, the request's [`Locales`] otherwise
+fn preferred_locales(ctx: &Context, locale: Option<String>) -> Vec<String> {
+    match locale {
+        Some(locale) => vec![locale],
+        None => ctx
+            .data_opt::<Locales>()
+            .map(|locales| locales.0.clone())
+            .unwrap_or_default(),
+    }
+}
+
+fn extract_resolver<'a>(ctx: &'a Context) -> async_graphql::Result<&'a dyn CategoryResolver> {
+    Ok(ctx.data::<Arc<dyn CategoryResolver>>()?.as_ref())
+}
+
+impl Category {
+    /// Categories that are not stored count as empty top level ones
+    async fn stats(&self, ctx: &Context<'_>) -> async_graphql::Result<CategoryStats> {
+        let loader = ctx.data::<DataLoader<CategoryStatsLoader>>()?;
+
+        Ok(loader.load_one(self.id).await?.unwrap_or_default())
+    }
+}
+
+#[ComplexObject]
+impl Category {
+    /// Category name in `locale`, or in the best match for the request's `Accept-Language`
+    async fn name(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(validator(max_length = 35))] locale: Option<String>,
+    ) -> String {
+        self.localised_name(&preferred_locales(ctx, locale))
+            .to_owned()
+    }
+
+    /// What the category holds, resolved like `name`
+    async fn description(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(validator(max_length = 35))] locale: Option<String>,
+    ) -> Option<String> {
+        self.localised_description(&preferred_locales(ctx, locale))
+            .map(ToOwned::to_owned)
+    }
+
+    /// The category this one sits under, null for top level categories
+    async fn parent(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<Category>> {
+        let Some(parent_id) = self.parent_id else {
+            return Ok(None);
+        };
+        let loader = ctx.data::<DataLoader<CategoryLoader>>()?;
+
+        Ok(loader.load_one(parent_id).await?)
+    }
+
+    /// IDs of the subcategories of the current item, in sibling order
+    async fn sub_category_ids(&self) -> &[Uuid] {
+        &self.sub_categories
+    }
+
+    /// The categories listed in `subCategoryIds`
+    async fn sub_categories(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<Category>> {
+        let loader = ctx.data::<DataLoader<CategoryLoader>>()?;
+        let mut loaded = loader
+            .load_many(self.sub_categories.iter().copied())
+            .await?;
+
+        Ok(self
+            .sub_categories
+            .iter()
+            .filter_map(|id| loaded.remove(id))
+            .collect())
+    }
+
+    /// The subcategories currently stored under this category, in sibling order
+    async fn children(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<Category>> {
+        let loader = ctx.data::<DataLoader<SubCategoryLoader>>()?;
+
+        Ok(loader.load_one(self.id).await?.unwrap_or_default())
+    }
+
+    /// Categories above the current one, from the top level down to its direct parent
+    async fn ancestors(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<Category>> {
+        let resolver = extract_resolver(ctx)?;
+
+        Ok(resolver.ancestors(&self.id).await?)
+    }
+
+    /// Names of the ancestors and the current category, joined by `separator`
+    async fn breadcrumb(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = " > ", validator(max_length = 10))] separator: String,
+        #[graphql(validator(max_length = 35))] locale: Option<String>,
+    ) -> async_graphql::Result<String> {
+        let resolver = extract_resolver(ctx)?;
+        let locales = preferred_locales(ctx, locale);
+
+        let mut names: Vec<_> = resolver
+            .ancestors(&self.id)
+            .await?
+            .iter()
+            .map(|category| category.localised_name(&locales).to_owned())
+            .collect();
+        names.push(self.localised_name(&locales).to_owned());
+
+        Ok(names.join(&separator))
+    }
+
+    /// Number of direct subcategories
+    async fn child_count(&self, ctx: &Context<'_>) -> async_graphql::Result<usize> {
+        Ok(self.stats(ctx).await?.child_count)
+    }
+
+    /// Number of categories anywhere below this one
+    async fn descendant_count(&self, ctx: &Context<'_>) -> async_graphql::Result<usize> {
+        Ok(self.stats(ctx).await?.descendant_count)
+    }
+
+    /// Whether the category has no subcategories
+    async fn is_leaf(&self, ctx: &Context<'_>) -> async_graphql::Result<bool> {
+        Ok(self.stats(ctx).await?.child_count == 0)
+    }
+
+    /// Number of ancestors, zero for top level categories
+    async fn depth(&self, ctx: &Context<'_>) -> async_graphql::Result<usize> {
+        Ok(self.stats(ctx).await?.depth)
+    }
+
+    /// Listings filed under the category, its descendants included by default
+    async fn listing_count(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = true)] include_descendants: bool,
+    ) -> async_graphql::Result<u64> {
+        let stats = self.stats(ctx).await?;
+
+        Ok(if include_descendants {
+            stats.total_listing_count
+        } else {
+            stats.listing_count
+        })
+    }
+}
@@ -1,32 +1,574 @@
 pub mod api;
+#[cfg(feature = "async-graphql")]
+pub mod graphql;
+#[cfg(feature = "taxonomy")]
+pub mod taxonomy;
 
 #[cfg(feature = "async-graphql")]
 use async_graphql::*;
 
+use std::cmp::Ordering;
+
+use api::CoreError;
+use chrono::{DateTime, Utc};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-#[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Clone)]
+#[derive(Debug, Default, PartialEq, PartialOrd, Ord, Eq, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "async-graphql", derive(InputObject, SimpleObject))]
-#[cfg_attr(feature = "async-graphql", graphql(input_name = "CategoryInput"))]
+#[cfg_attr(
+    feature = "async-graphql",
+    graphql(input_name = "CategoryInput", complex)
+)]
 pub struct Category {
     /// Category ID
     #[cfg_attr(feature = "async-graphql", graphql(skip_input))]
     pub id: Uuid,
-    /// Category name
+    /// Category name, in the default locale
+    #[cfg_attr(feature = "async-graphql", graphql(skip_output))]
     pub name: String,
-    /// A list of IDs that are subcategories for the current item
+    /// What the category holds, in the default locale
+    #[cfg_attr(feature = "serde", serde(default))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip_output))]
+    pub description: Option<String>,
+    /// The name and description in other locales
+    #[cfg_attr(feature = "serde", serde(default))]
     #[cfg_attr(feature = "async-graphql", graphql(default))]
+    pub translations: Vec<Translation>,
+    /// URL friendly form of `name`, unique among siblings
+    #[cfg_attr(feature = "serde", serde(default))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip_input))]
+    pub slug: String,
+    /// A list of IDs that are subcategories for the current item
+    #[cfg_attr(feature = "async-graphql", graphql(default, skip_output))]
     pub sub_categories: Vec<Uuid>,
     /// An image representing the current ID
     pub image_url: Option<String>,
+    /// Attributes listings in this category fill in, on top of the inherited ones
+    #[cfg_attr(feature = "serde", serde(default))]
+    #[cfg_attr(feature = "async-graphql", graphql(default))]
+    pub attributes: Vec<AttributeDefinition>,
     /// Id of this category's parent
     pub parent_id: Option<Uuid>,
+    /// Where the category sits among its siblings, lowest first
+    #[cfg_attr(feature = "serde", serde(default))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip_input))]
+    pub position: u32,
+    /// Incremented on every change to the category, used to detect concurrent edits
+    #[cfg_attr(feature = "serde", serde(default))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip_input))]
+    pub version: u64,
+    /// When the category was created
+    #[cfg_attr(feature = "serde", serde(default))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip_input))]
+    pub created_at: Option<DateTime<Utc>>,
+    /// When the category was last changed
+    #[cfg_attr(feature = "serde", serde(default))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip_input))]
+    pub updated_at: Option<DateTime<Utc>>,
+    /// Who created the category
+    #[cfg_attr(feature = "serde", serde(default))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip_input))]
+    pub created_by: Option<String>,
+    /// Who last changed the category
+    #[cfg_attr(feature = "serde", serde(default))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip_input))]
+    pub updated_by: Option<String>,
+    /// When the category was moved to the trash
+    #[cfg_attr(feature = "serde", serde(default))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip_input))]
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+impl Category {
+    /// The translation best matching `locales`, so `fr-CA` falls back to `fr`
+    pub fn translation(&self, locales: &[impl AsRef<str>]) -> Option<&Translation> {
+        locales.iter().find_map(|locale| {
+            let locale = locale.as_ref();
+            let language = locale.split('-').next().unwrap_or(locale);
+
+            self.translations
+                .iter()
+                .find(|translation| translation.locale.eq_ignore_ascii_case(locale))
+                .or_else(|| {
+                    self.translations
+                        .iter()
+                        .find(|translation| translation.locale.eq_ignore_ascii_case(language))
+                })
+        })
+    }
+
+    /// The name in the best matching locale, or in the default one when none is translated
+    pub fn localised_name(&self, locales: &[impl AsRef<str>]) -> &str {
+        self.translation(locales)
+            .map_or(&self.name, |translation| &translation.name)
+    }
+
+    /// The description in the best matching locale, or in the default one
+    pub fn localised_description(&self, locales: &[impl AsRef<str>]) -> Option<&str> {
+        self.translation(locales)
+            .and_then(|translation| translation.description.as_deref())
+            .or(self.description.as_deref())
+    }
+
+    /// The attributes listings in the category fill in, given its `ancestors` from the top down
+    pub fn effective_attributes<'a>(
+        &'a self,
+        ancestors: impl IntoIterator<Item = &'a Category>,
+    ) -> Vec<AttributeDefinition> {
+        let mut attributes: Vec<AttributeDefinition> = Vec::new();
+        for category in ancestors.into_iter().chain(std::iter::once(self)) {
+            for attribute in category.attributes.iter() {
+                match attributes
+                    .iter_mut()
+                    .find(|inherited| inherited.key == attribute.key)
+                {
+                    Some(inherited) => inherited.clone_from(attribute),
+                    None => attributes.push(attribute.to_owned()),
+                }
+            }
+        }
+
+        attributes
+    }
+}
+
+/// The kind of value a listing attribute holds
+#[derive(Debug, Default, PartialEq, PartialOrd, Ord, Eq, Clone, Copy)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "snake_case")
+)]
+#[cfg_attr(feature = "async-graphql", derive(Enum))]
+pub enum AttributeKind {
+    #[default]
+    Text,
+    Number,
+    Boolean,
+    /// One of the allowed values
+    Select,
+    /// Any number of the allowed values
+    MultiSelect,
+}
+
+/// An attribute listings in a category fill in, such as a size or a brand
+#[derive(Debug, Default, PartialEq, PartialOrd, Ord, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "async-graphql", derive(InputObject, SimpleObject))]
+#[cfg_attr(
+    feature = "async-graphql",
+    graphql(input_name = "AttributeDefinitionInput")
+)]
+pub struct AttributeDefinition {
+    /// Identifies the attribute within the category and its descendants
+    pub key: String,
+    /// What the attribute holds
+    pub kind: AttributeKind,
+    /// Whether a listing has to fill the attribute in
+    #[cfg_attr(feature = "serde", serde(default))]
+    #[cfg_attr(feature = "async-graphql", graphql(default))]
+    pub required: bool,
+    /// Values a `SELECT` or `MULTI_SELECT` attribute can take
+    #[cfg_attr(feature = "serde", serde(default))]
+    #[cfg_attr(feature = "async-graphql", graphql(default))]
+    pub allowed_values: Vec<String>,
+}
+
+/// Checks that attribute keys are set and unique, and that only selections take allowed values
+pub fn validate_attributes(attributes: &[AttributeDefinition]) -> Result<(), CoreError> {
+    let invalid = |attribute: &AttributeDefinition, reason: String| CoreError::InvalidAttribute {
+        key: attribute.key.to_owned(),
+        reason,
+    };
+
+    for (index, attribute) in attributes.iter().enumerate() {
+        if attribute.key.trim().is_empty() {
+            return Err(invalid(attribute, "the key cannot be empty".into()));
+        }
+        if attributes[..index]
+            .iter()
+            .any(|other| other.key == attribute.key)
+        {
+            return Err(invalid(
+                attribute,
+                "the key is defined more than once".into(),
+            ));
+        }
+
+        match attribute.kind {
+            AttributeKind::Select | AttributeKind::MultiSelect => {
+                if attribute.allowed_values.is_empty() {
+                    return Err(invalid(
+                        attribute,
+                        "a selection needs allowed values".into(),
+                    ));
+                }
+                for (index, value) in attribute.allowed_values.iter().enumerate() {
+                    if attribute.allowed_values[..index].contains(value) {
+                        return Err(invalid(
+                            attribute,
+                            format!("`{value}` is allowed more than once"),
+                        ));
+                    }
+                }
+            }
+            _ if !attribute.allowed_values.is_empty() => {
+                return Err(invalid(
+                    attribute,
+                    "only selections take allowed values".into(),
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// A category's name and description in one locale
+#[derive(Debug, Default, PartialEq, PartialOrd, Ord, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "async-graphql", derive(InputObject, SimpleObject))]
+#[cfg_attr(feature = "async-graphql", graphql(input_name = "TranslationInput"))]
+pub struct Translation {
+    /// Language tag of the locale, as in `fr` or `pt-BR`
+    pub locale: String,
+    /// Category name in the locale
+    pub name: String,
+    /// What the category holds, in the locale
+    pub description: Option<String>,
+}
+
+/// Turns a category name into lowercase letters and digits separated by `-`
+pub fn slugify(name: &str) -> String {
+    let mut slug = String::with_capacity(name.len());
+    for c in name.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    if slug.ends_with('-') {
+        slug.pop();
+    }
+
+    slug
+}
+
+/// A partial update to a category. Fields left as `None` keep their current value
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "async-graphql", derive(InputObject))]
+pub struct CategoryPatch {
+    /// New category name
+    pub name: Option<String>,
+    /// New description
+    pub description: Option<String>,
+    /// Replaces every translation of the category
+    pub translations: Option<Vec<Translation>>,
+    /// New image representing the category
+    pub image_url: Option<String>,
+    /// Replaces every attribute the category defines
+    pub attributes: Option<Vec<AttributeDefinition>>,
+}
+
+/// One entry of a batch update
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "async-graphql", derive(InputObject))]
+pub struct CategoryUpdate {
+    /// Category to update
+    pub id: Uuid,
+    /// New content of the category
+    pub category: Category,
+    /// Rejects the update when set and the category has changed since
+    pub expected_version: Option<u64>,
+}
+
+/// One entry of a batch delete
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "async-graphql", derive(InputObject))]
+pub struct CategoryDeletion {
+    /// Category to delete
+    pub id: Uuid,
+    /// Rejects the delete when set and the category has changed since
+    pub expected_version: Option<u64>,
+}
+
+/// Narrows down listed categories. A category has to match every field that is set
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "async-graphql", derive(InputObject))]
+pub struct CategoryFilter {
+    /// Part of the name in the default locale, ignoring case
+    pub name_contains: Option<String>,
+    /// Keeps the categories with a parent, or the top level ones when `false`
+    pub has_parent: Option<bool>,
+    /// Keeps the categories without subcategories, or the ones with some when `false`
+    pub is_leaf: Option<bool>,
+    /// Keeps the categories this many levels below the top level, which is at depth 0
+    pub depth: Option<u8>,
+    /// Keeps the categories with one of these IDs
+    pub ids: Option<Vec<Uuid>>,
+    /// Lists the categories in the trash along with the others
+    #[cfg_attr(feature = "serde", serde(default))]
+    #[cfg_attr(feature = "async-graphql", graphql(skip))]
+    pub include_deleted: bool,
+}
+
+impl CategoryFilter {
+    /// Whether `category`, `depth` levels below the top level, passes the filter
+    pub fn matches(&self, category: &Category, depth: usize) -> bool {
+        (self.include_deleted || category.deleted_at.is_none())
+            && self.name_contains.as_ref().map_or(true, |name| {
+                category.name.to_lowercase().contains(&name.to_lowercase())
+            })
+            && self.has_parent.map_or(true, |has_parent| {
+                category.parent_id.is_some() == has_parent
+            })
+            && self.is_leaf.map_or(true, |is_leaf| {
+                category.sub_categories.is_empty() == is_leaf
+            })
+            && self
+                .depth
+                .map_or(true, |expected| usize::from(expected) == depth)
+            && self
+                .ids
+                .as_ref()
+                .map_or(true, |ids| ids.contains(&category.id))
+    }
+}
+
+/// Fields categories can be sorted by
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "async-graphql", derive(Enum))]
+pub enum CategoryOrderField {
+    /// Name in the default locale
+    Name,
+    CreatedAt,
+    UpdatedAt,
+    /// Position among siblings
+    Position,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "async-graphql", derive(Enum))]
+pub enum OrderDirection {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// How listed categories are sorted, ties broken by ID
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "async-graphql", derive(InputObject))]
+pub struct CategoryOrder {
+    pub field: CategoryOrderField,
+    #[cfg_attr(feature = "serde", serde(default))]
+    #[cfg_attr(feature = "async-graphql", graphql(default))]
+    pub direction: OrderDirection,
+}
+
+impl CategoryOrder {
+    /// Where `category` sits when sorted by `field`
+    pub fn key(&self, category: &Category) -> SortKey {
+        let value = match self.field {
+            CategoryOrderField::Name => SortValue::Name(category.name.to_owned()),
+            CategoryOrderField::CreatedAt => SortValue::CreatedAt(category.created_at),
+            CategoryOrderField::UpdatedAt => SortValue::UpdatedAt(category.updated_at),
+            CategoryOrderField::Position => SortValue::Position(category.position),
+        };
+
+        SortKey {
+            value,
+            id: category.id,
+        }
+    }
+
+    /// Compares two keys in the order categories are sorted in
+    pub fn compare(&self, a: &SortKey, b: &SortKey) -> Ordering {
+        match self.direction {
+            OrderDirection::Asc => a.cmp(b),
+            OrderDirection::Desc => b.cmp(a),
+        }
+    }
+
+    pub fn sort(&self, categories: &mut [Category]) {
+        categories.sort_by_cached_key(|category| self.key(category));
+        if self.direction == OrderDirection::Desc {
+            categories.reverse();
+        }
+    }
+}
+
+/// A category's value for the field categories are sorted by
+#[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum SortValue {
+    Name(String),
+    CreatedAt(Option<DateTime<Utc>>),
+    UpdatedAt(Option<DateTime<Utc>>),
+    Position(u32),
+}
+
+impl SortValue {
+    /// The field the value was taken from
+    pub fn field(&self) -> CategoryOrderField {
+        match self {
+            SortValue::Name(_) => CategoryOrderField::Name,
+            SortValue::CreatedAt(_) => CategoryOrderField::CreatedAt,
+            SortValue::UpdatedAt(_) => CategoryOrderField::UpdatedAt,
+            SortValue::Position(_) => CategoryOrderField::Position,
+        }
+    }
+}
+
+/// Where a category sits in a sorted listing: its value for the sorted field, then its ID
+#[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SortKey {
+    pub value: SortValue,
+    pub id: Uuid,
+}
+
+/// One page of a sorted listing, bounded by the keys of the categories around it
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct KeysetPage {
+    /// Keeps the categories sorted after this key
+    pub after: Option<SortKey>,
+    /// Keeps the categories sorted before this key
+    pub before: Option<SortKey>,
+    /// How many categories the page holds at most
+    pub limit: usize,
+    /// Fills the page from `before`, or from the end, instead of from `after`
+    pub from_end: bool,
+}
+
+impl KeysetPage {
+    /// Whether a category at `key` falls between the bounds of the page when sorted by `order`
+    pub fn contains(&self, order: &CategoryOrder, key: &SortKey) -> bool {
+        self.after
+            .as_ref()
+            .map_or(true, |after| order.compare(key, after) == Ordering::Greater)
+            && self
+                .before
+                .as_ref()
+                .map_or(true, |before| order.compare(key, before) == Ordering::Less)
+    }
+}
+
+/// A category along with its nested subcategories
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "async-graphql", derive(SimpleObject))]
+pub struct CategoryTree {
+    /// Category at this position in the tree
+    pub category: Category,
+    /// Subcategories of the current node
+    pub children: Vec<CategoryTree>,
+}
+
+/// Where a category sits in the hierarchy and how many listings it holds
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CategoryStats {
+    /// Category the figures are for
+    pub id: Uuid,
+    /// Number of ancestors, zero for top level categories
+    pub depth: usize,
+    /// Number of direct subcategories
+    pub child_count: usize,
+    /// Number of categories anywhere below this one
+    pub descendant_count: usize,
+    /// Listings filed directly under the category
+    pub listing_count: u64,
+    /// Listings filed under the category or any of its descendants
+    pub total_listing_count: u64,
+}
+
+/// What happens to the subcategories of a deleted category
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "async-graphql", derive(Enum))]
+pub enum DeleteStrategy {
+    /// Refuse to delete a category that still has subcategories
+    #[default]
+    Restrict,
+    /// Delete the category along with every descendant
+    Cascade,
+    /// Move the subcategories to the deleted category's parent
+    Reparent,
+}
+
+/// A deleted (or purged) category and the other categories affected by the deletion
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DeletedCategory {
+    /// The category that was deleted
+    pub category: Category,
+    /// IDs of the descendants deleted (or purged) along with it
+    pub deleted: Vec<Uuid>,
+    /// IDs of the subcategories that were moved to a new parent
+    pub reparented: Vec<Uuid>,
+}
+
+/// A category taken out of the trash and the descendants restored along with it
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RestoredCategory {
+    /// The category that was restored
+    pub category: Category,
+    /// IDs of the descendants restored along with it
+    pub restored: Vec<Uuid>,
+}
+
+/// The kind of change recorded by a [`CategoryRevision`]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "lowercase")
+)]
+#[cfg_attr(feature = "async-graphql", derive(Enum))]
+pub enum RevisionKind {
+    Created,
+    Updated,
+    Deleted,
+    /// The category was brought back to an earlier revision
+    Restored,
+}
+
+/// A snapshot of a category taken whenever it changes
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "async-graphql", derive(SimpleObject))]
+pub struct CategoryRevision {
+    /// Sequence number of the change, the category's `version` once the change is applied
+    pub revision: u64,
+    /// What happened to the category
+    pub kind: RevisionKind,
+    /// The category after the change, or right before it for deletions
+    pub category: Category,
+    /// When the change was made
+    pub recorded_at: DateTime<Utc>,
+    /// Who made the change
+    pub recorded_by: Option<String>,
 }
 
 pub mod reexports {
+    pub use chrono;
     pub use uuid;
 }
 
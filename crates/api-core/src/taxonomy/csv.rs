@@ -0,0 +1,55 @@
+use std::io::{Read, Write};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::Category;
+
+use super::{Forest, TaxonomyError};
+
+/// Empty cells are read as `None`
+#[derive(Serialize, Deserialize)]
+struct Row {
+    id: Option<Uuid>,
+    name: String,
+    parent_id: Option<Uuid>,
+    image_url: Option<String>,
+}
+
+pub(super) fn read(reader: impl Read) -> Result<Vec<Category>, TaxonomyError> {
+    let mut reader = ::csv::ReaderBuilder::new()
+        .trim(::csv::Trim::All)
+        .from_reader(reader);
+
+    reader
+        .deserialize()
+        .map(|row| {
+            let row: Row = row?;
+
+            Ok(Category {
+                id: row.id.unwrap_or_else(Uuid::now_v7),
+                name: row.name,
+                image_url: row.image_url,
+                parent_id: row.parent_id,
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+/// Parents are written before their subcategories
+pub(super) fn write(forest: &Forest, writer: impl Write) -> Result<(), TaxonomyError> {
+    let mut writer = ::csv::Writer::from_writer(writer);
+
+    for category in forest.walk() {
+        writer.serialize(Row {
+            id: Some(category.id),
+            name: category.name.to_owned(),
+            parent_id: category.parent_id,
+            image_url: category.image_url.to_owned(),
+        })?;
+    }
+    writer.flush()?;
+
+    Ok(())
+}
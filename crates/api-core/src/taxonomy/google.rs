@@ -0,0 +1,76 @@
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Read, Write},
+};
+
+use uuid::Uuid;
+
+use crate::Category;
+
+use super::{Forest, TaxonomyError, SEPARATOR};
+
+/// Every category on a path is created once. Lines starting with `#` are comments
+pub(super) fn read(reader: impl Read) -> Result<Vec<Category>, TaxonomyError> {
+    let mut categories = Vec::new();
+    let mut paths: HashMap<String, Uuid> = HashMap::new();
+
+    for (index, line) in BufReader::new(reader).lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        // the variant of the taxonomy with IDs starts every line with `<number> - `
+        let line = match line.split_once(" - ") {
+            Some((number, path))
+                if !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()) =>
+            {
+                path
+            }
+            _ => line,
+        };
+
+        let mut path = String::with_capacity(line.len());
+        let mut parent_id = None;
+        // names may hold a `>` of their own, one left at either end is a doubled separator
+        for name in line.split(SEPARATOR).map(str::trim) {
+            if name.is_empty() || name.starts_with('>') || name.ends_with('>') {
+                return Err(TaxonomyError::Invalid(format!(
+                    "line {} has an empty category name",
+                    index + 1
+                )));
+            }
+            if !path.is_empty() {
+                path.push_str(SEPARATOR);
+            }
+            path.push_str(name);
+
+            let id = match paths.get(&path) {
+                Some(id) => *id,
+                None => {
+                    let id = Uuid::now_v7();
+                    paths.insert(path.clone(), id);
+                    categories.push(Category {
+                        id,
+                        name: name.to_owned(),
+                        parent_id,
+                        ..Default::default()
+                    });
+                    id
+                }
+            };
+            parent_id = Some(id);
+        }
+    }
+
+    Ok(categories)
+}
+
+/// Writes one line per category. IDs and images are left out
+pub(super) fn write(forest: &Forest, mut writer: impl Write) -> Result<(), TaxonomyError> {
+    for (_, path) in forest.paths() {
+        writeln!(writer, "{path}")?;
+    }
+
+    Ok(())
+}
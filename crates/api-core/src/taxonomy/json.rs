@@ -0,0 +1,65 @@
+use std::io::{Read, Write};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::Category;
+
+use super::{Forest, TaxonomyError};
+
+/// A category along with its nested subcategories
+#[derive(Serialize, Deserialize)]
+struct Node {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<Uuid>,
+    name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    image_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    children: Vec<Node>,
+}
+
+pub(super) fn read(reader: impl Read) -> Result<Vec<Category>, TaxonomyError> {
+    let nodes: Vec<Node> = serde_json::from_reader(reader)?;
+
+    let mut categories = Vec::new();
+    let mut pending: Vec<_> = nodes.into_iter().rev().map(|node| (None, node)).collect();
+    while let Some((parent_id, node)) = pending.pop() {
+        let id = node.id.unwrap_or_else(Uuid::now_v7);
+        pending.extend(
+            node.children
+                .into_iter()
+                .rev()
+                .map(|child| (Some(id), child)),
+        );
+        categories.push(Category {
+            id,
+            name: node.name,
+            image_url: node.image_url,
+            parent_id,
+            ..Default::default()
+        });
+    }
+
+    Ok(categories)
+}
+
+pub(super) fn write(forest: &Forest, writer: impl Write) -> Result<(), TaxonomyError> {
+    let nodes: Vec<_> = forest.roots.iter().map(|root| node(forest, root)).collect();
+    serde_json::to_writer_pretty(writer, &nodes)?;
+
+    Ok(())
+}
+
+fn node(forest: &Forest, category: &Category) -> Node {
+    Node {
+        id: Some(category.id),
+        name: category.name.to_owned(),
+        image_url: category.image_url.to_owned(),
+        children: forest
+            .children(category)
+            .iter()
+            .map(|child| node(forest, child))
+            .collect(),
+    }
+}
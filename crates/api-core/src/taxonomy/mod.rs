@@ -0,0 +1,344 @@
+//! Reads and writes the whole category tree in the formats taxonomies are usually exchanged in,
+//! and imports them through [`MutateCategories`].
+//!
+//! Every format is read into a flat list of categories with their `parent_id` and
+//! `sub_categories` linked. Categories without an ID in the source get a new one.
+
+mod csv;
+mod google;
+mod json;
+
+use std::{
+    collections::{HashMap, HashSet},
+    io::{Read, Write},
+    str::FromStr,
+};
+
+use serde::Serialize;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::{
+    api::{CoreError, MutateCategories, QueryCategories},
+    Category, CategoryUpdate,
+};
+
+/// The formats a taxonomy can be read from and written to
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Format {
+    /// Categories nested under their parent's `children`
+    #[default]
+    Json,
+    /// One category per row, with `id,name,parent_id,image_url` columns
+    Csv,
+    /// Google's product taxonomy, one `A > B > C` path per line
+    Google,
+}
+
+impl FromStr for Format {
+    type Err = TaxonomyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            "google" => Ok(Self::Google),
+            _ => Err(TaxonomyError::UnknownFormat(s.to_owned())),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum TaxonomyError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Csv(#[from] ::csv::Error),
+    #[error("invalid taxonomy: {0}")]
+    Invalid(String),
+    #[error("unknown taxonomy format `{0}`")]
+    UnknownFormat(String),
+    #[error(transparent)]
+    Core(#[from] CoreError),
+}
+
+/// What an import changes, or would change for a dry run
+#[derive(Debug, Default, PartialEq, Eq, Clone, Serialize)]
+pub struct ImportDiff {
+    /// Categories that are not stored yet
+    pub created: Vec<Category>,
+    /// Stored categories that differ from the import, before and after the change
+    pub updated: Vec<(Category, Category)>,
+    /// Stored categories the import leaves as they are
+    pub unchanged: Vec<Uuid>,
+}
+
+/// Reads a taxonomy in the given format
+pub fn read(format: Format, reader: impl Read) -> Result<Vec<Category>, TaxonomyError> {
+    let mut categories = match format {
+        Format::Json => json::read(reader)?,
+        Format::Csv => csv::read(reader)?,
+        Format::Google => google::read(reader)?,
+    };
+
+    let mut ids = HashSet::with_capacity(categories.len());
+    for category in categories.iter() {
+        if category.name.trim().is_empty() {
+            return Err(TaxonomyError::Invalid(format!(
+                "category {} has no name",
+                category.id
+            )));
+        }
+        if !ids.insert(category.id) {
+            return Err(TaxonomyError::Invalid(format!(
+                "category {} appears more than once",
+                category.id
+            )));
+        }
+    }
+    if Forest::new(&categories).walk().len() != categories.len() {
+        return Err(TaxonomyError::Invalid(
+            "some categories are their own ancestors".into(),
+        ));
+    }
+    link(&mut categories);
+
+    Ok(categories)
+}
+
+/// Writes `categories` in the given format
+pub fn write(
+    format: Format,
+    categories: &[Category],
+    writer: impl Write,
+) -> Result<(), TaxonomyError> {
+    let forest = Forest::new(categories);
+
+    match format {
+        Format::Json => json::write(&forest, writer),
+        Format::Csv => csv::write(&forest, writer),
+        Format::Google => google::write(&forest, writer),
+    }
+}
+
+/// Writes every stored category in the given format
+pub async fn export<D: QueryCategories>(
+    database: &D,
+    format: Format,
+    writer: impl Write,
+) -> Result<(), TaxonomyError> {
+    let categories: Vec<_> = database.get_categories().await?.collect();
+
+    write(format, &categories, writer)
+}
+
+/// Creates the imported categories that are not stored yet and updates the ones that changed.
+/// Nothing is written when `dry_run` is set
+pub async fn import<D: QueryCategories + MutateCategories>(
+    database: &D,
+    categories: &[Category],
+    dry_run: bool,
+    caller: Option<&str>,
+) -> Result<ImportDiff, TaxonomyError> {
+    let stored: Vec<_> = database.get_categories().await?.collect();
+    let mut diff = diff(&stored, categories);
+    if dry_run {
+        return Ok(diff);
+    }
+
+    if !diff.created.is_empty() {
+        diff.created = database.create_categories(&diff.created, caller).await?;
+    }
+    if !diff.updated.is_empty() {
+        let updates: Vec<_> = diff
+            .updated
+            .iter()
+            .map(|(before, after)| CategoryUpdate {
+                id: before.id,
+                category: after.to_owned(),
+                expected_version: Some(before.version),
+            })
+            .collect();
+
+        let updated = database.update_categories(&updates, caller).await?;
+        for ((_, after), updated) in diff.updated.iter_mut().zip(updated) {
+            if let Some(updated) = updated {
+                *after = updated;
+            }
+        }
+    }
+
+    Ok(diff)
+}
+
+/// Compares imported categories to the stored ones, matching by ID or else by path of names
+pub fn diff(stored: &[Category], imported: &[Category]) -> ImportDiff {
+    let stored_by_id: HashMap<_, _> = stored
+        .iter()
+        .map(|category| (category.id, category))
+        .collect();
+    let ordered_paths = Forest::new(stored).paths();
+    // categories sharing a path resolve to the first one
+    let mut stored_by_path = HashMap::with_capacity(ordered_paths.len());
+    for (id, path) in ordered_paths.iter() {
+        stored_by_path.entry(path.as_str()).or_insert(*id);
+    }
+    let stored_paths: HashMap<_, _> = ordered_paths.iter().cloned().collect();
+
+    let mut diff = ImportDiff::default();
+    // imported IDs to the ID they are stored under
+    let mut ids = HashMap::with_capacity(imported.len());
+    let mut paths: HashMap<Uuid, String> = HashMap::with_capacity(imported.len());
+
+    for category in Forest::new(imported).walk() {
+        let parent_id = category
+            .parent_id
+            .map(|parent| ids.get(&parent).copied().unwrap_or(parent));
+        let path = match parent_id
+            .and_then(|parent| paths.get(&parent).or_else(|| stored_paths.get(&parent)))
+        {
+            Some(parent) => format!("{parent}{SEPARATOR}{}", category.name),
+            None => category.name.to_owned(),
+        };
+
+        let matched = stored_by_id.get(&category.id).copied().or_else(|| {
+            stored_by_path
+                .get(path.as_str())
+                .and_then(|id| stored_by_id.get(id).copied())
+        });
+        let id = matched.map_or(category.id, |stored| stored.id);
+        ids.insert(category.id, id);
+        paths.insert(id, path);
+
+        match matched {
+            Some(stored)
+                if stored.name == category.name
+                    && stored.image_url == category.image_url
+                    && stored.parent_id == parent_id =>
+            {
+                diff.unchanged.push(id);
+            }
+            Some(stored) => diff.updated.push((
+                stored.to_owned(),
+                Category {
+                    name: category.name.to_owned(),
+                    image_url: category.image_url.to_owned(),
+                    parent_id,
+                    ..stored.to_owned()
+                },
+            )),
+            None => diff.created.push(Category {
+                id,
+                name: category.name.to_owned(),
+                image_url: category.image_url.to_owned(),
+                parent_id,
+                ..Default::default()
+            }),
+        }
+    }
+
+    diff
+}
+
+/// Separates the names in a path, as in Google's product taxonomy
+const SEPARATOR: &str = " > ";
+
+/// Fills in `sub_categories` from every category's `parent_id`
+fn link(categories: &mut [Category]) {
+    let mut children: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    for category in categories.iter() {
+        if let Some(parent) = category.parent_id {
+            children.entry(parent).or_default().push(category.id);
+        }
+    }
+
+    for category in categories.iter_mut() {
+        category.sub_categories = children.remove(&category.id).unwrap_or_default();
+    }
+}
+
+/// A flat list of categories arranged as a tree by their `parent_id`
+struct Forest<'a> {
+    roots: Vec<&'a Category>,
+    children: HashMap<Uuid, Vec<&'a Category>>,
+    len: usize,
+}
+
+impl<'a> Forest<'a> {
+    fn new(categories: &'a [Category]) -> Self {
+        let by_id: HashMap<_, _> = categories
+            .iter()
+            .map(|category| (category.id, category))
+            .collect();
+
+        let mut roots = Vec::new();
+        let mut children: HashMap<Uuid, Vec<&Category>> = HashMap::new();
+        for category in categories {
+            match category.parent_id {
+                Some(parent) if by_id.contains_key(&parent) => {
+                    children.entry(parent).or_default().push(category);
+                }
+                _ => roots.push(category),
+            }
+        }
+        // subcategories keep the order of their parent's `sub_categories`
+        for (parent, children) in children.iter_mut() {
+            let order = &by_id[parent].sub_categories;
+            children.sort_by_key(|child| {
+                order
+                    .iter()
+                    .position(|id| *id == child.id)
+                    .unwrap_or(usize::MAX)
+            });
+        }
+
+        Self {
+            roots,
+            children,
+            len: categories.len(),
+        }
+    }
+
+    fn children(&self, category: &Category) -> &[&'a Category] {
+        self.children
+            .get(&category.id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Every category reachable from the top level ones, each one before its subcategories
+    fn walk(&self) -> Vec<&'a Category> {
+        let mut visited = HashSet::with_capacity(self.len);
+        let mut ordered = Vec::with_capacity(self.len);
+        let mut pending: Vec<_> = self.roots.iter().rev().copied().collect();
+
+        while let Some(category) = pending.pop() {
+            if !visited.insert(category.id) {
+                continue;
+            }
+            ordered.push(category);
+            pending.extend(self.children(category).iter().rev());
+        }
+
+        ordered
+    }
+
+    /// The path of names leading to every category, parents first
+    fn paths(&self) -> Vec<(Uuid, String)> {
+        let mut paths: HashMap<Uuid, String> = HashMap::with_capacity(self.len);
+        let mut ordered = Vec::with_capacity(self.len);
+
+        for category in self.walk() {
+            let path = match category.parent_id.and_then(|parent| paths.get(&parent)) {
+                Some(parent) => format!("{parent}{SEPARATOR}{}", category.name),
+                None => category.name.to_owned(),
+            };
+            paths.insert(category.id, path.clone());
+            ordered.push((category.id, path));
+        }
+
+        ordered
+    }
+}
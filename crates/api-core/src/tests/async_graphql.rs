@@ -1,6 +1,6 @@
-use async_graphql::{EmptySubscription, Object, Schema};
+use async_graphql::{EmptySubscription, Object, Request, Schema};
 
-use crate::Category;
+use crate::{graphql::Locales, Category, Translation};
 
 use super::create_category;
 
@@ -15,6 +15,18 @@ impl Root {
     async fn input(&self, category: Category) -> Category {
         category
     }
+
+    async fn translated(&self) -> Category {
+        Category {
+            name: "Shoes".into(),
+            translations: vec![Translation {
+                locale: "fr".into(),
+                name: "Chaussures".into(),
+                description: Some("Bottes et sandales".into()),
+            }],
+            ..create_category()
+        }
+    }
 }
 
 #[tokio::test]
@@ -58,3 +70,42 @@ async fn gql_mutation() {
 
     assert!(res.errors.is_empty());
 }
+
+#[tokio::test]
+async fn gql_locales() {
+    let schema = Schema::new(Root, Root, EmptySubscription);
+    let query = r#"
+      query {
+        translated {
+          name
+          description
+          english: name(locale: "en")
+        }
+      }
+    "#;
+
+    let res = schema.execute(query).await;
+    assert!(res.errors.is_empty());
+    assert_eq!(
+        res.data,
+        async_graphql::value!({
+            "translated": { "name": "Shoes", "description": null, "english": "Shoes" }
+        })
+    );
+
+    let locales = Locales::from_accept_language("de;q=0.9, fr-CA, *;q=0.5");
+    assert_eq!(locales, Locales(vec!["fr-CA".into(), "de".into()]));
+
+    let res = schema.execute(Request::new(query).data(locales)).await;
+    assert!(res.errors.is_empty());
+    assert_eq!(
+        res.data,
+        async_graphql::value!({
+            "translated": {
+                "name": "Chaussures",
+                "description": "Bottes et sandales",
+                "english": "Shoes"
+            }
+        })
+    );
+}
@@ -1,43 +1,127 @@
 use std::{fmt::Debug, str::FromStr};
 
+use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
 use crate::{
     api::{
         CoreError, LocalMutateCategories, LocalQueryCategories, MutateCategories, QueryCategories,
     },
-    Category,
+    Category, CategoryDeletion, CategoryFilter, CategoryOrder, CategoryPatch, CategoryRevision,
+    CategoryStats, CategoryTree, CategoryUpdate, DeleteStrategy, DeletedCategory, KeysetPage,
+    RestoredCategory,
 };
 
 pub struct SampleDb;
 pub struct SampleDbSend;
 
 impl LocalQueryCategories for SampleDb {
-    async fn get_categories(&self) -> Result<impl ExactSizeIterator<Item = Category>, CoreError> {
+    async fn get_categories(
+        &self,
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
         Ok([].into_iter())
     }
 
     async fn get_sub_categories(
         &self,
         _id: Option<&Uuid>,
-    ) -> Result<impl ExactSizeIterator<Item = Category>, CoreError> {
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
+        Ok([].into_iter())
+    }
+
+    async fn find_categories(
+        &self,
+        _filter: &CategoryFilter,
+        _order: Option<&CategoryOrder>,
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
+        Ok([].into_iter())
+    }
+
+    async fn find_categories_page(
+        &self,
+        _filter: &CategoryFilter,
+        _order: &CategoryOrder,
+        _page: &KeysetPage,
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
         Ok([].into_iter())
     }
 
+    async fn count_categories(&self, _filter: &CategoryFilter) -> Result<usize, CoreError> {
+        Ok(0)
+    }
+
     async fn get_category_by_id(&self, _id: &Uuid) -> Result<Option<Category>, CoreError> {
         Ok(None)
     }
 
+    async fn get_categories_by_ids(
+        &self,
+        _ids: &[Uuid],
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
+        Ok([].into_iter())
+    }
+
+    async fn get_category_by_slug(&self, _path: &str) -> Result<Option<Category>, CoreError> {
+        Ok(None)
+    }
+
+    async fn get_deleted_categories(
+        &self,
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
+        Ok([].into_iter())
+    }
+
+    async fn get_ancestors(
+        &self,
+        _id: &Uuid,
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
+        Ok([].into_iter())
+    }
+
+    async fn get_category_tree(
+        &self,
+        _id: Option<&Uuid>,
+        _max_depth: usize,
+    ) -> Result<impl ExactSizeIterator<Item = CategoryTree> + Send, CoreError> {
+        Ok([].into_iter())
+    }
+
     async fn search(
         &self,
         _query: impl AsRef<str> + Debug + Send,
-    ) -> Result<impl ExactSizeIterator<Item = Category>, CoreError> {
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
+        Ok([].into_iter())
+    }
+
+    async fn get_category_stats(
+        &self,
+        _ids: &[Uuid],
+    ) -> Result<impl ExactSizeIterator<Item = CategoryStats> + Send, CoreError> {
         Ok([].into_iter())
     }
+
+    async fn get_category_history(
+        &self,
+        _id: &Uuid,
+    ) -> Result<impl ExactSizeIterator<Item = CategoryRevision> + Send, CoreError> {
+        Ok([].into_iter())
+    }
+
+    async fn get_category_at(
+        &self,
+        _id: &Uuid,
+        _timestamp: &DateTime<Utc>,
+    ) -> Result<Option<Category>, CoreError> {
+        Ok(None)
+    }
 }
 
 impl LocalMutateCategories for SampleDb {
-    async fn create_category(&self, category: &Category) -> Result<Category, CoreError> {
+    async fn create_category(
+        &self,
+        category: &Category,
+        _caller: Option<&str>,
+    ) -> Result<Category, CoreError> {
         Ok(category.to_owned())
     }
 
@@ -45,6 +129,8 @@ impl LocalMutateCategories for SampleDb {
         &self,
         id: &Uuid,
         data: &Category,
+        _expected_version: Option<u64>,
+        _caller: Option<&str>,
     ) -> Result<Option<Category>, CoreError> {
         if id.as_ref().is_empty() {
             Err(CoreError::from_str("Id cannot be empty")?)
@@ -53,13 +139,107 @@ impl LocalMutateCategories for SampleDb {
         }
     }
 
-    async fn delete_category(&self, _id: &Uuid) -> Result<Option<Category>, CoreError> {
+    async fn patch_category(
+        &self,
+        _id: &Uuid,
+        _patch: &CategoryPatch,
+        _expected_version: Option<u64>,
+        _caller: Option<&str>,
+    ) -> Result<Option<Category>, CoreError> {
+        Ok(None)
+    }
+
+    async fn delete_category(
+        &self,
+        _id: &Uuid,
+        _strategy: DeleteStrategy,
+        _expected_version: Option<u64>,
+        _caller: Option<&str>,
+    ) -> Result<Option<DeletedCategory>, CoreError> {
+        Ok(None)
+    }
+
+    async fn move_category(
+        &self,
+        _id: &Uuid,
+        _parent: Option<&Uuid>,
+        _position: Option<usize>,
+        _expected_version: Option<u64>,
+        _caller: Option<&str>,
+    ) -> Result<Option<Category>, CoreError> {
+        Ok(None)
+    }
+
+    async fn reorder_sub_categories(
+        &self,
+        _parent: Option<&Uuid>,
+        _ordered: &[Uuid],
+        _caller: Option<&str>,
+    ) -> Result<Option<Vec<Category>>, CoreError> {
+        Ok(None)
+    }
+
+    async fn adjust_listing_count(
+        &self,
+        _id: &Uuid,
+        _delta: i64,
+    ) -> Result<Option<u64>, CoreError> {
         Ok(None)
     }
+
+    async fn restore_category(
+        &self,
+        _id: &Uuid,
+        _revision: u64,
+        _caller: Option<&str>,
+    ) -> Result<Option<Category>, CoreError> {
+        Ok(None)
+    }
+
+    async fn undelete_category(
+        &self,
+        _id: &Uuid,
+        _caller: Option<&str>,
+    ) -> Result<Option<RestoredCategory>, CoreError> {
+        Ok(None)
+    }
+
+    async fn purge_category(&self, _id: &Uuid) -> Result<Option<DeletedCategory>, CoreError> {
+        Ok(None)
+    }
+
+    async fn create_categories(
+        &self,
+        categories: &[Category],
+        _caller: Option<&str>,
+    ) -> Result<Vec<Category>, CoreError> {
+        Ok(categories.to_vec())
+    }
+
+    async fn update_categories(
+        &self,
+        updates: &[CategoryUpdate],
+        _caller: Option<&str>,
+    ) -> Result<Vec<Option<Category>>, CoreError> {
+        Ok(vec![None; updates.len()])
+    }
+
+    async fn delete_categories(
+        &self,
+        deletions: &[CategoryDeletion],
+        _strategy: DeleteStrategy,
+        _caller: Option<&str>,
+    ) -> Result<Vec<Option<DeletedCategory>>, CoreError> {
+        Ok(vec![None; deletions.len()])
+    }
 }
 
 impl MutateCategories for SampleDbSend {
-    async fn create_category(&self, category: &Category) -> Result<Category, CoreError> {
+    async fn create_category(
+        &self,
+        category: &Category,
+        _caller: Option<&str>,
+    ) -> Result<Category, CoreError> {
         Ok(category.to_owned())
     }
 
@@ -67,35 +247,204 @@ impl MutateCategories for SampleDbSend {
         &self,
         _id: &Uuid,
         data: &Category,
+        _expected_version: Option<u64>,
+        _caller: Option<&str>,
     ) -> Result<Option<Category>, CoreError> {
         Ok(Some(data.to_owned()))
     }
 
-    async fn delete_category(&self, _id: &Uuid) -> Result<Option<Category>, CoreError> {
+    async fn patch_category(
+        &self,
+        _id: &Uuid,
+        _patch: &CategoryPatch,
+        _expected_version: Option<u64>,
+        _caller: Option<&str>,
+    ) -> Result<Option<Category>, CoreError> {
+        Ok(None)
+    }
+
+    async fn delete_category(
+        &self,
+        _id: &Uuid,
+        _strategy: DeleteStrategy,
+        _expected_version: Option<u64>,
+        _caller: Option<&str>,
+    ) -> Result<Option<DeletedCategory>, CoreError> {
+        Ok(None)
+    }
+
+    async fn move_category(
+        &self,
+        _id: &Uuid,
+        _parent: Option<&Uuid>,
+        _position: Option<usize>,
+        _expected_version: Option<u64>,
+        _caller: Option<&str>,
+    ) -> Result<Option<Category>, CoreError> {
+        Ok(None)
+    }
+
+    async fn reorder_sub_categories(
+        &self,
+        _parent: Option<&Uuid>,
+        _ordered: &[Uuid],
+        _caller: Option<&str>,
+    ) -> Result<Option<Vec<Category>>, CoreError> {
+        Ok(None)
+    }
+
+    async fn adjust_listing_count(
+        &self,
+        _id: &Uuid,
+        _delta: i64,
+    ) -> Result<Option<u64>, CoreError> {
         Ok(None)
     }
+
+    async fn restore_category(
+        &self,
+        _id: &Uuid,
+        _revision: u64,
+        _caller: Option<&str>,
+    ) -> Result<Option<Category>, CoreError> {
+        Ok(None)
+    }
+
+    async fn undelete_category(
+        &self,
+        _id: &Uuid,
+        _caller: Option<&str>,
+    ) -> Result<Option<RestoredCategory>, CoreError> {
+        Ok(None)
+    }
+
+    async fn purge_category(&self, _id: &Uuid) -> Result<Option<DeletedCategory>, CoreError> {
+        Ok(None)
+    }
+
+    async fn create_categories(
+        &self,
+        categories: &[Category],
+        _caller: Option<&str>,
+    ) -> Result<Vec<Category>, CoreError> {
+        Ok(categories.to_vec())
+    }
+
+    async fn update_categories(
+        &self,
+        updates: &[CategoryUpdate],
+        _caller: Option<&str>,
+    ) -> Result<Vec<Option<Category>>, CoreError> {
+        Ok(vec![None; updates.len()])
+    }
+
+    async fn delete_categories(
+        &self,
+        deletions: &[CategoryDeletion],
+        _strategy: DeleteStrategy,
+        _caller: Option<&str>,
+    ) -> Result<Vec<Option<DeletedCategory>>, CoreError> {
+        Ok(vec![None; deletions.len()])
+    }
 }
 
 impl QueryCategories for SampleDbSend {
-    async fn get_categories(&self) -> Result<impl ExactSizeIterator<Item = Category>, CoreError> {
+    async fn get_categories(
+        &self,
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
         Ok([].into_iter())
     }
 
     async fn get_sub_categories(
         &self,
         _id: Option<&Uuid>,
-    ) -> Result<impl ExactSizeIterator<Item = Category>, CoreError> {
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
+        Ok([].into_iter())
+    }
+
+    async fn find_categories(
+        &self,
+        _filter: &CategoryFilter,
+        _order: Option<&CategoryOrder>,
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
+        Ok([].into_iter())
+    }
+
+    async fn find_categories_page(
+        &self,
+        _filter: &CategoryFilter,
+        _order: &CategoryOrder,
+        _page: &KeysetPage,
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
         Ok([].into_iter())
     }
 
+    async fn count_categories(&self, _filter: &CategoryFilter) -> Result<usize, CoreError> {
+        Ok(0)
+    }
+
     async fn get_category_by_id(&self, _id: &Uuid) -> Result<Option<Category>, CoreError> {
         Ok(None)
     }
 
+    async fn get_categories_by_ids(
+        &self,
+        _ids: &[Uuid],
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
+        Ok([].into_iter())
+    }
+
+    async fn get_category_by_slug(&self, _path: &str) -> Result<Option<Category>, CoreError> {
+        Ok(None)
+    }
+
+    async fn get_deleted_categories(
+        &self,
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
+        Ok([].into_iter())
+    }
+
+    async fn get_ancestors(
+        &self,
+        _id: &Uuid,
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
+        Ok([].into_iter())
+    }
+
+    async fn get_category_tree(
+        &self,
+        _id: Option<&Uuid>,
+        _max_depth: usize,
+    ) -> Result<impl ExactSizeIterator<Item = CategoryTree> + Send, CoreError> {
+        Ok([].into_iter())
+    }
+
     async fn search(
         &self,
         _query: impl AsRef<str> + Debug + Send,
-    ) -> Result<impl ExactSizeIterator<Item = Category>, CoreError> {
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
+        Ok([].into_iter())
+    }
+
+    async fn get_category_stats(
+        &self,
+        _ids: &[Uuid],
+    ) -> Result<impl ExactSizeIterator<Item = CategoryStats> + Send, CoreError> {
         Ok([].into_iter())
     }
+
+    async fn get_category_history(
+        &self,
+        _id: &Uuid,
+    ) -> Result<impl ExactSizeIterator<Item = CategoryRevision> + Send, CoreError> {
+        Ok([].into_iter())
+    }
+
+    async fn get_category_at(
+        &self,
+        _id: &Uuid,
+        _timestamp: &DateTime<Utc>,
+    ) -> Result<Option<Category>, CoreError> {
+        Ok(None)
+    }
 }
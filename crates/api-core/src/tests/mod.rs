@@ -1,7 +1,14 @@
 mod async_graphql;
 mod db;
+#[cfg(feature = "taxonomy")]
+mod taxonomy;
 
-use crate::{tests::db::SampleDbSend, Category};
+use crate::{
+    api::CoreError, slugify, tests::db::SampleDbSend, validate_attributes, AttributeDefinition,
+    AttributeKind, Category, CategoryDeletion, CategoryFilter, CategoryOrder, CategoryOrderField,
+    CategoryPatch, CategoryUpdate, DeleteStrategy, KeysetPage, OrderDirection, SortKey, SortValue,
+    Translation,
+};
 
 use self::db::SampleDb;
 use uuid::Uuid;
@@ -13,6 +20,7 @@ fn create_category() -> Category {
         sub_categories: vec![],
         image_url: None,
         parent_id: None,
+        ..Default::default()
     }
 }
 
@@ -42,6 +50,7 @@ fn deserialise_list() {
         sub_categories: vec![],
         image_url: None,
         parent_id: None,
+        ..Default::default()
     };
 
     let categories = vec![category, category_2];
@@ -74,8 +83,50 @@ async fn trait_blank_queries() {
     let db = SampleDb.get_sub_categories(id).await;
     assert!(db.is_ok());
 
+    let filter = CategoryFilter::default();
+    let db = SampleDb.find_categories(&filter, None).await;
+    assert!(db.is_ok());
+
+    let order = CategoryOrder {
+        field: CategoryOrderField::Name,
+        direction: OrderDirection::Asc,
+    };
+    let page = KeysetPage::default();
+    let db = SampleDb.find_categories_page(&filter, &order, &page).await;
+    assert!(db.is_ok());
+
+    let db = SampleDb.count_categories(&filter).await;
+    assert!(db.is_ok());
+
     let db = SampleDb.get_category_by_id(&generated_id).await;
     assert!(db.is_ok());
+
+    let ids = [generated_id];
+    let db = SampleDb.get_categories_by_ids(&ids).await;
+    assert!(db.is_ok());
+
+    let db = SampleDb.get_category_by_slug("apparel/shoes").await;
+    assert!(db.is_ok());
+
+    let db = SampleDb.get_deleted_categories().await;
+    assert!(db.is_ok());
+
+    let db = SampleDb.get_ancestors(&generated_id).await;
+    assert!(db.is_ok());
+
+    let db = SampleDb.get_category_tree(id, 2).await;
+    assert!(db.is_ok());
+
+    let db = SampleDb.get_category_stats(&ids).await;
+    assert!(db.is_ok());
+
+    let db = SampleDb.get_category_history(&generated_id).await;
+    assert!(db.is_ok());
+
+    let db = SampleDb
+        .get_category_at(&generated_id, &chrono::Utc::now())
+        .await;
+    assert!(db.is_ok());
 }
 
 #[tokio::test]
@@ -84,14 +135,61 @@ async fn trait_blank_mutations() {
 
     let category = create_category();
 
-    let db = SampleDb.create_category(&category).await;
+    let db = SampleDb.create_category(&category, None).await;
     assert!(db.is_ok());
 
     let id = Uuid::now_v7();
-    let db = SampleDb.update_category(&id, &category).await;
+    let db = SampleDb.update_category(&id, &category, None, None).await;
+    assert!(db.is_ok());
+
+    let db = SampleDb
+        .delete_category(&id, DeleteStrategy::Restrict, None, None)
+        .await;
     assert!(db.is_ok());
 
-    let db = SampleDb.delete_category(&id).await;
+    let db = SampleDb.move_category(&id, None, Some(0), None, None).await;
+    assert!(db.is_ok());
+
+    let db = SampleDb.reorder_sub_categories(None, &[id], None).await;
+    assert!(db.is_ok());
+
+    let db = SampleDb
+        .patch_category(&id, &CategoryPatch::default(), Some(1), None)
+        .await;
+    assert!(db.is_ok());
+
+    let db = SampleDb.adjust_listing_count(&id, 1).await;
+    assert!(db.is_ok());
+
+    let db = SampleDb.restore_category(&id, 1, None).await;
+    assert!(db.is_ok());
+
+    let db = SampleDb.undelete_category(&id, None).await;
+    assert!(db.is_ok());
+
+    let db = SampleDb.purge_category(&id).await;
+    assert!(db.is_ok());
+
+    let db = SampleDb
+        .create_categories(std::slice::from_ref(&category), None)
+        .await;
+    assert!(db.is_ok());
+
+    let updates = [CategoryUpdate {
+        id,
+        category: category.clone(),
+        expected_version: None,
+    }];
+    let db = SampleDb.update_categories(&updates, None).await;
+    assert!(db.is_ok());
+
+    let deletions = [CategoryDeletion {
+        id,
+        expected_version: None,
+    }];
+    let db = SampleDb
+        .delete_categories(&deletions, DeleteStrategy::Cascade, None)
+        .await;
     assert!(db.is_ok());
 }
 
@@ -102,13 +200,64 @@ async fn mutation_returns_send() {
     let category = create_category();
 
     let id = Uuid::now_v7();
-    let db = SampleDbSend.create_category(&category).await;
+    let db = SampleDbSend.create_category(&category, None).await;
+    assert!(db.is_ok());
+
+    let db = SampleDbSend
+        .update_category(&id, &category, None, None)
+        .await;
+    assert!(db.is_ok());
+
+    let db = SampleDbSend
+        .delete_category(&id, DeleteStrategy::Restrict, None, None)
+        .await;
+    assert!(db.is_ok());
+
+    let db = SampleDbSend
+        .move_category(&id, None, Some(0), None, None)
+        .await;
+    assert!(db.is_ok());
+
+    let db = SampleDbSend.reorder_sub_categories(None, &[id], None).await;
     assert!(db.is_ok());
 
-    let db = SampleDbSend.update_category(&id, &category).await;
+    let db = SampleDbSend
+        .patch_category(&id, &CategoryPatch::default(), Some(1), None)
+        .await;
     assert!(db.is_ok());
 
-    let db = SampleDbSend.delete_category(&id).await;
+    let db = SampleDbSend.adjust_listing_count(&id, 1).await;
+    assert!(db.is_ok());
+
+    let db = SampleDbSend.restore_category(&id, 1, None).await;
+    assert!(db.is_ok());
+
+    let db = SampleDbSend.undelete_category(&id, None).await;
+    assert!(db.is_ok());
+
+    let db = SampleDbSend.purge_category(&id).await;
+    assert!(db.is_ok());
+
+    let db = SampleDbSend
+        .create_categories(std::slice::from_ref(&category), None)
+        .await;
+    assert!(db.is_ok());
+
+    let updates = [CategoryUpdate {
+        id,
+        category: category.clone(),
+        expected_version: None,
+    }];
+    let db = SampleDbSend.update_categories(&updates, None).await;
+    assert!(db.is_ok());
+
+    let deletions = [CategoryDeletion {
+        id,
+        expected_version: None,
+    }];
+    let db = SampleDbSend
+        .delete_categories(&deletions, DeleteStrategy::Cascade, None)
+        .await;
     assert!(db.is_ok());
 }
 
@@ -128,6 +277,265 @@ async fn query_returns_send() {
     let db = SampleDbSend.get_sub_categories(id).await;
     assert!(db.is_ok());
 
+    let filter = CategoryFilter::default();
+    let db = SampleDbSend.find_categories(&filter, None).await;
+    assert!(db.is_ok());
+
+    let order = CategoryOrder {
+        field: CategoryOrderField::Name,
+        direction: OrderDirection::Asc,
+    };
+    let page = KeysetPage::default();
+    let db = SampleDbSend
+        .find_categories_page(&filter, &order, &page)
+        .await;
+    assert!(db.is_ok());
+
+    let db = SampleDbSend.count_categories(&filter).await;
+    assert!(db.is_ok());
+
     let db = SampleDbSend.get_category_by_id(&generated_id).await;
     assert!(db.is_ok());
+
+    let ids = [generated_id];
+    let db = SampleDbSend.get_categories_by_ids(&ids).await;
+    assert!(db.is_ok());
+
+    let db = SampleDbSend.get_category_by_slug("apparel/shoes").await;
+    assert!(db.is_ok());
+
+    let db = SampleDbSend.get_deleted_categories().await;
+    assert!(db.is_ok());
+
+    let db = SampleDbSend.get_ancestors(&generated_id).await;
+    assert!(db.is_ok());
+
+    let db = SampleDbSend.get_category_tree(id, 2).await;
+    assert!(db.is_ok());
+
+    let db = SampleDbSend.get_category_stats(&ids).await;
+    assert!(db.is_ok());
+
+    let db = SampleDbSend.get_category_history(&generated_id).await;
+    assert!(db.is_ok());
+
+    let db = SampleDbSend
+        .get_category_at(&generated_id, &chrono::Utc::now())
+        .await;
+    assert!(db.is_ok());
+}
+
+#[tokio::test]
+async fn shared_returns_send() {
+    use crate::api::{MutateCategories, QueryCategories};
+    use std::sync::Arc;
+
+    let db = Arc::new(SampleDbSend);
+    let category = create_category();
+
+    let res = db.get_categories().await;
+    assert!(res.is_ok());
+
+    let res = db.get_sub_categories(Some(&category.id)).await;
+    assert!(res.is_ok());
+
+    let res = db.create_category(&category, None).await;
+    assert_eq!(res.unwrap(), category);
+
+    let res = db
+        .delete_category(&category.id, DeleteStrategy::Cascade, None, None)
+        .await;
+    assert!(res.is_ok());
+}
+
+#[test]
+fn slugs() {
+    assert_eq!(slugify("Animals & Pet Supplies"), "animals-pet-supplies");
+    assert_eq!(slugify(" T-Shirts -- Tops "), "t-shirts-tops");
+    assert_eq!(slugify("Café Crème"), "café-crème");
+    assert_eq!(slugify("&&"), "");
+}
+
+#[test]
+fn translations() {
+    let category = Category {
+        name: "Shoes".into(),
+        description: Some("Footwear".into()),
+        translations: vec![
+            Translation {
+                locale: "fr".into(),
+                name: "Chaussures".into(),
+                description: None,
+            },
+            Translation {
+                locale: "pt-BR".into(),
+                name: "Sapatos".into(),
+                description: Some("Calçados".into()),
+            },
+        ],
+        ..Default::default()
+    };
+
+    assert_eq!(category.localised_name(&["pt-br"]), "Sapatos");
+    assert_eq!(category.localised_description(&["pt-BR"]), Some("Calçados"));
+    // regional locales fall back to their language, untranslated fields to the default locale
+    assert_eq!(category.localised_name(&["fr-CA"]), "Chaussures");
+    assert_eq!(category.localised_description(&["fr-CA"]), Some("Footwear"));
+    assert_eq!(category.localised_name(&["de", "fr"]), "Chaussures");
+    assert_eq!(category.localised_name(&["pt"]), "Shoes");
+    assert_eq!(category.localised_name(&Vec::<String>::new()), "Shoes");
+}
+
+#[test]
+fn attributes() {
+    let attribute = |key: &str, kind, allowed_values: &[&str]| AttributeDefinition {
+        key: key.into(),
+        kind,
+        required: true,
+        allowed_values: allowed_values
+            .iter()
+            .map(|value| value.to_string())
+            .collect(),
+    };
+
+    let apparel = Category {
+        attributes: vec![
+            attribute("size", AttributeKind::Select, &["S", "M", "L"]),
+            attribute("brand", AttributeKind::Text, &[]),
+        ],
+        ..Default::default()
+    };
+    let shoes = Category {
+        attributes: vec![
+            attribute("condition", AttributeKind::Select, &["new", "used"]),
+            attribute("size", AttributeKind::Number, &[]),
+        ],
+        ..Default::default()
+    };
+    let boots = Category::default();
+
+    // overrides keep the position of the inherited attribute
+    let keys: Vec<_> = boots
+        .effective_attributes([&apparel, &shoes])
+        .into_iter()
+        .map(|attribute| (attribute.key, attribute.kind))
+        .collect();
+    assert_eq!(
+        keys,
+        [
+            ("size".to_owned(), AttributeKind::Number),
+            ("brand".to_owned(), AttributeKind::Text),
+            ("condition".to_owned(), AttributeKind::Select),
+        ]
+    );
+
+    assert!(validate_attributes(&apparel.attributes).is_ok());
+    for invalid in [
+        vec![attribute(" ", AttributeKind::Text, &[])],
+        vec![
+            attribute("brand", AttributeKind::Text, &[]),
+            attribute("brand", AttributeKind::Boolean, &[]),
+        ],
+        vec![attribute("size", AttributeKind::MultiSelect, &[])],
+        vec![attribute("size", AttributeKind::Select, &["S", "S"])],
+        vec![attribute("weight", AttributeKind::Number, &["1"])],
+    ] {
+        assert!(matches!(
+            validate_attributes(&invalid),
+            Err(CoreError::InvalidAttribute { .. })
+        ));
+    }
+}
+
+#[test]
+fn filter_and_order() {
+    let parent = Category {
+        id: Uuid::from_u128(1),
+        name: "Shoes".into(),
+        ..Default::default()
+    };
+    let child = Category {
+        id: Uuid::from_u128(2),
+        name: "Boots".into(),
+        parent_id: Some(parent.id),
+        position: 0,
+        ..Default::default()
+    };
+    let parent = Category {
+        sub_categories: vec![child.id],
+        ..parent
+    };
+
+    let filter = CategoryFilter {
+        name_contains: Some("OOT".into()),
+        has_parent: Some(true),
+        is_leaf: Some(true),
+        depth: Some(1),
+        ids: Some(vec![child.id]),
+        ..Default::default()
+    };
+    assert!(filter.matches(&child, 1));
+    assert!(!filter.matches(&child, 0));
+    assert!(!filter.matches(&parent, 0));
+
+    let deleted = Category {
+        deleted_at: Some(chrono::Utc::now()),
+        ..child.clone()
+    };
+    assert!(!CategoryFilter::default().matches(&deleted, 1));
+    assert!(CategoryFilter {
+        include_deleted: true,
+        ..Default::default()
+    }
+    .matches(&deleted, 1));
+
+    let mut categories = vec![parent.clone(), child.clone()];
+    let order = CategoryOrder {
+        field: CategoryOrderField::Name,
+        direction: OrderDirection::Asc,
+    };
+    order.sort(&mut categories);
+    assert_eq!(categories, [child.clone(), parent.clone()]);
+
+    // ties are broken by ID, in the same direction
+    let order = CategoryOrder {
+        field: CategoryOrderField::Position,
+        direction: OrderDirection::Desc,
+    };
+    order.sort(&mut categories);
+    assert_eq!(categories, [child, parent]);
+}
+
+#[test]
+fn keyset_page() {
+    let order = CategoryOrder {
+        field: CategoryOrderField::Position,
+        direction: OrderDirection::Desc,
+    };
+    let key = |position, id| SortKey {
+        value: SortValue::Position(position),
+        id: Uuid::from_u128(id),
+    };
+
+    let page = KeysetPage {
+        after: Some(key(5, 2)),
+        before: Some(key(1, 1)),
+        ..Default::default()
+    };
+    // sorted in descending order, same positions by descending ID
+    assert!(page.contains(&order, &key(5, 1)));
+    assert!(page.contains(&order, &key(2, 9)));
+    assert!(!page.contains(&order, &key(5, 2)));
+    assert!(!page.contains(&order, &key(5, 3)));
+    assert!(!page.contains(&order, &key(1, 0)));
+
+    let category = Category {
+        position: 5,
+        ..Default::default()
+    };
+    assert_eq!(order.key(&category), key(5, 0));
+    assert_eq!(
+        order.key(&category).value.field(),
+        CategoryOrderField::Position
+    );
 }
@@ -0,0 +1,147 @@
+use uuid::Uuid;
+
+use crate::{
+    taxonomy::{self, Format, TaxonomyError},
+    Category,
+};
+
+const GOOGLE: &str = "# Google_Product_Taxonomy_Version: 2021-09-21
+1 - Animals & Pet Supplies
+3237 - Animals & Pet Supplies > Live Animals
+2 - Animals & Pet Supplies > Pet Supplies
+3 - Animals & Pet Supplies > Pet Supplies > Bird Supplies
+
+Arts & Entertainment > Hobbies & Creative Arts
+";
+
+fn name_of(categories: &[Category], id: Option<Uuid>) -> Option<&str> {
+    let id = id?;
+    categories
+        .iter()
+        .find(|category| category.id == id)
+        .map(|category| category.name.as_str())
+}
+
+#[test]
+fn read_google() {
+    let categories = taxonomy::read(Format::Google, GOOGLE.as_bytes()).unwrap();
+
+    let names: Vec<_> = categories
+        .iter()
+        .map(|category| category.name.as_str())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "Animals & Pet Supplies",
+            "Live Animals",
+            "Pet Supplies",
+            "Bird Supplies",
+            "Arts & Entertainment",
+            "Hobbies & Creative Arts"
+        ]
+    );
+    assert_eq!(
+        name_of(&categories, categories[3].parent_id),
+        Some("Pet Supplies")
+    );
+    assert_eq!(categories[0].sub_categories.len(), 2);
+    assert!(categories[4].parent_id.is_none());
+}
+
+#[test]
+fn read_google_empty_name() {
+    let res = taxonomy::read(Format::Google, "Apparel > > Shoes".as_bytes());
+
+    assert!(matches!(res, Err(TaxonomyError::Invalid(_))));
+}
+
+#[test]
+fn read_google_separator_in_name() {
+    let categories = taxonomy::read(Format::Google, "Cables > HDMI->DVI".as_bytes()).unwrap();
+
+    let names: Vec<_> = categories
+        .iter()
+        .map(|category| category.name.as_str())
+        .collect();
+    assert_eq!(names, ["Cables", "HDMI->DVI"]);
+}
+
+#[test]
+fn round_trip() {
+    let categories = taxonomy::read(Format::Google, GOOGLE.as_bytes()).unwrap();
+
+    for format in [Format::Json, Format::Csv] {
+        let mut buf = Vec::new();
+        taxonomy::write(format, &categories, &mut buf).unwrap();
+        let read = taxonomy::read(format, buf.as_slice()).unwrap();
+
+        assert_eq!(read, categories, "{format:?}");
+    }
+
+    let mut buf = Vec::new();
+    taxonomy::write(Format::Google, &categories, &mut buf).unwrap();
+    let lines: Vec<_> = std::str::from_utf8(&buf).unwrap().lines().collect();
+    assert_eq!(
+        lines[3],
+        "Animals & Pet Supplies > Pet Supplies > Bird Supplies"
+    );
+    assert_eq!(lines.len(), categories.len());
+}
+
+#[test]
+fn read_json_without_ids() {
+    let json =
+        r#"[{ "name": "Apparel", "children": [{ "name": "Shoes", "image_url": "shoes.png" }] }]"#;
+    let categories = taxonomy::read(Format::Json, json.as_bytes()).unwrap();
+
+    assert_eq!(categories.len(), 2);
+    assert_eq!(categories[0].sub_categories, [categories[1].id]);
+    assert_eq!(categories[1].parent_id, Some(categories[0].id));
+    assert_eq!(categories[1].image_url.as_deref(), Some("shoes.png"));
+}
+
+#[test]
+fn read_csv_rejects_cycles() {
+    let first = Uuid::now_v7();
+    let second = Uuid::now_v7();
+    let csv = format!("id,name,parent_id,image_url\n{first},A,{second},\n{second},B,{first},\n");
+
+    let res = taxonomy::read(Format::Csv, csv.as_bytes());
+
+    assert!(matches!(res, Err(TaxonomyError::Invalid(_))));
+}
+
+#[test]
+fn diff_matches_paths() {
+    let stored = taxonomy::read(Format::Google, GOOGLE.as_bytes()).unwrap();
+    let imported = taxonomy::read(
+        Format::Google,
+        "Animals & Pet Supplies > Pet Supplies > Fish Supplies\nArts & Entertainment\n".as_bytes(),
+    )
+    .unwrap();
+
+    let diff = taxonomy::diff(&stored, &imported);
+
+    assert_eq!(diff.unchanged, [stored[0].id, stored[2].id, stored[4].id]);
+    assert!(diff.updated.is_empty());
+    assert_eq!(diff.created.len(), 1);
+    assert_eq!(diff.created[0].name, "Fish Supplies");
+    assert_eq!(diff.created[0].parent_id, Some(stored[2].id));
+}
+
+#[test]
+fn diff_matches_ids() {
+    let stored = taxonomy::read(Format::Google, GOOGLE.as_bytes()).unwrap();
+    let mut imported = stored.clone();
+    imported[1].name = String::from("Pets");
+    imported[3].parent_id = Some(stored[0].id);
+
+    let diff = taxonomy::diff(&stored, &imported);
+
+    assert!(diff.created.is_empty());
+    assert_eq!(diff.updated.len(), 2);
+    assert_eq!(diff.updated[0].1.name, "Pets");
+    assert_eq!(diff.updated[1].0.parent_id, Some(stored[2].id));
+    assert_eq!(diff.updated[1].1.parent_id, Some(stored[0].id));
+}
@@ -6,9 +6,15 @@ use surrealdb::{
 
 #[non_exhaustive]
 #[derive(Deserialize, Serialize)]
-#[serde(rename_all = "lowercase")]
+#[serde(rename_all = "snake_case")]
 pub(crate) enum Collection {
     Category,
+    /// Append-only snapshots of every change made to a category
+    CategoryRevision,
+    /// Number of listings filed directly under each category, keyed by the category's ID
+    CategoryListing,
+    /// One-off changes already applied to the stored data, keyed by name
+    Migration,
 }
 
 impl std::fmt::Display for Collection {
@@ -18,6 +24,9 @@ impl std::fmt::Display for Collection {
             "{}",
             match self {
                 Collection::Category => "category",
+                Collection::CategoryRevision => "category_revision",
+                Collection::CategoryListing => "category_listing",
+                Collection::Migration => "migration",
             }
         )
     }
@@ -1,30 +1,122 @@
-use api_core::{api::CoreError, reexports::uuid::Uuid, Category};
+use api_core::{
+    api::CoreError, reexports::uuid::Uuid, AttributeDefinition, Category, CategoryRevision,
+    CategoryTree, RevisionKind, Translation,
+};
 use serde::{Deserialize, Serialize};
-use surrealdb::{opt::RecordId, sql::Id};
+use surrealdb::{
+    opt::RecordId,
+    sql::{Datetime, Id},
+};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub(crate) struct DatabaseEntity {
     pub id: RecordId,
     pub name: String,
+    #[serde(default)]
+    pub slug: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub translations: Vec<Translation>,
     pub sub_categories: Vec<RecordId>,
     pub image_url: Option<String>,
+    #[serde(default)]
+    pub attributes: Vec<AttributeDefinition>,
     pub parent_id: Option<RecordId>,
+    #[serde(default)]
+    pub position: u32,
+    #[serde(default)]
+    pub version: u64,
+    #[serde(default)]
+    pub created_at: Option<Datetime>,
+    #[serde(default)]
+    pub updated_at: Option<Datetime>,
+    #[serde(default)]
+    pub created_by: Option<String>,
+    #[serde(default)]
+    pub updated_by: Option<String>,
+    #[serde(default)]
+    pub deleted_at: Option<Datetime>,
+}
+
+/// A [`DatabaseEntity`] with its subcategories nested under `children`
+#[derive(Deserialize, Debug)]
+pub(crate) struct DatabaseTreeEntity {
+    #[serde(flatten)]
+    pub entity: DatabaseEntity,
+    #[serde(default)]
+    pub children: Vec<DatabaseTreeEntity>,
+}
+
+impl TryFrom<DatabaseTreeEntity> for CategoryTree {
+    type Error = CoreError;
+
+    fn try_from(entity: DatabaseTreeEntity) -> Result<Self, Self::Error> {
+        Ok(CategoryTree {
+            category: Category::try_from(entity.entity)?,
+            children: entity
+                .children
+                .into_iter()
+                .map(CategoryTree::try_from)
+                .collect::<Result<Vec<CategoryTree>, _>>()?,
+        })
+    }
+}
+
+/// A row of the revision collection, holding a snapshot of the category it belongs to
+#[derive(Deserialize, Debug)]
+pub(crate) struct RevisionEntity {
+    pub revision: u64,
+    pub kind: RevisionKind,
+    pub category: DatabaseEntity,
+    pub recorded_at: Datetime,
+    pub recorded_by: Option<String>,
+}
+
+impl TryFrom<RevisionEntity> for CategoryRevision {
+    type Error = CoreError;
+
+    fn try_from(entity: RevisionEntity) -> Result<Self, Self::Error> {
+        Ok(CategoryRevision {
+            revision: entity.revision,
+            kind: entity.kind,
+            category: Category::try_from(entity.category)?,
+            recorded_at: entity.recorded_at.0,
+            recorded_by: entity.recorded_by,
+        })
+    }
+}
+
+/// A row of the listing count collection, sharing its ID with the category it counts for
+#[derive(Deserialize, Debug)]
+pub(crate) struct ListingCountEntity {
+    pub id: RecordId,
+    #[serde(default)]
+    pub count: u64,
+}
+
+impl TryFrom<ListingCountEntity> for (Uuid, u64) {
+    type Error = CoreError;
+
+    fn try_from(entity: ListingCountEntity) -> Result<Self, Self::Error> {
+        Ok((Uuid::parse_str(&id_to_string(&entity.id.id))?, entity.count))
+    }
+}
+
+pub(crate) fn id_to_string(id: &Id) -> String {
+    let id = id.to_raw();
+    id.split(':')
+        .next()
+        .unwrap_or(&id)
+        .chars()
+        .filter(|&c| c != '⟨' && c != '⟩')
+        .collect()
 }
 
 impl TryFrom<DatabaseEntity> for Category {
     type Error = CoreError;
 
     fn try_from(entity: DatabaseEntity) -> Result<Self, Self::Error> {
-        let id_to_string = |id: &Id| -> String {
-            let id = id.to_raw();
-            id.split(':')
-                .next()
-                .unwrap_or(&id)
-                .chars()
-                .filter(|&c| c != '⟨' && c != '⟩')
-                .collect()
-        };
-
         let pk = id_to_string(&entity.id.id);
         let id = Uuid::parse_str(&pk)?;
 
@@ -43,12 +135,23 @@ impl TryFrom<DatabaseEntity> for Category {
         Ok(Category {
             id,
             name: entity.name,
+            slug: entity.slug,
+            description: entity.description,
+            translations: entity.translations,
             sub_categories,
             image_url: entity.image_url,
+            attributes: entity.attributes,
             parent_id: match parent_id {
                 Some(parent_id) => Some(parent_id?),
                 None => None,
             },
+            position: entity.position,
+            version: entity.version,
+            created_at: entity.created_at.map(|datetime| datetime.0),
+            updated_at: entity.updated_at.map(|datetime| datetime.0),
+            created_by: entity.created_by,
+            updated_by: entity.updated_by,
+            deleted_at: entity.deleted_at.map(|datetime| datetime.0),
         })
     }
 }
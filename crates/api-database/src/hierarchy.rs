@@ -0,0 +1,536 @@
+//! The rules that keep the category hierarchy consistent, applied to categories held in
+//! memory. [`MemoryClient`](crate::MemoryClient) stores its categories this way, while
+//! [`Client`](crate::Client) plans batches against a snapshot before writing them.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use api_core::{
+    api::CoreError,
+    reexports::{chrono::Utc, uuid::Uuid},
+    slugify, validate_attributes, Category, CategoryStats, CategoryTree, DeleteStrategy,
+    DeletedCategory, RevisionKind,
+};
+use tracing::error;
+
+/// Looks up `id`, skipping categories in the trash
+pub(crate) fn live<'a>(
+    categories: &'a BTreeMap<Uuid, Category>,
+    id: &Uuid,
+) -> Option<&'a Category> {
+    categories
+        .get(id)
+        .filter(|category| category.deleted_at.is_none())
+}
+
+pub(crate) fn check_parent(
+    categories: &BTreeMap<Uuid, Category>,
+    parent: Option<&Uuid>,
+) -> Result<(), CoreError> {
+    match parent {
+        Some(parent) if live(categories, parent).is_none() => Err(CoreError::Database(format!(
+            "provided parent does not exist: category:{parent}"
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// The parents of `id`, from the top level category down to its direct parent
+pub(crate) fn ancestors(categories: &BTreeMap<Uuid, Category>, id: &Uuid) -> Vec<Category> {
+    let mut ancestors = Vec::new();
+    let mut visited = HashSet::from([*id]);
+    let mut parent_id = categories.get(id).and_then(|category| category.parent_id);
+
+    while let Some(id) = parent_id {
+        if !visited.insert(id) {
+            error!(%id, "cycle detected in category ancestors");
+            break;
+        }
+        let Some(parent) = categories.get(&id) else {
+            break;
+        };
+        parent_id = parent.parent_id;
+        ancestors.push(parent.to_owned());
+    }
+    ancestors.reverse();
+
+    ancestors
+}
+
+/// The IDs of every category below `category`, grouped by level
+pub(crate) fn descendant_levels(
+    categories: &BTreeMap<Uuid, Category>,
+    category: &Category,
+) -> Vec<Vec<Uuid>> {
+    let mut visited = HashSet::from([category.id]);
+    let mut levels = Vec::new();
+    let mut pending = category.sub_categories.clone();
+
+    while !pending.is_empty() {
+        let level: Vec<_> = pending
+            .drain(..)
+            .filter(|id| visited.insert(*id) && categories.contains_key(id))
+            .collect();
+        if level.is_empty() {
+            break;
+        }
+        pending = level
+            .iter()
+            .flat_map(|id| categories[id].sub_categories.iter().copied())
+            .collect();
+        levels.push(level);
+    }
+
+    levels
+}
+
+/// The [`CategoryStats`] of each of `ids` found in `categories`
+pub(crate) fn category_stats(
+    categories: &BTreeMap<Uuid, Category>,
+    listings: &HashMap<Uuid, u64>,
+    ids: &[Uuid],
+) -> Vec<CategoryStats> {
+    ids.iter()
+        .filter_map(|id| categories.get(id))
+        .map(|category| {
+            let levels = descendant_levels(categories, category);
+            let listing_count = listings.get(&category.id).copied().unwrap_or_default();
+
+            CategoryStats {
+                id: category.id,
+                depth: ancestors(categories, &category.id).len(),
+                child_count: levels.first().map_or(0, Vec::len),
+                descendant_count: levels.iter().map(Vec::len).sum(),
+                listing_count,
+                total_listing_count: listing_count
+                    + levels
+                        .iter()
+                        .flatten()
+                        .filter_map(|id| listings.get(id))
+                        .sum::<u64>(),
+            }
+        })
+        .collect()
+}
+
+/// Makes sure `parent` can hold `category` without a cycle or nesting past `max_depth`
+pub(crate) fn check_hierarchy(
+    categories: &BTreeMap<Uuid, Category>,
+    category: Option<&Category>,
+    parent: Option<&Uuid>,
+    max_depth: Option<usize>,
+) -> Result<(), CoreError> {
+    let Some(parent) = parent else {
+        return Ok(());
+    };
+
+    let mut chain: Vec<_> = ancestors(categories, parent)
+        .into_iter()
+        .map(|category| category.id)
+        .collect();
+    chain.push(*parent);
+
+    if let Some(category) = category {
+        if chain.contains(&category.id) {
+            return Err(CoreError::CyclicHierarchy {
+                id: category.id,
+                parent: *parent,
+            });
+        }
+    }
+
+    if let Some(max_depth) = max_depth {
+        let height = category.map_or(0, |category| descendant_levels(categories, category).len());
+        if chain.len() + 1 + height > max_depth {
+            return Err(CoreError::MaxDepthExceeded(max_depth));
+        }
+    }
+
+    Ok(())
+}
+
+/// Makes sure no category under `parent`, other than `except`, already goes by `slug`
+pub(crate) fn check_slug(
+    categories: &BTreeMap<Uuid, Category>,
+    except: &[Uuid],
+    parent: Option<&Uuid>,
+    slug: &str,
+) -> Result<(), CoreError> {
+    if slug.is_empty() {
+        return Err(CoreError::EmptySlug);
+    }
+    let taken = categories.values().any(|category| {
+        category.deleted_at.is_none()
+            && category.parent_id.as_ref() == parent
+            && category.slug == slug
+            && !except.contains(&category.id)
+    });
+
+    if taken {
+        Err(CoreError::DuplicateSlug {
+            slug: slug.to_owned(),
+            parent_id: parent.copied(),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/// Orders siblings by their position, ties going to the category created first
+pub(crate) fn sort_siblings(categories: &mut [Category]) {
+    categories.sort_by_key(|category| (category.position, category.id));
+}
+
+/// Orders every level of `tree` like [`sort_siblings`]
+pub(crate) fn sort_tree(tree: &mut [CategoryTree]) {
+    tree.sort_by_key(|node| (node.category.position, node.category.id));
+    for node in tree.iter_mut() {
+        sort_tree(&mut node.children);
+    }
+}
+
+/// Lists every category right after its parent, siblings in order
+pub(crate) fn tree_order(categories: Vec<Category>) -> Vec<Category> {
+    let ids: HashSet<_> = categories.iter().map(|category| category.id).collect();
+    let mut children: HashMap<Option<Uuid>, Vec<Category>> = HashMap::new();
+    for category in categories {
+        let parent = category.parent_id.filter(|parent| ids.contains(parent));
+        children.entry(parent).or_default().push(category);
+    }
+    for siblings in children.values_mut() {
+        sort_siblings(siblings);
+        siblings.reverse();
+    }
+
+    let mut ordered = Vec::with_capacity(ids.len());
+    let mut pending = children.remove(&None).unwrap_or_default();
+    while let Some(category) = pending.pop() {
+        if let Some(siblings) = children.remove(&Some(category.id)) {
+            pending.extend(siblings);
+        }
+        ordered.push(category);
+    }
+    // only categories in a cycle are out of reach from the top level
+    ordered.extend(children.into_values().flatten());
+
+    ordered
+}
+
+/// Lists live categories like [`tree_order`], followed by the deleted ones as given
+pub(crate) fn listing_order(categories: Vec<Category>) -> Vec<Category> {
+    let (live, deleted): (Vec<_>, Vec<_>) = categories
+        .into_iter()
+        .partition(|category| category.deleted_at.is_none());
+
+    [tree_order(live), deleted].concat()
+}
+
+/// The position after the last category under `parent`
+pub(crate) fn next_position(categories: &BTreeMap<Uuid, Category>, parent: Option<&Uuid>) -> u32 {
+    categories
+        .values()
+        .filter(|category| category.deleted_at.is_none() && category.parent_id.as_ref() == parent)
+        .map(|category| category.position + 1)
+        .max()
+        .unwrap_or_default()
+}
+
+/// Makes sure `ordered` lists every one of `siblings` exactly once
+pub(crate) fn check_order(siblings: &[Uuid], ordered: &[Uuid]) -> Result<(), CoreError> {
+    let unique: BTreeSet<_> = ordered.iter().collect();
+
+    if unique.len() == ordered.len()
+        && ordered.len() == siblings.len()
+        && siblings.iter().all(|id| unique.contains(id))
+    {
+        Ok(())
+    } else {
+        Err(CoreError::InvalidOrder)
+    }
+}
+
+pub(crate) fn check_version(
+    category: &Category,
+    expected_version: Option<u64>,
+) -> Result<(), CoreError> {
+    match expected_version {
+        Some(version) if version != category.version => {
+            Err(CoreError::VersionConflict(category.id))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Records a change made by `caller`
+pub(crate) fn touch(category: &mut Category, caller: Option<&str>) {
+    category.version += 1;
+    category.updated_at = Some(Utc::now());
+    category.updated_by = caller.map(ToOwned::to_owned);
+}
+
+pub(crate) fn link_parent(
+    categories: &mut BTreeMap<Uuid, Category>,
+    parent: Option<&Uuid>,
+    id: &Uuid,
+) {
+    if let Some(parent) = parent.and_then(|parent| categories.get_mut(parent)) {
+        parent.sub_categories.push(*id);
+    }
+}
+
+pub(crate) fn unlink_parent(
+    categories: &mut BTreeMap<Uuid, Category>,
+    parent: Option<&Uuid>,
+    id: &Uuid,
+) {
+    if let Some(parent) = parent.and_then(|parent| categories.get_mut(parent)) {
+        parent.sub_categories.retain(|child| child != id);
+    }
+}
+
+/// Gives every category of a batch an ID and puts parents first, keeping each batch position
+pub(crate) fn creation_order(categories: &[Category]) -> Result<Vec<(usize, Category)>, CoreError> {
+    let mut ids = HashSet::with_capacity(categories.len());
+    let mut pending = Vec::with_capacity(categories.len());
+    for (index, category) in categories.iter().enumerate() {
+        let id = if category.id.is_nil() {
+            Uuid::now_v7()
+        } else {
+            category.id
+        };
+        if !ids.insert(id) {
+            return Err(CoreError::AlreadyExists(id));
+        }
+        pending.push((
+            index,
+            Category {
+                id,
+                ..category.to_owned()
+            },
+        ));
+    }
+
+    let mut ordered = Vec::with_capacity(pending.len());
+    let mut placed = HashSet::with_capacity(pending.len());
+    while !pending.is_empty() {
+        let (ready, waiting): (Vec<_>, Vec<_>) =
+            pending
+                .into_iter()
+                .partition(
+                    |(_, category): &(usize, Category)| match category.parent_id {
+                        Some(ref parent) if ids.contains(parent) => placed.contains(parent),
+                        _ => true,
+                    },
+                );
+        // every category left waits on another one of them
+        if let (true, Some((_, category))) = (ready.is_empty(), waiting.first()) {
+            return Err(CoreError::CyclicHierarchy {
+                id: category.id,
+                parent: category.parent_id.unwrap_or_default(),
+            });
+        }
+
+        placed.extend(ready.iter().map(|(_, category)| category.id));
+        ordered.extend(ready);
+        pending = waiting;
+    }
+
+    Ok(ordered)
+}
+
+/// Mutations applied to categories held in memory, collecting a revision for every change
+pub(crate) struct Changeset<'a> {
+    categories: &'a mut BTreeMap<Uuid, Category>,
+    max_depth: Option<usize>,
+    caller: Option<&'a str>,
+    changes: Vec<(RevisionKind, Category)>,
+}
+
+impl<'a> Changeset<'a> {
+    pub(crate) fn new(
+        categories: &'a mut BTreeMap<Uuid, Category>,
+        max_depth: Option<usize>,
+        caller: Option<&'a str>,
+    ) -> Self {
+        Self {
+            categories,
+            max_depth,
+            caller,
+            changes: Vec::new(),
+        }
+    }
+
+    /// The changed categories, in the order the changes were made
+    pub(crate) fn into_changes(self) -> Vec<(RevisionKind, Category)> {
+        self.changes
+    }
+
+    fn record<'b>(&mut self, kind: RevisionKind, ids: impl IntoIterator<Item = &'b Uuid>) {
+        for id in ids {
+            if let Some(category) = self.categories.get(id) {
+                self.changes.push((kind, category.to_owned()));
+            }
+        }
+    }
+
+    /// Creates `category` under the given `id` and appends it to its parent's `sub_categories`
+    pub(crate) fn create(&mut self, category: &Category, id: Uuid) -> Result<Category, CoreError> {
+        if self.categories.contains_key(&id) {
+            return Err(CoreError::AlreadyExists(id));
+        }
+        check_parent(self.categories, category.parent_id.as_ref())?;
+        check_hierarchy(
+            self.categories,
+            None,
+            category.parent_id.as_ref(),
+            self.max_depth,
+        )?;
+        validate_attributes(&category.attributes)?;
+        let slug = slugify(&category.name);
+        check_slug(self.categories, &[], category.parent_id.as_ref(), &slug)?;
+
+        let now = Utc::now();
+        let category = Category {
+            id,
+            slug,
+            position: next_position(self.categories, category.parent_id.as_ref()),
+            sub_categories: Vec::new(),
+            version: 1,
+            created_at: Some(now),
+            updated_at: Some(now),
+            created_by: self.caller.map(ToOwned::to_owned),
+            updated_by: self.caller.map(ToOwned::to_owned),
+            ..category.to_owned()
+        };
+        link_parent(self.categories, category.parent_id.as_ref(), &id);
+        self.categories.insert(id, category.clone());
+        self.record(RevisionKind::Created, [&id]);
+
+        Ok(category)
+    }
+
+    pub(crate) fn update(
+        &mut self,
+        id: &Uuid,
+        data: &Category,
+        expected_version: Option<u64>,
+    ) -> Result<Option<Category>, CoreError> {
+        check_parent(self.categories, data.parent_id.as_ref())?;
+
+        let Some(current) = live(self.categories, id).cloned() else {
+            return Ok(None);
+        };
+        check_version(&current, expected_version)?;
+        if current.parent_id != data.parent_id {
+            check_hierarchy(
+                self.categories,
+                Some(&current),
+                data.parent_id.as_ref(),
+                self.max_depth,
+            )?;
+        }
+        validate_attributes(&data.attributes)?;
+        let slug = slugify(&data.name);
+        check_slug(self.categories, &[*id], data.parent_id.as_ref(), &slug)?;
+
+        let position = if current.parent_id == data.parent_id {
+            current.position
+        } else {
+            next_position(self.categories, data.parent_id.as_ref())
+        };
+
+        let category = Category {
+            id: *id,
+            slug,
+            position,
+            sub_categories: current.sub_categories,
+            version: current.version + 1,
+            created_at: current.created_at,
+            updated_at: Some(Utc::now()),
+            created_by: current.created_by,
+            updated_by: self.caller.map(ToOwned::to_owned),
+            ..data.to_owned()
+        };
+        if current.parent_id != category.parent_id {
+            unlink_parent(self.categories, current.parent_id.as_ref(), id);
+            link_parent(self.categories, category.parent_id.as_ref(), id);
+        }
+        self.categories.insert(*id, category.clone());
+        self.record(RevisionKind::Updated, [id]);
+
+        Ok(Some(category))
+    }
+
+    pub(crate) fn delete(
+        &mut self,
+        id: &Uuid,
+        strategy: DeleteStrategy,
+        expected_version: Option<u64>,
+    ) -> Result<Option<DeletedCategory>, CoreError> {
+        let Some(mut category) = live(self.categories, id).cloned() else {
+            return Ok(None);
+        };
+        check_version(&category, expected_version)?;
+
+        let now = Utc::now();
+        let mut deleted = Vec::new();
+        let mut reparented = Vec::new();
+        match strategy {
+            DeleteStrategy::Restrict => {
+                if !category.sub_categories.is_empty() {
+                    return Err(CoreError::HasSubCategories(*id));
+                }
+            }
+            DeleteStrategy::Cascade => {
+                // the subtree stays linked so it can be restored in one go
+                deleted = descendant_levels(self.categories, &category).concat();
+                for descendant in deleted.iter() {
+                    if let Some(descendant) = self.categories.get_mut(descendant) {
+                        descendant.deleted_at = Some(now);
+                        touch(descendant, self.caller);
+                    }
+                }
+                self.record(RevisionKind::Deleted, deleted.iter());
+            }
+            DeleteStrategy::Reparent => {
+                for child in category
+                    .sub_categories
+                    .iter()
+                    .filter_map(|child| live(self.categories, child))
+                {
+                    check_slug(
+                        self.categories,
+                        &[*id, child.id],
+                        category.parent_id.as_ref(),
+                        &child.slug,
+                    )?;
+                }
+                unlink_parent(self.categories, category.parent_id.as_ref(), id);
+                // the subcategories keep their order, after the new siblings
+                let first = next_position(self.categories, category.parent_id.as_ref());
+                for child_id in &category.sub_categories {
+                    if let Some(child) = self.categories.get_mut(child_id) {
+                        child.parent_id = category.parent_id;
+                        child.position += first;
+                        touch(child, self.caller);
+                        reparented.push(*child_id);
+                        link_parent(self.categories, category.parent_id.as_ref(), child_id);
+                    }
+                }
+                self.record(RevisionKind::Updated, reparented.iter());
+                category.sub_categories.clear();
+            }
+        }
+
+        unlink_parent(self.categories, category.parent_id.as_ref(), id);
+        category.deleted_at = Some(now);
+        touch(&mut category, self.caller);
+        self.categories.insert(*id, category.clone());
+        self.record(RevisionKind::Deleted, [id]);
+
+        Ok(Some(DeletedCategory {
+            category,
+            deleted,
+            reparented,
+        }))
+    }
+}
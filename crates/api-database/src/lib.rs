@@ -3,52 +3,170 @@ use thiserror::Error;
 
 mod collections;
 pub(crate) mod entity;
+mod hierarchy;
+mod maintenance;
+mod memory;
+mod migration;
 mod mutation;
 mod query;
 mod redis;
 
+use serde::Serialize;
 use surrealdb::{
-    engine::remote::ws::{Client as SurrealClient, Ws},
-    opt::auth::Root,
+    engine::any::{self, Any},
+    opt::auth::{Database, Namespace, Root, Scope},
     Surreal,
 };
-use tracing::{instrument, trace};
+use tracing::{error, instrument, trace};
 
 use self::redis::RedisPool;
 
+pub use memory::MemoryClient;
+
+/// Fields of the search index queries are matched against, every translation included
+pub(crate) const SEARCHABLE_ATTRIBUTES: [&str; 4] = [
+    "name",
+    "translations.name",
+    "description",
+    "translations.description",
+];
+
 pub(crate) fn map_db_error(error: surrealdb::Error) -> CoreError {
     CoreError::Database(error.to_string())
 }
 
+/// How the [`Client`] signs in to SurrealDB
+#[derive(Debug, Clone, Copy)]
+pub enum DatabaseAuth<'a> {
+    /// Do not sign in. Embedded engines (`mem://`, `rocksdb://`) usually need no credentials
+    None,
+    Root {
+        username: &'a str,
+        password: &'a str,
+    },
+    /// A user defined on the namespace the client connects to
+    Namespace {
+        username: &'a str,
+        password: &'a str,
+    },
+    /// A user defined on the database the client connects to
+    Database {
+        username: &'a str,
+        password: &'a str,
+    },
+    /// A scope user, `username` and `password` are passed on as the scope's signin parameters
+    Scope {
+        scope: &'a str,
+        username: &'a str,
+        password: &'a str,
+    },
+}
+
+#[derive(Serialize)]
+struct ScopeCredentials<'a> {
+    username: &'a str,
+    password: &'a str,
+}
+
+/// Picks the SurrealDB engine from the DSN scheme, defaulting to `ws://`
+fn endpoint(dsn: &str) -> String {
+    if dsn.contains("://") {
+        dsn.to_owned()
+    } else {
+        format!("ws://{dsn}")
+    }
+}
+
+/// The scheme and feature of an embedded engine named in `dsn` this build was made without
+fn missing_engine(dsn: &str) -> Option<(&'static str, &'static str)> {
+    match dsn.split_once("://").map(|(scheme, _)| scheme) {
+        Some("mem") if !cfg!(feature = "kv-mem") => Some(("mem", "kv-mem")),
+        Some("rocksdb") if !cfg!(feature = "kv-rocksdb") => Some(("rocksdb", "kv-rocksdb")),
+        _ => None,
+    }
+}
+
 pub struct Client {
-    client: Surreal<SurrealClient>,
+    client: Surreal<Any>,
     redis: Option<(RedisPool, u64)>,
     search_client: Option<meilisearch_sdk::Client>,
+    max_depth: Option<usize>,
 }
 
 impl Client {
     #[instrument(skip_all)]
     pub async fn try_new(
         dsn: &str,
-        username: &str,
-        password: &str,
+        auth: DatabaseAuth<'_>,
         namespace: &str,
         database: &str,
         redis: Option<(&str, bool, u16, u64)>,
         meilisearch: Option<(&str, Option<&str>)>,
     ) -> Result<Self, ClientError> {
+        if let Some((scheme, feature)) = missing_engine(dsn) {
+            return Err(ClientError::UnsupportedEngine { scheme, feature });
+        }
         trace!("connecting to database");
-        let db = Surreal::new::<Ws>(dsn).await?;
+        let db = any::connect(endpoint(dsn)).await?;
 
-        // Signin as a namespace, database, or root user
-        db.signin(Root { username, password }).await?;
+        match auth {
+            DatabaseAuth::None => {
+                trace!("skipping database signin");
+            }
+            DatabaseAuth::Root { username, password } => {
+                db.signin(Root { username, password }).await?;
+            }
+            DatabaseAuth::Namespace { username, password } => {
+                db.signin(Namespace {
+                    namespace,
+                    username,
+                    password,
+                })
+                .await?;
+            }
+            DatabaseAuth::Database { username, password } => {
+                db.signin(Database {
+                    namespace,
+                    database,
+                    username,
+                    password,
+                })
+                .await?;
+            }
+            DatabaseAuth::Scope {
+                scope,
+                username,
+                password,
+            } => {
+                db.signin(Scope {
+                    namespace,
+                    database,
+                    scope,
+                    params: ScopeCredentials { username, password },
+                })
+                .await?;
+            }
+        }
 
         db.use_ns(namespace).use_db(database).await?;
 
+        migration::migrate(&db).await?;
+
+        let search_client =
+            meilisearch.map(|(host, api_key)| meilisearch_sdk::Client::new(host, api_key));
+        if let Some(ref client) = search_client {
+            if let Err(e) = client
+                .index("categories")
+                .set_searchable_attributes(SEARCHABLE_ATTRIBUTES)
+                .await
+            {
+                error!("[search settings]: {e}");
+            }
+        }
+
         Ok(Client {
             client: db,
-            search_client: meilisearch
-                .map(|(host, api_key)| meilisearch_sdk::Client::new(host, api_key)),
+            search_client,
             redis: match redis {
                 Some((dsn, clustered, size, ttl)) => Some((
                     if clustered {
@@ -60,18 +178,34 @@ impl Client {
                 )),
                 None => None,
             },
+            max_depth: None,
         })
     }
+
+    /// Rejects writes that would nest categories more than `max_depth` levels deep
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
 }
 
 #[derive(Error, Debug)]
 pub enum ClientError {
     #[error("database engine error")]
     Engine(#[from] surrealdb::Error),
+    #[error("could not define the category schema: {0}")]
+    Schema(surrealdb::Error),
     #[error("the data for key `{0}` is not available")]
     Redaction(String),
     #[error("invalid header (expected {expected:?}, found {found:?})")]
     InvalidHeader { expected: String, found: String },
+    #[error(
+        "the `{scheme}://` engine needs api-database to be built with the `{feature}` feature"
+    )]
+    UnsupportedEngine {
+        scheme: &'static str,
+        feature: &'static str,
+    },
     #[error("unknown data store error")]
     Unknown,
 }
@@ -0,0 +1,51 @@
+use api_core::api::CoreError;
+use tracing::{debug, instrument};
+
+use crate::{
+    query::db_select_categories,
+    redis::{cache_keys::CacheKey, redis_query},
+    Client, SEARCHABLE_ATTRIBUTES,
+};
+
+impl Client {
+    /// Rebuilds the search index from the database, returning the number of categories indexed
+    #[instrument(skip(self), err(Debug))]
+    pub async fn reindex_search(&self) -> Result<usize, CoreError> {
+        let client = self
+            .search_client
+            .as_ref()
+            .ok_or_else(|| CoreError::Other("search is not configured".into()))?;
+        let categories = db_select_categories(self, false).await?;
+
+        let index = client.index("categories");
+        index
+            .set_searchable_attributes(SEARCHABLE_ATTRIBUTES)
+            .await
+            .map_err(|e| CoreError::Other(e.to_string()))?;
+        index
+            .delete_all_documents()
+            .await
+            .map_err(|e| CoreError::Other(e.to_string()))?;
+        debug!(count = categories.len(), "indexing categories for search");
+        index
+            .add_documents(&categories, Some("id"))
+            .await
+            .map_err(|e| CoreError::Other(e.to_string()))?
+            .wait_for_completion(client, None, None)
+            .await
+            .map_err(|e| CoreError::Other(e.to_string()))?;
+
+        Ok(categories.len())
+    }
+
+    /// Removes every cached category entry, returning how many were removed
+    #[instrument(skip(self), err(Debug))]
+    pub async fn flush_cache(&self) -> Result<usize, CoreError> {
+        let Some((ref pool, _ttl)) = self.redis else {
+            return Err(CoreError::Other("cache is not configured".into()));
+        };
+        redis_query::invalidate_matching(CacheKey::PATTERN, pool)
+            .await
+            .map_err(|e| CoreError::Other(e.to_string()))
+    }
+}
@@ -0,0 +1,844 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+use api_core::{
+    api::{CoreError, MutateCategories, QueryCategories},
+    reexports::{
+        chrono::{DateTime, Utc},
+        uuid::Uuid,
+    },
+    slugify, validate_attributes, Category, CategoryDeletion, CategoryFilter, CategoryOrder,
+    CategoryPatch, CategoryRevision, CategoryStats, CategoryTree, CategoryUpdate, DeleteStrategy,
+    DeletedCategory, KeysetPage, RestoredCategory, RevisionKind,
+};
+use tracing::instrument;
+
+use crate::hierarchy::{
+    self, ancestors, category_stats, check_hierarchy, check_order, check_parent, check_slug,
+    check_version, descendant_levels, link_parent, listing_order, live, next_position,
+    sort_siblings, sort_tree, touch, tree_order, unlink_parent, Changeset,
+};
+
+/// A storage backend that keeps every category in process memory, for tests and local
+/// development
+#[derive(Debug, Default)]
+pub struct MemoryClient {
+    categories: RwLock<BTreeMap<Uuid, Category>>,
+    revisions: RwLock<BTreeMap<Uuid, Vec<CategoryRevision>>>,
+    listings: RwLock<HashMap<Uuid, u64>>,
+    max_depth: Option<usize>,
+}
+
+impl MemoryClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rejects writes that would nest categories more than `max_depth` levels deep
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    fn read(&self) -> Result<RwLockReadGuard<'_, BTreeMap<Uuid, Category>>, CoreError> {
+        self.categories
+            .read()
+            .map_err(|e| CoreError::Database(e.to_string()))
+    }
+
+    fn write(&self) -> Result<RwLockWriteGuard<'_, BTreeMap<Uuid, Category>>, CoreError> {
+        self.categories
+            .write()
+            .map_err(|e| CoreError::Database(e.to_string()))
+    }
+
+    /// Every category passing `filter`, in no particular order
+    fn matching(&self, filter: &CategoryFilter) -> Result<Vec<Category>, CoreError> {
+        let categories = self.read()?;
+
+        Ok(categories
+            .values()
+            .filter(|category| filter.matches(category, ancestors(&categories, &category.id).len()))
+            .cloned()
+            .collect())
+    }
+
+    fn history(&self, id: &Uuid) -> Result<Vec<CategoryRevision>, CoreError> {
+        let revisions = self
+            .revisions
+            .read()
+            .map_err(|e| CoreError::Database(e.to_string()))?;
+
+        Ok(revisions.get(id).cloned().unwrap_or_default())
+    }
+
+    /// Appends a revision for each of `categories`, under the categories write lock
+    fn record<'a>(
+        &self,
+        kind: RevisionKind,
+        categories: impl IntoIterator<Item = &'a Category>,
+        caller: Option<&str>,
+    ) -> Result<(), CoreError> {
+        let mut revisions = self
+            .revisions
+            .write()
+            .map_err(|e| CoreError::Database(e.to_string()))?;
+        let recorded_at = Utc::now();
+
+        for category in categories {
+            revisions
+                .entry(category.id)
+                .or_default()
+                .push(CategoryRevision {
+                    revision: category.version,
+                    kind,
+                    category: category.to_owned(),
+                    recorded_at,
+                    recorded_by: caller.map(ToOwned::to_owned),
+                });
+        }
+
+        Ok(())
+    }
+
+    /// Records the revisions collected by a [`Changeset`]
+    fn record_changes(
+        &self,
+        changes: Vec<(RevisionKind, Category)>,
+        caller: Option<&str>,
+    ) -> Result<(), CoreError> {
+        for (kind, category) in changes.iter() {
+            self.record(*kind, [category], caller)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn build_tree(
+    categories: &BTreeMap<Uuid, Category>,
+    category: &Category,
+    max_depth: usize,
+) -> CategoryTree {
+    let children = match max_depth.checked_sub(1) {
+        Some(depth) => category
+            .sub_categories
+            .iter()
+            .filter_map(|id| categories.get(id))
+            .map(|child| build_tree(categories, child, depth))
+            .collect(),
+        None => Vec::new(),
+    };
+
+    CategoryTree {
+        category: category.to_owned(),
+        children,
+    }
+}
+
+impl QueryCategories for MemoryClient {
+    #[instrument(skip(self), err(Debug))]
+    async fn get_categories(
+        &self,
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
+        let categories: Vec<_> = self
+            .read()?
+            .values()
+            .filter(|category| category.deleted_at.is_none())
+            .cloned()
+            .collect();
+
+        Ok(tree_order(categories).into_iter())
+    }
+
+    #[instrument(skip(self), err(Debug))]
+    async fn get_sub_categories(
+        &self,
+        id: Option<&Uuid>,
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
+        let categories = self.read()?;
+
+        let mut sub_categories: Vec<_> = match id {
+            Some(id) => {
+                let parent = categories
+                    .get(id)
+                    .ok_or(CoreError::Database("Database returned no items".into()))?;
+
+                parent
+                    .sub_categories
+                    .iter()
+                    .filter_map(|id| live(&categories, id).cloned())
+                    .collect()
+            }
+            None => categories
+                .values()
+                .filter(|category| category.parent_id.is_none() && category.deleted_at.is_none())
+                .cloned()
+                .collect(),
+        };
+        sort_siblings(&mut sub_categories);
+
+        Ok(sub_categories.into_iter())
+    }
+
+    #[instrument(skip(self), err(Debug))]
+    async fn find_categories(
+        &self,
+        filter: &CategoryFilter,
+        order: Option<&CategoryOrder>,
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
+        let mut found = self.matching(filter)?;
+        let found = match order {
+            Some(order) => {
+                order.sort(&mut found);
+                found
+            }
+            None => listing_order(found),
+        };
+
+        Ok(found.into_iter())
+    }
+
+    #[instrument(skip(self), err(Debug))]
+    async fn find_categories_page(
+        &self,
+        filter: &CategoryFilter,
+        order: &CategoryOrder,
+        page: &KeysetPage,
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
+        let mut found = self.matching(filter)?;
+        found.retain(|category| page.contains(order, &order.key(category)));
+        order.sort(&mut found);
+
+        let skipped = if page.from_end {
+            found.len().saturating_sub(page.limit)
+        } else {
+            0
+        };
+        found.drain(..skipped);
+        found.truncate(page.limit);
+
+        Ok(found.into_iter())
+    }
+
+    #[instrument(skip(self), err(Debug))]
+    async fn count_categories(&self, filter: &CategoryFilter) -> Result<usize, CoreError> {
+        Ok(self.matching(filter)?.len())
+    }
+
+    #[instrument(skip(self), err(Debug))]
+    async fn search(
+        &self,
+        query: impl AsRef<str> + Send + std::fmt::Debug,
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
+        let query = query.as_ref().to_lowercase();
+
+        let categories: Vec<_> = self
+            .read()?
+            .values()
+            .filter(|category| {
+                // every translation is searched, like the search index does
+                let mut texts = std::iter::once(&category.name)
+                    .chain(category.description.iter())
+                    .chain(category.translations.iter().flat_map(|translation| {
+                        std::iter::once(&translation.name).chain(translation.description.iter())
+                    }));
+
+                category.deleted_at.is_none()
+                    && texts.any(|text| text.to_lowercase().contains(&query))
+            })
+            .cloned()
+            .collect();
+
+        Ok(categories.into_iter())
+    }
+
+    #[instrument(skip(self), err(Debug))]
+    async fn get_category_by_id(&self, id: &Uuid) -> Result<Option<Category>, CoreError> {
+        Ok(live(&*self.read()?, id).cloned())
+    }
+
+    #[instrument(skip(self), err(Debug))]
+    async fn get_categories_by_ids(
+        &self,
+        ids: &[Uuid],
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
+        let categories = self.read()?;
+
+        Ok(ids
+            .iter()
+            .filter_map(|id| live(&categories, id).cloned())
+            .collect::<Vec<_>>()
+            .into_iter())
+    }
+
+    #[instrument(skip(self), err(Debug))]
+    async fn get_category_by_slug(&self, path: &str) -> Result<Option<Category>, CoreError> {
+        let categories = self.read()?;
+
+        let mut category: Option<&Category> = None;
+        for slug in path.split('/').filter(|slug| !slug.is_empty()) {
+            let parent = category.map(|category| category.id);
+            category = categories.values().find(|category| {
+                category.deleted_at.is_none()
+                    && category.parent_id == parent
+                    && category.slug == slug
+            });
+            if category.is_none() {
+                break;
+            }
+        }
+
+        Ok(category.cloned())
+    }
+
+    #[instrument(skip(self), err(Debug))]
+    async fn get_deleted_categories(
+        &self,
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
+        let categories: Vec<_> = self
+            .read()?
+            .values()
+            .filter(|category| category.deleted_at.is_some())
+            .cloned()
+            .collect();
+
+        Ok(categories.into_iter())
+    }
+
+    #[instrument(skip(self), err(Debug))]
+    async fn get_ancestors(
+        &self,
+        id: &Uuid,
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
+        let ancestors = ancestors(&*self.read()?, id);
+
+        Ok(ancestors.into_iter())
+    }
+
+    #[instrument(skip(self), err(Debug))]
+    async fn get_category_tree(
+        &self,
+        id: Option<&Uuid>,
+        max_depth: usize,
+    ) -> Result<impl ExactSizeIterator<Item = CategoryTree> + Send, CoreError> {
+        let categories = self.read()?;
+
+        let mut tree: Vec<_> = match id {
+            Some(id) => live(&categories, id)
+                .map(|root| build_tree(&categories, root, max_depth))
+                .into_iter()
+                .collect(),
+            None => categories
+                .values()
+                .filter(|category| category.parent_id.is_none() && category.deleted_at.is_none())
+                .map(|root| build_tree(&categories, root, max_depth))
+                .collect(),
+        };
+        sort_tree(&mut tree);
+
+        Ok(tree.into_iter())
+    }
+
+    #[instrument(skip(self), err(Debug))]
+    async fn get_category_stats(
+        &self,
+        ids: &[Uuid],
+    ) -> Result<impl ExactSizeIterator<Item = CategoryStats> + Send, CoreError> {
+        let categories = self.read()?;
+        let listings = self
+            .listings
+            .read()
+            .map_err(|e| CoreError::Database(e.to_string()))?;
+
+        Ok(category_stats(&categories, &listings, ids).into_iter())
+    }
+
+    #[instrument(skip(self), err(Debug))]
+    async fn get_category_history(
+        &self,
+        id: &Uuid,
+    ) -> Result<impl ExactSizeIterator<Item = CategoryRevision> + Send, CoreError> {
+        Ok(self.history(id)?.into_iter())
+    }
+
+    #[instrument(skip(self), err(Debug))]
+    async fn get_category_at(
+        &self,
+        id: &Uuid,
+        timestamp: &DateTime<Utc>,
+    ) -> Result<Option<Category>, CoreError> {
+        let history = self.history(id)?;
+
+        Ok(history
+            .into_iter()
+            .take_while(|revision| revision.recorded_at <= *timestamp)
+            .last()
+            .filter(|revision| revision.kind != RevisionKind::Deleted)
+            .map(|revision| revision.category))
+    }
+}
+
+impl MutateCategories for MemoryClient {
+    #[instrument(skip(self), err(Debug))]
+    async fn create_category(
+        &self,
+        category: &Category,
+        caller: Option<&str>,
+    ) -> Result<Category, CoreError> {
+        let mut categories = self.write()?;
+
+        let mut changeset = Changeset::new(&mut categories, self.max_depth, caller);
+        let category = changeset.create(category, Uuid::now_v7())?;
+        self.record_changes(changeset.into_changes(), caller)?;
+
+        Ok(category)
+    }
+
+    #[instrument(skip(self, id), err(Debug))]
+    async fn update_category(
+        &self,
+        id: &Uuid,
+        data: &Category,
+        expected_version: Option<u64>,
+        caller: Option<&str>,
+    ) -> Result<Option<Category>, CoreError> {
+        let mut categories = self.write()?;
+
+        let mut changeset = Changeset::new(&mut categories, self.max_depth, caller);
+        let category = changeset.update(id, data, expected_version)?;
+        self.record_changes(changeset.into_changes(), caller)?;
+
+        Ok(category)
+    }
+
+    #[instrument(skip(self, id), err(Debug))]
+    async fn patch_category(
+        &self,
+        id: &Uuid,
+        patch: &CategoryPatch,
+        expected_version: Option<u64>,
+        caller: Option<&str>,
+    ) -> Result<Option<Category>, CoreError> {
+        let mut categories = self.write()?;
+
+        let Some(current) = live(&categories, id) else {
+            return Ok(None);
+        };
+        check_version(current, expected_version)?;
+        if let Some(ref attributes) = patch.attributes {
+            validate_attributes(attributes)?;
+        }
+        let slug = patch.name.as_deref().map(slugify);
+        if let Some(ref slug) = slug {
+            check_slug(&categories, &[*id], current.parent_id.as_ref(), slug)?;
+        }
+
+        let category = categories.get_mut(id).ok_or(CoreError::Unreachable)?;
+        if let Some(ref name) = patch.name {
+            category.name.clone_from(name);
+        }
+        if let Some(slug) = slug {
+            category.slug = slug;
+        }
+        if let Some(ref description) = patch.description {
+            category.description = Some(description.to_owned());
+        }
+        if let Some(ref translations) = patch.translations {
+            category.translations.clone_from(translations);
+        }
+        if let Some(ref image_url) = patch.image_url {
+            category.image_url = Some(image_url.to_owned());
+        }
+        if let Some(ref attributes) = patch.attributes {
+            category.attributes.clone_from(attributes);
+        }
+        touch(category, caller);
+        self.record(RevisionKind::Updated, [&*category], caller)?;
+
+        Ok(Some(category.clone()))
+    }
+
+    #[instrument(skip(self, id), err(Debug))]
+    async fn delete_category(
+        &self,
+        id: &Uuid,
+        strategy: DeleteStrategy,
+        expected_version: Option<u64>,
+        caller: Option<&str>,
+    ) -> Result<Option<DeletedCategory>, CoreError> {
+        let mut categories = self.write()?;
+
+        let mut changeset = Changeset::new(&mut categories, self.max_depth, caller);
+        let deleted = changeset.delete(id, strategy, expected_version)?;
+        self.record_changes(changeset.into_changes(), caller)?;
+
+        Ok(deleted)
+    }
+
+    #[instrument(skip(self, id), err(Debug))]
+    async fn move_category(
+        &self,
+        id: &Uuid,
+        parent: Option<&Uuid>,
+        position: Option<usize>,
+        expected_version: Option<u64>,
+        caller: Option<&str>,
+    ) -> Result<Option<Category>, CoreError> {
+        let mut categories = self.write()?;
+        check_parent(&categories, parent)?;
+
+        let Some(current) = live(&categories, id).cloned() else {
+            return Ok(None);
+        };
+        check_version(&current, expected_version)?;
+        if current.parent_id.as_ref() != parent {
+            check_hierarchy(&categories, Some(&current), parent, self.max_depth)?;
+            check_slug(&categories, &[*id], parent, &current.slug)?;
+        }
+
+        unlink_parent(&mut categories, current.parent_id.as_ref(), id);
+        let mut siblings: Vec<_> = categories
+            .values()
+            .filter(|category| {
+                category.deleted_at.is_none()
+                    && category.parent_id.as_ref() == parent
+                    && category.id != *id
+            })
+            .cloned()
+            .collect();
+        sort_siblings(&mut siblings);
+        let mut order: Vec<_> = siblings.iter().map(|sibling| sibling.id).collect();
+        let index = position.map_or(order.len(), |position| position.min(order.len()));
+        order.insert(index, *id);
+
+        // appending leaves the siblings where they are
+        let mut renumbered = Vec::new();
+        if index == siblings.len() {
+            let position = siblings.last().map_or(0, |last| last.position + 1);
+            categories
+                .get_mut(id)
+                .ok_or(CoreError::Unreachable)?
+                .position = position;
+        } else {
+            for (position, sibling) in order.iter().enumerate() {
+                let Some(sibling) = categories.get_mut(sibling) else {
+                    continue;
+                };
+                if sibling.id == *id {
+                    sibling.position = position as u32;
+                } else if sibling.position != position as u32 {
+                    // only the siblings that change position get a new version
+                    sibling.position = position as u32;
+                    touch(sibling, caller);
+                    renumbered.push(sibling.clone());
+                }
+            }
+        }
+        if let Some(parent) = parent.and_then(|parent| categories.get_mut(parent)) {
+            parent.sub_categories = order;
+        }
+
+        let category = categories.get_mut(id).ok_or(CoreError::Unreachable)?;
+        category.parent_id = parent.copied();
+        touch(category, caller);
+        let category = category.clone();
+        self.record(
+            RevisionKind::Updated,
+            std::iter::once(&category).chain(renumbered.iter()),
+            caller,
+        )?;
+
+        Ok(Some(category))
+    }
+
+    #[instrument(skip(self), err(Debug))]
+    async fn reorder_sub_categories(
+        &self,
+        parent: Option<&Uuid>,
+        ordered: &[Uuid],
+        caller: Option<&str>,
+    ) -> Result<Option<Vec<Category>>, CoreError> {
+        let mut categories = self.write()?;
+
+        if parent.is_some_and(|parent| live(&categories, parent).is_none()) {
+            return Ok(None);
+        }
+        let siblings: Vec<_> = categories
+            .values()
+            .filter(|category| {
+                category.deleted_at.is_none() && category.parent_id.as_ref() == parent
+            })
+            .map(|category| category.id)
+            .collect();
+        check_order(&siblings, ordered)?;
+
+        let mut reordered = Vec::with_capacity(ordered.len());
+        let mut revised = Vec::new();
+        for (position, id) in ordered.iter().enumerate() {
+            let category = categories.get_mut(id).ok_or(CoreError::Unreachable)?;
+            if category.position != position as u32 {
+                category.position = position as u32;
+                touch(category, caller);
+                revised.push(category.clone());
+            }
+            reordered.push(category.clone());
+        }
+        if let Some(parent) = parent.and_then(|parent| categories.get_mut(parent)) {
+            parent.sub_categories = ordered.to_vec();
+        }
+        self.record(RevisionKind::Updated, &revised, caller)?;
+
+        Ok(Some(reordered))
+    }
+
+    #[instrument(skip(self), err(Debug))]
+    async fn adjust_listing_count(&self, id: &Uuid, delta: i64) -> Result<Option<u64>, CoreError> {
+        // held so the category cannot be purged halfway
+        let categories = self.read()?;
+        if live(&categories, id).is_none() {
+            return Ok(None);
+        }
+        let mut listings = self
+            .listings
+            .write()
+            .map_err(|e| CoreError::Database(e.to_string()))?;
+
+        let count = listings.entry(*id).or_default();
+        *count = count.saturating_add_signed(delta);
+
+        Ok(Some(*count))
+    }
+
+    #[instrument(skip(self, id), err(Debug))]
+    async fn restore_category(
+        &self,
+        id: &Uuid,
+        revision: u64,
+        caller: Option<&str>,
+    ) -> Result<Option<Category>, CoreError> {
+        let mut categories = self.write()?;
+
+        let history = self.history(id)?;
+        let (Some(target), Some(latest)) = (
+            history.iter().find(|item| item.revision == revision),
+            history.last(),
+        ) else {
+            return Ok(None);
+        };
+        let snapshot = &target.category;
+        check_parent(&categories, snapshot.parent_id.as_ref())?;
+
+        let current = categories.get(id).cloned();
+        let relink = !current.as_ref().is_some_and(|current| {
+            current.deleted_at.is_none() && current.parent_id == snapshot.parent_id
+        });
+        if relink {
+            check_hierarchy(
+                &categories,
+                current.as_ref(),
+                snapshot.parent_id.as_ref(),
+                self.max_depth,
+            )?;
+        }
+        let slug = slugify(&snapshot.name);
+        check_slug(&categories, &[*id], snapshot.parent_id.as_ref(), &slug)?;
+        let position = match current {
+            Some(ref current) if !relink => current.position,
+            _ => next_position(&categories, snapshot.parent_id.as_ref()),
+        };
+
+        let category = Category {
+            slug,
+            position,
+            sub_categories: current
+                .as_ref()
+                .map(|current| {
+                    current
+                        .sub_categories
+                        .iter()
+                        .filter(|child| live(&categories, child).is_some())
+                        .copied()
+                        .collect()
+                })
+                .unwrap_or_default(),
+            version: latest.revision + 1,
+            updated_at: Some(Utc::now()),
+            updated_by: caller.map(ToOwned::to_owned),
+            deleted_at: None,
+            ..snapshot.to_owned()
+        };
+        if relink {
+            // a category in the trash is still linked when its parent went to the trash with it
+            if let Some(ref current) = current {
+                unlink_parent(&mut categories, current.parent_id.as_ref(), id);
+            }
+            link_parent(&mut categories, category.parent_id.as_ref(), id);
+        }
+        categories.insert(*id, category.clone());
+        self.record(RevisionKind::Restored, [&category], caller)?;
+
+        Ok(Some(category))
+    }
+
+    #[instrument(skip(self, id), err(Debug))]
+    async fn undelete_category(
+        &self,
+        id: &Uuid,
+        caller: Option<&str>,
+    ) -> Result<Option<RestoredCategory>, CoreError> {
+        let mut categories = self.write()?;
+
+        let Some(category) = categories
+            .get(id)
+            .filter(|category| category.deleted_at.is_some())
+            .cloned()
+        else {
+            return Ok(None);
+        };
+        check_parent(&categories, category.parent_id.as_ref())?;
+        check_hierarchy(
+            &categories,
+            Some(&category),
+            category.parent_id.as_ref(),
+            self.max_depth,
+        )?;
+        check_slug(
+            &categories,
+            &[*id],
+            category.parent_id.as_ref(),
+            &category.slug,
+        )?;
+
+        let restored: Vec<_> = std::iter::once(*id)
+            .chain(descendant_levels(&categories, &category).concat())
+            .collect();
+        // back after its siblings, as it is linked last
+        let position = next_position(&categories, category.parent_id.as_ref());
+        for id in restored.iter() {
+            if let Some(category) = categories.get_mut(id) {
+                category.deleted_at = None;
+                touch(category, caller);
+            }
+        }
+        categories
+            .get_mut(id)
+            .ok_or(CoreError::Unreachable)?
+            .position = position;
+        link_parent(&mut categories, category.parent_id.as_ref(), id);
+        self.record(
+            RevisionKind::Restored,
+            restored.iter().filter_map(|id| categories.get(id)),
+            caller,
+        )?;
+
+        Ok(categories
+            .get(id)
+            .cloned()
+            .map(|category| RestoredCategory {
+                category,
+                restored: restored[1..].to_vec(),
+            }))
+    }
+
+    #[instrument(skip(self, id), err(Debug))]
+    async fn purge_category(&self, id: &Uuid) -> Result<Option<DeletedCategory>, CoreError> {
+        let mut categories = self.write()?;
+
+        let Some(category) = categories.get(id).cloned() else {
+            return Ok(None);
+        };
+        if category.deleted_at.is_none() {
+            return Err(CoreError::NotDeleted(*id));
+        }
+
+        let mut listings = self
+            .listings
+            .write()
+            .map_err(|e| CoreError::Database(e.to_string()))?;
+
+        let deleted = descendant_levels(&categories, &category).concat();
+        for descendant in deleted.iter() {
+            categories.remove(descendant);
+            listings.remove(descendant);
+        }
+        categories.remove(id);
+        listings.remove(id);
+        // still linked when its parent went to the trash along with it
+        unlink_parent(&mut categories, category.parent_id.as_ref(), id);
+
+        Ok(Some(DeletedCategory {
+            category,
+            deleted,
+            reparented: Vec::new(),
+        }))
+    }
+
+    #[instrument(skip(self), err(Debug))]
+    async fn create_categories(
+        &self,
+        categories: &[Category],
+        caller: Option<&str>,
+    ) -> Result<Vec<Category>, CoreError> {
+        let mut stored = self.write()?;
+        // applied to a copy so nothing is kept when one of the categories is rejected
+        let mut staged = stored.clone();
+
+        let mut changeset = Changeset::new(&mut staged, self.max_depth, caller);
+        let mut created = Vec::with_capacity(categories.len());
+        for (index, category) in hierarchy::creation_order(categories)? {
+            created.push((index, changeset.create(&category, category.id)?));
+        }
+        let changes = changeset.into_changes();
+        *stored = staged;
+        self.record_changes(changes, caller)?;
+
+        created.sort_by_key(|(index, _)| *index);
+        Ok(created.into_iter().map(|(_, category)| category).collect())
+    }
+
+    #[instrument(skip(self), err(Debug))]
+    async fn update_categories(
+        &self,
+        updates: &[CategoryUpdate],
+        caller: Option<&str>,
+    ) -> Result<Vec<Option<Category>>, CoreError> {
+        let mut stored = self.write()?;
+        let mut staged = stored.clone();
+
+        let mut changeset = Changeset::new(&mut staged, self.max_depth, caller);
+        let updated = updates
+            .iter()
+            .map(|update| changeset.update(&update.id, &update.category, update.expected_version))
+            .collect::<Result<Vec<_>, _>>()?;
+        let changes = changeset.into_changes();
+        *stored = staged;
+        self.record_changes(changes, caller)?;
+
+        Ok(updated)
+    }
+
+    #[instrument(skip(self), err(Debug))]
+    async fn delete_categories(
+        &self,
+        deletions: &[CategoryDeletion],
+        strategy: DeleteStrategy,
+        caller: Option<&str>,
+    ) -> Result<Vec<Option<DeletedCategory>>, CoreError> {
+        let mut stored = self.write()?;
+        let mut staged = stored.clone();
+
+        let mut changeset = Changeset::new(&mut staged, self.max_depth, caller);
+        let deleted = deletions
+            .iter()
+            .map(|deletion| changeset.delete(&deletion.id, strategy, deletion.expected_version))
+            .collect::<Result<Vec<_>, _>>()?;
+        let changes = changeset.into_changes();
+        *stored = staged;
+        self.record_changes(changes, caller)?;
+
+        Ok(deleted)
+    }
+}
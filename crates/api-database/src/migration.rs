@@ -0,0 +1,122 @@
+use std::collections::HashSet;
+
+use api_core::slugify;
+use serde::{Deserialize, Serialize};
+use surrealdb::{
+    engine::any::Any,
+    opt::RecordId,
+    sql::{Datetime, Thing},
+    Surreal,
+};
+use tracing::{debug, instrument};
+
+use crate::{collections::Collection, entity::id_to_string, ClientError};
+
+/// Keeps siblings from sharing a slug, `deleted_at` leaving the trash out
+const SCHEMA: &str =
+    "DEFINE INDEX category_slug ON TABLE category FIELDS parent_id, slug, deleted_at UNIQUE";
+
+/// Name the slug backfill is recorded under once applied
+const SLUGS: &str = "slugs";
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct SlugEntity {
+    pub id: RecordId,
+    pub name: String,
+    #[serde(default)]
+    pub slug: Option<String>,
+    pub parent_id: Option<RecordId>,
+    #[serde(default)]
+    pub deleted_at: Option<Datetime>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub(crate) struct Backfill {
+    pub id: Thing,
+    pub slug: String,
+}
+
+/// Slugs for categories whose slug is missing or stale, suffixed to stay unique among siblings
+pub(crate) fn backfill_slugs(categories: &[SlugEntity]) -> Vec<Backfill> {
+    let sibling_key = |category: &SlugEntity, slug: &str| {
+        (
+            category.parent_id.as_ref().map(ToString::to_string),
+            category.deleted_at.as_ref().map(ToString::to_string),
+            slug.to_owned(),
+        )
+    };
+
+    let (current, stale): (Vec<_>, Vec<_>) = categories.iter().partition(|category| {
+        let slug = slugify(&category.name);
+        !slug.is_empty() && category.slug.as_ref() == Some(&slug)
+    });
+    let mut taken: HashSet<_> = current
+        .iter()
+        .map(|category| sibling_key(category, &slugify(&category.name)))
+        .collect();
+
+    stale
+        .into_iter()
+        .map(|category| {
+            let base = match slugify(&category.name) {
+                slug if slug.is_empty() => id_to_string(&category.id.id),
+                slug => slug,
+            };
+            let mut slug = base.clone();
+            let mut suffix = 1;
+            while !taken.insert(sibling_key(category, &slug)) {
+                suffix += 1;
+                slug = format!("{base}-{suffix}");
+            }
+            Backfill {
+                id: category.id.clone(),
+                slug,
+            }
+        })
+        .collect()
+}
+
+/// Backfills slugs and positions once, then defines the schema
+#[instrument(skip(db), err(Debug))]
+pub(crate) async fn migrate(db: &Surreal<Any>) -> Result<(), ClientError> {
+    let applied: Option<serde_json::Value> = db
+        .select((Collection::Migration.to_string(), SLUGS))
+        .await?;
+
+    if applied.is_none() {
+        let categories: Vec<SlugEntity> = db
+            .query(
+                "SELECT id, name, slug, parent_id, deleted_at FROM type::table($table) ORDER BY id",
+            )
+            .bind(("table", Collection::Category))
+            .await?
+            .take(0)?;
+        let backfill = backfill_slugs(&categories);
+        debug!(count = backfill.len(), "backfilling category slugs");
+
+        db.query("BEGIN TRANSACTION")
+            .query(
+                "FOR $entry IN $backfill { UPDATE type::thing($entry.id) SET slug = $entry.slug }",
+            )
+            .query("UPDATE type::table($table) SET position = 0 WHERE position IS NONE")
+            .query("CREATE type::thing($migration) SET applied_at = time::now()")
+            .query("COMMIT TRANSACTION")
+            .bind(("backfill", &backfill))
+            .bind(("table", Collection::Category))
+            .bind((
+                "migration",
+                Thing::from((Collection::Migration.to_string(), SLUGS.to_owned())),
+            ))
+            .await?
+            .check()?;
+    }
+
+    // siblings already sharing a slug keep the index from being defined
+    db.query(SCHEMA)
+        .await
+        .map_err(ClientError::Schema)?
+        .check()
+        .map_err(ClientError::Schema)?;
+
+    Ok(())
+}
@@ -0,0 +1,252 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use api_core::{api::CoreError, reexports::uuid::Uuid, Category, RevisionKind};
+use surrealdb::sql::Thing;
+
+use crate::{
+    collections::Collection,
+    entity::DatabaseEntity,
+    map_db_error,
+    query::db_select_by_ids,
+    redis::{cache_keys::CacheKey, redis_query},
+    Client,
+};
+
+use super::{record_id, InputCategory};
+
+/// Overwrites every category in `$updated`, aborting when one changed since it was read
+const UPDATE_BATCH: &str = "FOR $write IN $updated { IF (($write.id.version OR 0) != $write.version) OR ($write.id.sub_categories != $write.sub_categories) { THROW \"version conflict \" + <string> $write.id }; UPDATE type::thing($write.id) CONTENT $write.content }";
+
+const CREATE_BATCH: &str =
+    "FOR $write IN $created { CREATE type::thing($write.id) CONTENT $write.content }";
+
+/// Records a revision for every change in `$changes`
+const RECORD_CHANGES: &str = "FOR $change IN $changes { CREATE category_revision CONTENT { category_id: $change.category.id, revision: $change.category.version, kind: $change.kind, category: $change.category, recorded_at: time::now(), recorded_by: $caller } }";
+
+fn map_batch_error(ids: &[Uuid]) -> impl Fn(surrealdb::Error) -> CoreError + '_ {
+    move |error| {
+        let message = error.to_string();
+        let id = ids
+            .iter()
+            .find(|id| message.contains(id.to_string().as_str()));
+
+        match id {
+            Some(id) if message.contains("version conflict") => CoreError::VersionConflict(*id),
+            Some(id) if message.contains("already exists") => CoreError::AlreadyExists(*id),
+            _ => map_db_error(error),
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+struct BatchWrite<'a> {
+    id: Thing,
+    /// The version the category had when the batch was planned
+    version: u64,
+    /// The subcategories when the batch was planned, as linking a child keeps the version
+    sub_categories: Vec<Thing>,
+    content: InputCategory<'a>,
+}
+
+#[derive(serde::Serialize)]
+struct BatchChange<'a> {
+    kind: RevisionKind,
+    category: RevisionContent<'a>,
+}
+
+#[derive(serde::Serialize)]
+struct RevisionContent<'a> {
+    id: Thing,
+    #[serde(flatten)]
+    content: InputCategory<'a>,
+}
+
+impl Client {
+    /// Reads `ids` with their subtrees and siblings, and the ancestors of those and of `parents`
+    pub(super) async fn snapshot(
+        &self,
+        ids: &[Uuid],
+        parents: &[Option<Uuid>],
+    ) -> Result<BTreeMap<Uuid, Category>, CoreError> {
+        let mut categories = BTreeMap::new();
+
+        let mut pending: Vec<_> = ids
+            .iter()
+            .chain(parents.iter().flatten())
+            .copied()
+            .collect();
+        while !pending.is_empty() {
+            let level = db_select_by_ids(self, &pending, true).await?;
+            pending = level
+                .iter()
+                .filter_map(|category| category.parent_id)
+                .collect();
+            categories.extend(level.into_iter().map(|category| (category.id, category)));
+            pending.retain(|id| !categories.contains_key(id));
+        }
+
+        // the siblings of every category of the batch, where it is now and where it goes
+        let slots: BTreeSet<_> = ids
+            .iter()
+            .filter_map(|id| categories.get(id))
+            .map(|category| category.parent_id)
+            .chain(parents.iter().copied())
+            .collect();
+        if slots.contains(&None) {
+            let top_level: Vec<DatabaseEntity> = self
+                .client
+                .query("SELECT * FROM type::table($table) WHERE (parent_id is none or null) AND deleted_at is none")
+                .bind(("table", Collection::Category))
+                .await
+                .map_err(map_db_error)?
+                .take(0)
+                .map_err(map_db_error)?;
+            for item in top_level {
+                let category = Category::try_from(item)?;
+                categories.entry(category.id).or_insert(category);
+            }
+        }
+
+        let mut subtrees: HashSet<_> = ids.iter().copied().collect();
+        let mut pending: Vec<_> = slots
+            .iter()
+            .flatten()
+            .chain(ids)
+            .filter_map(|id| categories.get(id))
+            .flat_map(|category| category.sub_categories.iter().copied())
+            .filter(|id| !categories.contains_key(id))
+            .collect();
+        while !pending.is_empty() {
+            pending.sort();
+            pending.dedup();
+            let level = db_select_by_ids(self, &pending, true).await?;
+            pending = level
+                .iter()
+                .filter(|category| {
+                    category
+                        .parent_id
+                        .is_some_and(|parent| subtrees.contains(&parent))
+                })
+                .flat_map(|category| category.sub_categories.iter().copied())
+                .collect();
+            for category in level {
+                if category
+                    .parent_id
+                    .is_some_and(|parent| subtrees.contains(&parent))
+                {
+                    subtrees.insert(category.id);
+                }
+                categories.insert(category.id, category);
+            }
+            pending.retain(|id| !categories.contains_key(id));
+        }
+
+        Ok(categories)
+    }
+
+    /// Writes the difference between `snapshot` and `staged` in a single transaction
+    pub(super) async fn write_batch(
+        &self,
+        snapshot: &BTreeMap<Uuid, Category>,
+        staged: &BTreeMap<Uuid, Category>,
+        changes: Vec<(RevisionKind, Category)>,
+        caller: Option<&str>,
+    ) -> Result<BTreeMap<Uuid, Category>, CoreError> {
+        let mut touched = Vec::new();
+        let mut created = Vec::new();
+        let mut updated = Vec::new();
+        for (id, category) in staged {
+            let previous = snapshot.get(id);
+            if previous == Some(category) {
+                continue;
+            }
+
+            touched.push(*id);
+            let write = BatchWrite {
+                id: record_id(id),
+                version: previous.map_or(0, |previous| previous.version),
+                sub_categories: previous
+                    .map(|previous| previous.sub_categories.iter().map(record_id).collect())
+                    .unwrap_or_default(),
+                content: InputCategory::from(category),
+            };
+            match previous {
+                Some(_) => updated.push(write),
+                None => created.push(write),
+            }
+        }
+        if touched.is_empty() {
+            return Ok(BTreeMap::new());
+        }
+
+        // every change moved its category one version on, so each gets a revision of its own
+        let changes: Vec<_> = changes
+            .iter()
+            .map(|(kind, category)| BatchChange {
+                kind: *kind,
+                category: RevisionContent {
+                    id: record_id(&category.id),
+                    content: InputCategory::from(category),
+                },
+            })
+            .collect();
+
+        let mut resp = self
+            .client
+            .query("BEGIN TRANSACTION")
+            .query(UPDATE_BATCH)
+            .query(CREATE_BATCH)
+            .query(RECORD_CHANGES)
+            .query("SELECT * FROM category WHERE id INSIDE $touched")
+            .query("COMMIT TRANSACTION")
+            .bind(("updated", &updated))
+            .bind(("created", &created))
+            .bind(("changes", &changes))
+            .bind(("touched", touched.iter().map(record_id).collect::<Vec<_>>()))
+            .bind(("caller", caller))
+            .await
+            .map_err(map_batch_error(&touched))?
+            .check()
+            .map_err(map_batch_error(&touched))?;
+
+        let items: Vec<DatabaseEntity> = resp.take(3).map_err(map_db_error)?;
+        let stored = items
+            .into_iter()
+            .map(|item| Category::try_from(item).map(|category| (category.id, category)))
+            .collect::<Result<BTreeMap<_, _>, _>>()?;
+
+        if let Some((ref redis, _ttl)) = self.redis {
+            let mut parents = BTreeSet::new();
+            for id in touched.iter() {
+                parents.insert(Some(*id));
+                for category in [snapshot.get(id), staged.get(id)].into_iter().flatten() {
+                    parents.insert(category.parent_id);
+                }
+            }
+
+            let mut cache_keys = vec![CacheKey::AllCategories];
+            cache_keys.extend(touched.iter().map(|id| CacheKey::Category { id }));
+            cache_keys.extend(parents.iter().map(|parent| CacheKey::SubCategories {
+                parent: parent.as_ref(),
+            }));
+
+            redis_query::invalidate(&cache_keys, redis).await;
+        }
+
+        let removed: Vec<_> = stored
+            .values()
+            .filter(|category| {
+                category.deleted_at.is_some()
+                    && snapshot
+                        .get(&category.id)
+                        .is_some_and(|previous| previous.deleted_at.is_none())
+            })
+            .map(|category| category.id)
+            .collect();
+        if !removed.is_empty() {
+            self.remove_from_search(&removed).await;
+        }
+
+        Ok(stored)
+    }
+}
@@ -1,58 +1,347 @@
+mod batch;
+
+use std::collections::{HashMap, HashSet};
+
 use api_core::{
-    api::{CoreError, MutateCategories},
-    reexports::uuid::Uuid,
-    Category,
+    api::{CoreError, MutateCategories, QueryCategories},
+    reexports::{chrono::Utc, uuid::Uuid},
+    slugify, validate_attributes, AttributeDefinition, Category, CategoryDeletion, CategoryPatch,
+    CategoryUpdate, DeleteStrategy, DeletedCategory, RestoredCategory, RevisionKind, Translation,
+};
+use surrealdb::{
+    opt::RecordId,
+    sql::{Datetime, Thing},
 };
-use surrealdb::{opt::RecordId, sql::Thing};
 use tracing::{error, instrument};
 
 use crate::{
     collections::Collection,
-    entity::DatabaseEntity,
+    entity::{id_to_string, DatabaseEntity},
+    hierarchy::{self, check_order, sort_siblings, Changeset},
     map_db_error,
-    redis::{cache_keys::CacheKey, PoolLike, PooledConnectionLike},
+    redis::{cache_keys::CacheKey, redis_query},
     Client,
 };
 
-impl MutateCategories for Client {
-    #[instrument(skip(self), err(Debug))]
-    async fn create_category(&self, category: &Category) -> Result<Category, CoreError> {
-        if let Some(ref parent) = category.parent_id {
-            let id = Thing::from(("category", parent.to_string().as_str()));
+/// Aborts the surrounding transaction when the stored version moved on since it was read
+const CHECK_VERSION: &str = "IF ($record.version OR 0) != $version { THROW \"version conflict\" }";
+
+/// Snapshots every category in `$revised`, as currently stored, into a revision of kind `$kind`
+const RECORD_REVISIONS: &str = "FOR $category IN (SELECT * FROM category WHERE id INSIDE $revised) { CREATE category_revision CONTENT { category_id: $category.id, revision: $category.version OR 0, kind: $kind, category: $category, recorded_at: time::now(), recorded_by: $caller } }";
+
+/// Snapshots every category in `$removed` into a revision of kind `deleted`
+const RECORD_DELETIONS: &str = "FOR $category IN (SELECT * FROM category WHERE id INSIDE $removed) { CREATE category_revision CONTENT { category_id: $category.id, revision: $category.version OR 0, kind: 'deleted', category: $category, recorded_at: time::now(), recorded_by: $caller } }";
+
+/// Gives every category in `$renumbered` its new position among its siblings
+const RENUMBER: &str = "FOR $entry IN $renumbered { UPDATE type::thing($entry.id) SET position = $entry.position, version += 1, updated_at = time::now(), updated_by = $caller }";
+
+/// Moves a category to the trash, `$now` is shared by everything deleted in one go
+const TRASH: &str = "deleted_at = $now, version += 1, updated_at = $now, updated_by = $caller";
+
+fn map_write_error(id: &Uuid) -> impl Fn(surrealdb::Error) -> CoreError + '_ {
+    move |error| {
+        if error.to_string().contains("version conflict") {
+            CoreError::VersionConflict(*id)
+        } else {
+            map_db_error(error)
+        }
+    }
+}
+
+fn check_version(category: &Category, expected_version: Option<u64>) -> Result<(), CoreError> {
+    match expected_version {
+        Some(version) if version != category.version => {
+            Err(CoreError::VersionConflict(category.id))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Treats categories in the trash like missing ones
+fn live(category: Option<Category>) -> Option<Category> {
+    category.filter(|category| category.deleted_at.is_none())
+}
+
+fn record_id(id: &Uuid) -> Thing {
+    Thing::from((
+        Collection::Category.to_string().as_str(),
+        id.to_string().as_str(),
+    ))
+}
+
+impl Client {
+    async fn check_parent(&self, parent: Option<&Uuid>) -> Result<(), CoreError> {
+        if let Some(parent) = parent {
+            let id = record_id(parent);
             let item: Option<DatabaseEntity> =
                 self.client.select(&id).await.map_err(map_db_error)?;
-            if item.is_none() {
+            if !item.is_some_and(|item| item.deleted_at.is_none()) {
                 return Err(CoreError::Database(format!(
                     "provided parent does not exist: {id}"
                 )));
             }
         }
-        let input_category = InputCategory::from(category);
+        Ok(())
+    }
+
+    /// The categories under `parent` other than `except`, in order
+    async fn siblings(
+        &self,
+        parent: Option<&Uuid>,
+        except: Option<&Uuid>,
+    ) -> Result<Vec<Category>, CoreError> {
+        let query = match parent {
+            Some(_) => {
+                "SELECT * FROM type::table($table) WHERE parent_id = $parent AND deleted_at is none AND id != $except"
+            }
+            None => {
+                "SELECT * FROM type::table($table) WHERE (parent_id is none or null) AND deleted_at is none AND id != $except"
+            }
+        };
+
+        let items: Vec<DatabaseEntity> = self
+            .client
+            .query(query)
+            .bind(("table", Collection::Category))
+            .bind(("parent", parent.map(record_id)))
+            .bind(("except", except.map(record_id)))
+            .await
+            .map_err(map_db_error)?
+            .take(0)
+            .map_err(map_db_error)?;
+        let mut siblings = items
+            .into_iter()
+            .map(Category::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        sort_siblings(&mut siblings);
+
+        Ok(siblings)
+    }
+
+    /// The position after the last category under `parent`
+    async fn next_position(&self, parent: Option<&Uuid>) -> Result<u32, CoreError> {
+        let siblings = self.siblings(parent, None).await?;
+
+        Ok(siblings.last().map_or(0, |last| last.position + 1))
+    }
 
-        let id = Uuid::now_v7().to_string();
-        let item: Option<DatabaseEntity> = self
+    /// Makes sure no category under `parent`, other than `except`, goes by one of `slugs`
+    async fn check_slugs(
+        &self,
+        except: &[Uuid],
+        parent: Option<&Uuid>,
+        slugs: &[String],
+    ) -> Result<(), CoreError> {
+        if slugs.iter().any(String::is_empty) {
+            return Err(CoreError::EmptySlug);
+        }
+        let query = match parent {
+            Some(_) => {
+                "SELECT VALUE slug FROM type::table($table) WHERE parent_id = $parent AND slug INSIDE $slugs AND deleted_at is none AND id NOTINSIDE $except LIMIT 1"
+            }
+            None => {
+                "SELECT VALUE slug FROM type::table($table) WHERE (parent_id is none or null) AND slug INSIDE $slugs AND deleted_at is none AND id NOTINSIDE $except LIMIT 1"
+            }
+        };
+
+        let taken: Vec<String> = self
             .client
-            .create(("category", id))
-            .content(input_category)
+            .query(query)
+            .bind(("table", Collection::Category))
+            .bind(("parent", parent.map(record_id)))
+            .bind(("slugs", slugs))
+            .bind(("except", except.iter().map(record_id).collect::<Vec<_>>()))
             .await
+            .map_err(map_db_error)?
+            .take(0)
             .map_err(map_db_error)?;
 
+        match taken.into_iter().next() {
+            Some(slug) => Err(CoreError::DuplicateSlug {
+                slug,
+                parent_id: parent.copied(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Makes sure `parent` can hold `category` without a cycle or nesting too deep
+    async fn check_hierarchy(
+        &self,
+        category: Option<&Category>,
+        parent: Option<&Uuid>,
+    ) -> Result<(), CoreError> {
+        let Some(parent) = parent else {
+            return Ok(());
+        };
+
+        let mut chain: Vec<_> = self
+            .get_ancestors(parent)
+            .await?
+            .map(|category| category.id)
+            .collect();
+        chain.push(*parent);
+
+        if let Some(category) = category {
+            if chain.contains(&category.id) {
+                return Err(CoreError::CyclicHierarchy {
+                    id: category.id,
+                    parent: *parent,
+                });
+            }
+        }
+
+        if let Some(max_depth) = self.max_depth {
+            let height = match category {
+                Some(category) => self.descendant_levels(category).await?.len(),
+                None => 0,
+            };
+            if chain.len() + 1 + height > max_depth {
+                return Err(CoreError::MaxDepthExceeded(max_depth));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The IDs at each level below `category`
+    async fn descendant_levels(&self, category: &Category) -> Result<Vec<Vec<Uuid>>, CoreError> {
+        let mut visited = HashSet::from([category.id]);
+        let mut levels = Vec::new();
+        let mut pending = category.sub_categories.clone();
+
+        while !pending.is_empty() {
+            let records: Vec<_> = pending
+                .drain(..)
+                .filter(|id| visited.insert(*id))
+                .map(|id| record_id(&id))
+                .collect();
+
+            let children: Vec<DatabaseEntity> = self
+                .client
+                .query("SELECT * FROM $records")
+                .bind(("records", records))
+                .await
+                .map_err(map_db_error)?
+                .take(0)
+                .map_err(map_db_error)?;
+
+            let mut level = Vec::with_capacity(children.len());
+            for child in children {
+                let child = Category::try_from(child)?;
+                pending.extend(child.sub_categories);
+                level.push(child.id);
+            }
+            if !level.is_empty() {
+                levels.push(level);
+            }
+        }
+
+        Ok(levels)
+    }
+
+    /// Drops `ids` from the search index so deleted categories stop showing up in results
+    async fn remove_from_search(&self, ids: &[Uuid]) {
+        if let Some(ref client) = self.search_client {
+            if let Err(e) = client.index("categories").delete_documents(ids).await {
+                error!("[search delete]: {e}");
+            }
+        }
+    }
+
+    /// Adds restored categories back to the search index
+    async fn add_to_search(&self, categories: &[Category]) {
+        if let Some(ref client) = self.search_client {
+            if let Err(e) = client
+                .index("categories")
+                .add_documents(categories, Some("id"))
+                .await
+            {
+                error!("[search update]: {e}");
+            }
+        }
+    }
+}
+
+impl MutateCategories for Client {
+    #[instrument(skip(self), err(Debug))]
+    async fn create_category(
+        &self,
+        category: &Category,
+        caller: Option<&str>,
+    ) -> Result<Category, CoreError> {
+        validate_attributes(&category.attributes)?;
+        self.check_parent(category.parent_id.as_ref()).await?;
+        self.check_hierarchy(None, category.parent_id.as_ref())
+            .await?;
+        let slug = slugify(&category.name);
+        self.check_slugs(
+            &[],
+            category.parent_id.as_ref(),
+            std::slice::from_ref(&slug),
+        )
+        .await?;
+
+        let position = self.next_position(category.parent_id.as_ref()).await?;
+
+        let now = Utc::now();
+        let category = Category {
+            slug,
+            position,
+            // sub_categories are linked as children get created
+            sub_categories: Vec::new(),
+            version: 1,
+            created_at: Some(now),
+            updated_at: Some(now),
+            created_by: caller.map(ToOwned::to_owned),
+            updated_by: caller.map(ToOwned::to_owned),
+            ..category.to_owned()
+        };
+        let input_category = InputCategory::from(&category);
+
+        let id = record_id(&Uuid::now_v7());
+
+        let mut query = self
+            .client
+            .query("BEGIN TRANSACTION")
+            .query("CREATE type::thing($record) CONTENT $content");
+        if input_category.parent_id.is_some() {
+            query = query
+                .query("UPDATE type::thing($parent) SET sub_categories += type::thing($record)");
+        }
+
+        let mut resp = query
+            .query(RECORD_REVISIONS)
+            .query("COMMIT TRANSACTION")
+            .bind(("record", &id))
+            .bind(("parent", &input_category.parent_id))
+            .bind(("content", &input_category))
+            .bind(("revised", [&id]))
+            .bind(("kind", RevisionKind::Created))
+            .bind(("caller", caller))
+            .await
+            .map_err(map_db_error)?
+            .check()
+            .map_err(map_db_error)?;
+
+        let item: Option<DatabaseEntity> = resp.take(0).map_err(map_db_error)?;
+
         match item {
             Some(e) => {
                 let category = Category::try_from(e)?;
 
                 if let Some((ref redis, _ttl)) = self.redis {
-                    let sub_categories = CacheKey::SubCategories {
-                        parent: category.parent_id.as_ref(),
-                    };
-
-                    let mut redis = redis.get().await.unwrap();
-                    let mut pipeline = redis::Pipeline::new();
-                    pipeline.del(CacheKey::AllCategories).del(sub_categories);
-
-                    if let Err(e) = redis.query_async_pipeline::<()>(pipeline).await {
-                        error!("{e}");
+                    let mut cache_keys = vec![
+                        CacheKey::AllCategories,
+                        CacheKey::SubCategories {
+                            parent: category.parent_id.as_ref(),
+                        },
+                    ];
+                    if let Some(ref parent) = category.parent_id {
+                        cache_keys.push(CacheKey::Category { id: parent });
                     }
+
+                    redis_query::invalidate(&cache_keys, redis).await;
                 }
 
                 Ok(category)
@@ -66,51 +355,174 @@ impl MutateCategories for Client {
         &self,
         id: &Uuid,
         data: &Category,
+        expected_version: Option<u64>,
+        caller: Option<&str>,
     ) -> Result<Option<Category>, CoreError> {
-        if let Some(ref parent) = data.parent_id {
-            let id = Thing::from(("category", parent.to_string().as_str()));
-            let item: Option<DatabaseEntity> =
-                self.client.select(&id).await.map_err(map_db_error)?;
-            if item.is_none() {
-                return Err(CoreError::Database(format!(
-                    "provided parent does not exist: {id}"
-                )));
+        validate_attributes(&data.attributes)?;
+        self.check_parent(data.parent_id.as_ref()).await?;
+
+        let record = record_id(id);
+
+        let current: Option<DatabaseEntity> =
+            self.client.select(&record).await.map_err(map_db_error)?;
+        let Some(current) = live(current.map(Category::try_from).transpose()?) else {
+            return Ok(None);
+        };
+        check_version(&current, expected_version)?;
+        if current.parent_id != data.parent_id {
+            self.check_hierarchy(Some(&current), data.parent_id.as_ref())
+                .await?;
+        }
+        let slug = slugify(&data.name);
+        self.check_slugs(&[*id], data.parent_id.as_ref(), std::slice::from_ref(&slug))
+            .await?;
+        let position = if current.parent_id == data.parent_id {
+            current.position
+        } else {
+            self.next_position(data.parent_id.as_ref()).await?
+        };
+
+        let data = Category {
+            slug,
+            position,
+            sub_categories: current.sub_categories,
+            version: current.version + 1,
+            created_at: current.created_at,
+            updated_at: Some(Utc::now()),
+            created_by: current.created_by,
+            updated_by: caller.map(ToOwned::to_owned),
+            ..data.to_owned()
+        };
+        let input_category = InputCategory::from(&data);
+
+        let mut query = self
+            .client
+            .query("BEGIN TRANSACTION")
+            .query(CHECK_VERSION)
+            .query("UPDATE type::thing($record) CONTENT $content");
+        if current.parent_id != data.parent_id {
+            if current.parent_id.is_some() {
+                query = query.query(
+                    "UPDATE type::thing($old_parent) SET sub_categories -= type::thing($record)",
+                );
+            }
+            if data.parent_id.is_some() {
+                query = query.query(
+                    "UPDATE type::thing($parent) SET sub_categories += type::thing($record)",
+                );
             }
         }
 
-        let id = Thing::from((
-            Collection::Category.to_string().as_str(),
-            id.to_string().as_str(),
-        ));
+        let mut resp = query
+            .query(RECORD_REVISIONS)
+            .query("COMMIT TRANSACTION")
+            .bind(("record", &record))
+            .bind(("old_parent", current.parent_id.as_ref().map(record_id)))
+            .bind(("parent", &input_category.parent_id))
+            .bind(("content", &input_category))
+            .bind(("version", current.version))
+            .bind(("revised", [&record]))
+            .bind(("kind", RevisionKind::Updated))
+            .bind(("caller", caller))
+            .await
+            .map_err(map_write_error(id))?
+            .check()
+            .map_err(map_write_error(id))?;
+
+        let item: Option<DatabaseEntity> = resp.take(1).map_err(map_db_error)?;
+        let res = match item {
+            Some(e) => {
+                let category = Category::try_from(e)?;
+
+                if let Some((ref redis, _ttl)) = self.redis {
+                    let mut cache_keys = vec![
+                        CacheKey::AllCategories,
+                        CacheKey::Category { id: &category.id },
+                        CacheKey::SubCategories {
+                            parent: category.parent_id.as_ref(),
+                        },
+                    ];
+                    if let Some(ref parent) = category.parent_id {
+                        cache_keys.push(CacheKey::Category { id: parent });
+                    }
+                    if current.parent_id != category.parent_id {
+                        cache_keys.push(CacheKey::SubCategories {
+                            parent: current.parent_id.as_ref(),
+                        });
+                        if let Some(ref parent) = current.parent_id {
+                            cache_keys.push(CacheKey::Category { id: parent });
+                        }
+                    }
+
+                    redis_query::invalidate(&cache_keys, redis).await;
+                }
+
+                Some(category)
+            }
+            None => None,
+        };
+
+        Ok(res)
+    }
+
+    #[instrument(skip(self, id), err(Debug))]
+    async fn patch_category(
+        &self,
+        id: &Uuid,
+        patch: &CategoryPatch,
+        expected_version: Option<u64>,
+        caller: Option<&str>,
+    ) -> Result<Option<Category>, CoreError> {
+        if let Some(ref attributes) = patch.attributes {
+            validate_attributes(attributes)?;
+        }
+        let record = record_id(id);
 
-        let input_category = InputCategory::from(data);
+        // MERGE would create a missing record
+        let current: Option<DatabaseEntity> =
+            self.client.select(&record).await.map_err(map_db_error)?;
+        let Some(current) = live(current.map(Category::try_from).transpose()?) else {
+            return Ok(None);
+        };
+        check_version(&current, expected_version)?;
+        if let Some(ref name) = patch.name {
+            self.check_slugs(&[*id], current.parent_id.as_ref(), &[slugify(name)])
+                .await?;
+        }
 
-        let item: Option<DatabaseEntity> = self
+        let mut resp = self
             .client
-            .update(id)
-            .content(input_category)
+            .query("BEGIN TRANSACTION")
+            .query(CHECK_VERSION)
+            .query("UPDATE type::thing($record) MERGE $patch")
+            .query(RECORD_REVISIONS)
+            .query("COMMIT TRANSACTION")
+            .bind(("record", &record))
+            .bind(("patch", InputPatch::new(patch, current.version + 1, caller)))
+            .bind(("version", current.version))
+            .bind(("revised", [&record]))
+            .bind(("kind", RevisionKind::Updated))
+            .bind(("caller", caller))
             .await
-            .map_err(map_db_error)?;
+            .map_err(map_write_error(id))?
+            .check()
+            .map_err(map_write_error(id))?;
+
+        let item: Option<DatabaseEntity> = resp.take(1).map_err(map_db_error)?;
         let res = match item {
             Some(e) => {
                 let category = Category::try_from(e)?;
 
                 if let Some((ref redis, _ttl)) = self.redis {
-                    let sub_categories = CacheKey::SubCategories {
-                        parent: category.parent_id.as_ref(),
-                    };
-                    let category_cache_key = CacheKey::Category { id: &category.id };
-
-                    let mut redis = redis.get().await.unwrap();
-                    let mut pipeline = redis::Pipeline::new();
-                    pipeline
-                        .del(CacheKey::AllCategories)
-                        .del(sub_categories)
-                        .del(category_cache_key);
-
-                    if let Err(e) = redis.query_async_pipeline::<()>(pipeline).await {
-                        error!("{e}");
-                    }
+                    let cache_keys = [
+                        CacheKey::AllCategories,
+                        CacheKey::Category { id: &category.id },
+                        CacheKey::SubCategories {
+                            parent: category.parent_id.as_ref(),
+                        },
+                    ];
+
+                    redis_query::invalidate(&cache_keys, redis).await;
                 }
 
                 Some(category)
@@ -122,34 +534,291 @@ impl MutateCategories for Client {
     }
 
     #[instrument(skip(self, id), err(Debug))]
-    async fn delete_category(&self, id: &Uuid) -> Result<Option<Category>, CoreError> {
-        let id = Thing::from((
-            Collection::Category.to_string().as_str(),
-            id.to_string().as_ref(),
-        ));
+    async fn delete_category(
+        &self,
+        id: &Uuid,
+        strategy: DeleteStrategy,
+        expected_version: Option<u64>,
+        caller: Option<&str>,
+    ) -> Result<Option<DeletedCategory>, CoreError> {
+        let record = record_id(id);
+
+        let current: Option<DatabaseEntity> =
+            self.client.select(&record).await.map_err(map_db_error)?;
+        let Some(current) = live(current.map(Category::try_from).transpose()?) else {
+            return Ok(None);
+        };
+        check_version(&current, expected_version)?;
+
+        let mut query = self.client.query("BEGIN TRANSACTION").query(CHECK_VERSION);
+        query = match strategy {
+            // the reparented subcategories are no longer linked to it
+            DeleteStrategy::Reparent => query.query(format!(
+                "UPDATE type::thing($record) SET sub_categories = [], {TRASH}"
+            )),
+            _ => query.query(format!("UPDATE type::thing($record) SET {TRASH}")),
+        };
+
+        let mut deleted = Vec::new();
+        let mut first = 0;
+        match strategy {
+            DeleteStrategy::Restrict => {
+                if !current.sub_categories.is_empty() {
+                    return Err(CoreError::HasSubCategories(*id));
+                }
+            }
+            DeleteStrategy::Cascade => {
+                // the subtree stays linked so it can be restored in one go
+                deleted = self.descendant_levels(&current).await?.concat();
+                query = query.query(format!(
+                    "UPDATE category SET {TRASH} WHERE id INSIDE $descendants"
+                ));
+            }
+            DeleteStrategy::Reparent => {
+                let slugs: Vec<String> = self
+                    .client
+                    .query("SELECT VALUE slug FROM category WHERE id INSIDE $children AND deleted_at is none")
+                    .bind((
+                        "children",
+                        current.sub_categories.iter().map(record_id).collect::<Vec<_>>(),
+                    ))
+                    .await
+                    .map_err(map_db_error)?
+                    .take(0)
+                    .map_err(map_db_error)?;
+                let except: Vec<_> = std::iter::once(*id)
+                    .chain(current.sub_categories.iter().copied())
+                    .collect();
+                self.check_slugs(&except, current.parent_id.as_ref(), &slugs)
+                    .await?;
+                first = self.next_position(current.parent_id.as_ref()).await?;
+
+                // the subcategories keep their order, after the new siblings
+                query = query.query(
+                    "UPDATE category SET parent_id = $parent, position += $first, version += 1, updated_at = time::now(), updated_by = $caller WHERE id INSIDE $children RETURN VALUE id",
+                );
+            }
+        }
+        if current.parent_id.is_some() {
+            query = query
+                .query("UPDATE type::thing($parent) SET sub_categories -= type::thing($record)");
+            if strategy == DeleteStrategy::Reparent {
+                query = query.query("UPDATE type::thing($parent) SET sub_categories += $children");
+            }
+        }
+        query = query.query(RECORD_DELETIONS);
+        if strategy == DeleteStrategy::Reparent {
+            query = query.query(RECORD_REVISIONS);
+        }
+
+        let children: Vec<_> = current.sub_categories.iter().map(record_id).collect();
+        let mut resp = query
+            .query("COMMIT TRANSACTION")
+            .bind(("record", &record))
+            .bind(("parent", current.parent_id.as_ref().map(record_id)))
+            .bind(("children", &children))
+            .bind(("first", first))
+            .bind((
+                "descendants",
+                deleted.iter().map(record_id).collect::<Vec<_>>(),
+            ))
+            .bind((
+                "removed",
+                std::iter::once(*id)
+                    .chain(deleted.iter().copied())
+                    .map(|id| record_id(&id))
+                    .collect::<Vec<_>>(),
+            ))
+            .bind(("version", current.version))
+            .bind(("revised", &children))
+            .bind(("kind", RevisionKind::Updated))
+            .bind(("now", Datetime::from(Utc::now())))
+            .bind(("caller", caller))
+            .await
+            .map_err(map_write_error(id))?
+            .check()
+            .map_err(map_write_error(id))?;
+
+        let res: Option<DatabaseEntity> = resp.take(1).map_err(map_db_error)?;
+        let reparented = if strategy == DeleteStrategy::Reparent {
+            let ids: Vec<RecordId> = resp.take(2).map_err(map_db_error)?;
+            ids.into_iter()
+                .map(|id| Uuid::parse_str(&id_to_string(&id.id)).map_err(CoreError::from))
+                .collect::<Result<Vec<_>, _>>()?
+        } else {
+            Vec::new()
+        };
 
-        let res: Option<DatabaseEntity> = self.client.delete(id).await.map_err(map_db_error)?;
         let res = match res {
             Some(e) => {
                 let category = Category::try_from(e)?;
 
                 if let Some((ref redis, _ttl)) = self.redis {
-                    let sub_categories = CacheKey::SubCategories {
-                        parent: category.parent_id.as_ref(),
-                    };
-                    let category_cache_key = CacheKey::Category { id: &category.id };
-
-                    let mut redis = redis.get().await.unwrap();
-                    let mut pipeline = redis::Pipeline::new();
-                    pipeline
-                        .del(CacheKey::AllCategories)
-                        .del(sub_categories)
-                        .del(category_cache_key);
-
-                    if let Err(e) = redis.query_async_pipeline::<()>(pipeline).await {
-                        error!("{e}");
+                    let mut cache_keys = vec![
+                        CacheKey::AllCategories,
+                        CacheKey::Category { id: &category.id },
+                        CacheKey::SubCategories {
+                            parent: category.parent_id.as_ref(),
+                        },
+                        CacheKey::SubCategories {
+                            parent: Some(&category.id),
+                        },
+                    ];
+                    if let Some(ref parent) = category.parent_id {
+                        cache_keys.push(CacheKey::Category { id: parent });
+                    }
+                    for id in deleted.iter() {
+                        cache_keys.push(CacheKey::Category { id });
+                        cache_keys.push(CacheKey::SubCategories { parent: Some(id) });
+                    }
+                    for id in reparented.iter() {
+                        cache_keys.push(CacheKey::Category { id });
+                    }
+
+                    redis_query::invalidate(&cache_keys, redis).await;
+                }
+
+                let removed: Vec<_> = std::iter::once(category.id)
+                    .chain(deleted.iter().copied())
+                    .collect();
+                self.remove_from_search(&removed).await;
+
+                Some(DeletedCategory {
+                    category,
+                    deleted,
+                    reparented,
+                })
+            }
+            None => None,
+        };
+
+        Ok(res)
+    }
+
+    #[instrument(skip(self, id), err(Debug))]
+    async fn move_category(
+        &self,
+        id: &Uuid,
+        parent: Option<&Uuid>,
+        position: Option<usize>,
+        expected_version: Option<u64>,
+        caller: Option<&str>,
+    ) -> Result<Option<Category>, CoreError> {
+        self.check_parent(parent).await?;
+
+        let record = record_id(id);
+
+        let current: Option<DatabaseEntity> =
+            self.client.select(&record).await.map_err(map_db_error)?;
+        let Some(current) = live(current.map(Category::try_from).transpose()?) else {
+            return Ok(None);
+        };
+        check_version(&current, expected_version)?;
+        if current.parent_id.as_ref() != parent {
+            self.check_hierarchy(Some(&current), parent).await?;
+            self.check_slugs(&[*id], parent, std::slice::from_ref(&current.slug))
+                .await?;
+        }
+
+        let siblings = self.siblings(parent, Some(id)).await?;
+        let mut order: Vec<_> = siblings.iter().map(|sibling| sibling.id).collect();
+        let index = position.map_or(order.len(), |position| position.min(order.len()));
+        order.insert(index, *id);
+        // appending leaves the siblings where they are
+        let (new_position, renumbered) = if index == siblings.len() {
+            (
+                siblings.last().map_or(0, |last| last.position + 1),
+                Vec::new(),
+            )
+        } else {
+            let positions: HashMap<_, _> = siblings
+                .iter()
+                .map(|sibling| (sibling.id, sibling.position))
+                .collect();
+            // only the siblings that change position get a new version
+            let renumbered: Vec<_> = order
+                .iter()
+                .enumerate()
+                .filter(|(position, sibling)| {
+                    positions
+                        .get(*sibling)
+                        .is_some_and(|current| *current != *position as u32)
+                })
+                .map(|(position, sibling)| SiblingPosition {
+                    id: record_id(sibling),
+                    position: position as u32,
+                })
+                .collect();
+            (index as u32, renumbered)
+        };
+
+        let mut query = self
+            .client
+            .query("BEGIN TRANSACTION")
+            .query(CHECK_VERSION)
+            .query("UPDATE type::thing($record) SET parent_id = $parent, position = $position, version += 1, updated_at = time::now(), updated_by = $caller")
+            .query(RENUMBER);
+        if current.parent_id.is_some() {
+            query = query.query(
+                "UPDATE type::thing($old_parent) SET sub_categories -= type::thing($record)",
+            );
+        }
+        if parent.is_some() {
+            query = query.query("UPDATE type::thing($parent) SET sub_categories = $order");
+        }
+
+        let mut resp = query
+            .query(RECORD_REVISIONS)
+            .query("COMMIT TRANSACTION")
+            .bind(("record", &record))
+            .bind(("old_parent", current.parent_id.as_ref().map(record_id)))
+            .bind(("parent", parent.map(record_id)))
+            .bind(("position", new_position))
+            .bind(("renumbered", &renumbered))
+            .bind(("order", order.iter().map(record_id).collect::<Vec<_>>()))
+            .bind(("version", current.version))
+            .bind((
+                "revised",
+                std::iter::once(&record)
+                    .chain(renumbered.iter().map(|entry| &entry.id))
+                    .collect::<Vec<_>>(),
+            ))
+            .bind(("kind", RevisionKind::Updated))
+            .bind(("caller", caller))
+            .await
+            .map_err(map_write_error(id))?
+            .check()
+            .map_err(map_write_error(id))?;
+
+        let item: Option<DatabaseEntity> = resp.take(1).map_err(map_db_error)?;
+        let res = match item {
+            Some(e) => {
+                let category = Category::try_from(e)?;
+
+                if let Some((ref redis, _ttl)) = self.redis {
+                    let mut cache_keys = vec![
+                        CacheKey::AllCategories,
+                        CacheKey::Category { id: &category.id },
+                        CacheKey::SubCategories {
+                            parent: current.parent_id.as_ref(),
+                        },
+                        CacheKey::SubCategories {
+                            parent: category.parent_id.as_ref(),
+                        },
+                    ];
+                    for parent in [&current.parent_id, &category.parent_id]
+                        .into_iter()
+                        .flatten()
+                    {
+                        cache_keys.push(CacheKey::Category { id: parent });
                     }
+                    if !renumbered.is_empty() {
+                        cache_keys.extend(order.iter().map(|id| CacheKey::Category { id }));
+                    }
+
+                    redis_query::invalidate(&cache_keys, redis).await;
                 }
+
                 Some(category)
             }
             None => None,
@@ -157,30 +826,607 @@ impl MutateCategories for Client {
 
         Ok(res)
     }
+
+    #[instrument(skip(self), err(Debug))]
+    async fn reorder_sub_categories(
+        &self,
+        parent: Option<&Uuid>,
+        ordered: &[Uuid],
+        caller: Option<&str>,
+    ) -> Result<Option<Vec<Category>>, CoreError> {
+        if let Some(parent) = parent {
+            let item: Option<DatabaseEntity> = self
+                .client
+                .select(record_id(parent))
+                .await
+                .map_err(map_db_error)?;
+            if !item.is_some_and(|item| item.deleted_at.is_none()) {
+                return Ok(None);
+            }
+        }
+        let positions: HashMap<_, _> = self
+            .siblings(parent, None)
+            .await?
+            .into_iter()
+            .map(|sibling| (sibling.id, sibling.position))
+            .collect();
+        check_order(&positions.keys().copied().collect::<Vec<_>>(), ordered)?;
+
+        // only the siblings that change position get a new version
+        let renumbered: Vec<_> = ordered
+            .iter()
+            .enumerate()
+            .filter(|(position, id)| positions.get(*id) != Some(&(*position as u32)))
+            .map(|(position, id)| SiblingPosition {
+                id: record_id(id),
+                position: position as u32,
+            })
+            .collect();
+        let order: Vec<_> = ordered.iter().map(record_id).collect();
+
+        let mut query = self.client.query("BEGIN TRANSACTION").query(RENUMBER);
+        if parent.is_some() {
+            query = query.query("UPDATE type::thing($parent) SET sub_categories = $order");
+        }
+        let mut resp = query
+            .query(RECORD_REVISIONS)
+            .query("SELECT * FROM category WHERE id INSIDE $order")
+            .query("COMMIT TRANSACTION")
+            .bind(("renumbered", &renumbered))
+            .bind(("parent", parent.map(record_id)))
+            .bind(("order", &order))
+            .bind((
+                "revised",
+                renumbered.iter().map(|entry| &entry.id).collect::<Vec<_>>(),
+            ))
+            .bind(("kind", RevisionKind::Updated))
+            .bind(("caller", caller))
+            .await
+            .map_err(map_db_error)?
+            .check()
+            .map_err(map_db_error)?;
+
+        let index = if parent.is_some() { 3 } else { 2 };
+        let items: Vec<DatabaseEntity> = resp.take(index).map_err(map_db_error)?;
+        let mut categories = items
+            .into_iter()
+            .map(Category::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        sort_siblings(&mut categories);
+
+        if let Some((ref redis, _ttl)) = self.redis {
+            let mut cache_keys = vec![CacheKey::AllCategories, CacheKey::SubCategories { parent }];
+            if let Some(parent) = parent {
+                cache_keys.push(CacheKey::Category { id: parent });
+            }
+            cache_keys.extend(ordered.iter().map(|id| CacheKey::Category { id }));
+
+            redis_query::invalidate(&cache_keys, redis).await;
+        }
+
+        Ok(Some(categories))
+    }
+
+    /// Counts live apart so replacing the category cannot lose an adjustment
+    #[instrument(skip(self), err(Debug))]
+    async fn adjust_listing_count(&self, id: &Uuid, delta: i64) -> Result<Option<u64>, CoreError> {
+        let current: Option<DatabaseEntity> = self
+            .client
+            .select(record_id(id))
+            .await
+            .map_err(map_db_error)?;
+        if !current.is_some_and(|current| current.deleted_at.is_none()) {
+            return Ok(None);
+        }
+
+        // updating a missing record creates it, starting the count at zero
+        let count: Option<u64> = self
+            .client
+            .query("UPDATE type::thing($listing) SET count = math::max([(count OR 0) + $delta, 0]) RETURN VALUE count")
+            .bind((
+                "listing",
+                Thing::from((
+                    Collection::CategoryListing.to_string().as_str(),
+                    id.to_string().as_str(),
+                )),
+            ))
+            .bind(("delta", delta))
+            .await
+            .map_err(map_db_error)?
+            .take(0)
+            .map_err(map_db_error)?;
+
+        Ok(count)
+    }
+
+    #[instrument(skip(self, id), err(Debug))]
+    async fn restore_category(
+        &self,
+        id: &Uuid,
+        revision: u64,
+        caller: Option<&str>,
+    ) -> Result<Option<Category>, CoreError> {
+        let history: Vec<_> = self.get_category_history(id).await?.collect();
+        let (Some(target), Some(latest)) = (
+            history.iter().find(|item| item.revision == revision),
+            history.last(),
+        ) else {
+            return Ok(None);
+        };
+        let snapshot = &target.category;
+        self.check_parent(snapshot.parent_id.as_ref()).await?;
+
+        let record = record_id(id);
+
+        let current: Option<DatabaseEntity> =
+            self.client.select(&record).await.map_err(map_db_error)?;
+        let current = current.map(Category::try_from).transpose()?;
+        let linked = live(current.clone());
+        // a category in the trash is still linked when its parent went to the trash with it
+        let old_parent = current.as_ref().and_then(|current| current.parent_id);
+        let relink = linked.is_none() || old_parent != snapshot.parent_id;
+        if relink {
+            self.check_hierarchy(current.as_ref(), snapshot.parent_id.as_ref())
+                .await?;
+        }
+        let slug = slugify(&snapshot.name);
+        self.check_slugs(
+            &[*id],
+            snapshot.parent_id.as_ref(),
+            std::slice::from_ref(&slug),
+        )
+        .await?;
+        let position = match linked {
+            Some(ref linked) if !relink => linked.position,
+            _ => self.next_position(snapshot.parent_id.as_ref()).await?,
+        };
+
+        let category = Category {
+            slug,
+            position,
+            sub_categories: linked
+                .as_ref()
+                .map(|linked| linked.sub_categories.clone())
+                .unwrap_or_default(),
+            version: latest.revision + 1,
+            updated_at: Some(Utc::now()),
+            updated_by: caller.map(ToOwned::to_owned),
+            deleted_at: None,
+            ..snapshot.to_owned()
+        };
+        let input_category = InputCategory::from(&category);
+
+        let mut query = self.client.query("BEGIN TRANSACTION");
+        query = match current {
+            // an existing category has to be left as it was when read
+            Some(_) => query
+                .query(CHECK_VERSION)
+                .query("UPDATE type::thing($record) CONTENT $content"),
+            None => query.query("CREATE type::thing($record) CONTENT $content"),
+        };
+        if relink && old_parent.is_some() {
+            query = query.query(
+                "UPDATE type::thing($old_parent) SET sub_categories -= type::thing($record)",
+            );
+        }
+        if relink && category.parent_id.is_some() {
+            query = query
+                .query("UPDATE type::thing($parent) SET sub_categories += type::thing($record)");
+        }
+
+        let mut resp = query
+            .query(RECORD_REVISIONS)
+            .query("COMMIT TRANSACTION")
+            .bind(("record", &record))
+            .bind(("old_parent", old_parent.as_ref().map(record_id)))
+            .bind(("parent", &input_category.parent_id))
+            .bind(("content", &input_category))
+            .bind(("version", current.as_ref().map(|current| current.version)))
+            .bind(("revised", [&record]))
+            .bind(("kind", RevisionKind::Restored))
+            .bind(("caller", caller))
+            .await
+            .map_err(map_write_error(id))?
+            .check()
+            .map_err(map_write_error(id))?;
+
+        let index = usize::from(current.is_some());
+        let item: Option<DatabaseEntity> = resp.take(index).map_err(map_db_error)?;
+        let res = match item {
+            Some(e) => {
+                let category = Category::try_from(e)?;
+
+                if let Some((ref redis, _ttl)) = self.redis {
+                    let mut cache_keys = vec![
+                        CacheKey::AllCategories,
+                        CacheKey::Category { id: &category.id },
+                        CacheKey::SubCategories {
+                            parent: old_parent.as_ref(),
+                        },
+                        CacheKey::SubCategories {
+                            parent: category.parent_id.as_ref(),
+                        },
+                    ];
+                    for parent in [&old_parent, &category.parent_id].into_iter().flatten() {
+                        cache_keys.push(CacheKey::Category { id: parent });
+                    }
+
+                    redis_query::invalidate(&cache_keys, redis).await;
+                }
+                self.add_to_search(std::slice::from_ref(&category)).await;
+
+                Some(category)
+            }
+            None => None,
+        };
+
+        Ok(res)
+    }
+
+    #[instrument(skip(self, id), err(Debug))]
+    async fn undelete_category(
+        &self,
+        id: &Uuid,
+        caller: Option<&str>,
+    ) -> Result<Option<RestoredCategory>, CoreError> {
+        let record = record_id(id);
+
+        let current: Option<DatabaseEntity> =
+            self.client.select(&record).await.map_err(map_db_error)?;
+        let Some(current) = current
+            .map(Category::try_from)
+            .transpose()?
+            .filter(|current| current.deleted_at.is_some())
+        else {
+            return Ok(None);
+        };
+        self.check_parent(current.parent_id.as_ref()).await?;
+        self.check_hierarchy(Some(&current), current.parent_id.as_ref())
+            .await?;
+        self.check_slugs(
+            &[*id],
+            current.parent_id.as_ref(),
+            std::slice::from_ref(&current.slug),
+        )
+        .await?;
+
+        let restored: Vec<_> = std::iter::once(*id)
+            .chain(self.descendant_levels(&current).await?.concat())
+            .collect();
+        // back after its siblings, as it is linked last
+        let position = self.next_position(current.parent_id.as_ref()).await?;
+
+        let mut query = self
+            .client
+            .query("BEGIN TRANSACTION")
+            .query(CHECK_VERSION)
+            .query("UPDATE type::thing($record) SET position = $position")
+            .query("UPDATE category SET deleted_at = NONE, version += 1, updated_at = time::now(), updated_by = $caller WHERE id INSIDE $revised");
+        if current.parent_id.is_some() {
+            query = query
+                .query("UPDATE type::thing($parent) SET sub_categories += type::thing($record)");
+        }
+
+        let mut resp = query
+            .query(RECORD_REVISIONS)
+            .query("COMMIT TRANSACTION")
+            .bind(("record", &record))
+            .bind(("parent", current.parent_id.as_ref().map(record_id)))
+            .bind(("position", position))
+            .bind(("version", current.version))
+            .bind((
+                "revised",
+                restored.iter().map(record_id).collect::<Vec<_>>(),
+            ))
+            .bind(("kind", RevisionKind::Restored))
+            .bind(("caller", caller))
+            .await
+            .map_err(map_write_error(id))?
+            .check()
+            .map_err(map_write_error(id))?;
+
+        let items: Vec<DatabaseEntity> = resp.take(2).map_err(map_db_error)?;
+        let categories = items
+            .into_iter()
+            .map(Category::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if let Some((ref redis, _ttl)) = self.redis {
+            let mut cache_keys = vec![
+                CacheKey::AllCategories,
+                CacheKey::SubCategories {
+                    parent: current.parent_id.as_ref(),
+                },
+            ];
+            if let Some(ref parent) = current.parent_id {
+                cache_keys.push(CacheKey::Category { id: parent });
+            }
+            for id in restored.iter() {
+                cache_keys.push(CacheKey::Category { id });
+                cache_keys.push(CacheKey::SubCategories { parent: Some(id) });
+            }
+
+            redis_query::invalidate(&cache_keys, redis).await;
+        }
+        self.add_to_search(&categories).await;
+
+        Ok(categories
+            .into_iter()
+            .find(|category| category.id == *id)
+            .map(|category| RestoredCategory {
+                category,
+                restored: restored[1..].to_vec(),
+            }))
+    }
+
+    #[instrument(skip(self, id), err(Debug))]
+    async fn purge_category(&self, id: &Uuid) -> Result<Option<DeletedCategory>, CoreError> {
+        let record = record_id(id);
+
+        let current: Option<DatabaseEntity> =
+            self.client.select(&record).await.map_err(map_db_error)?;
+        let Some(current) = current.map(Category::try_from).transpose()? else {
+            return Ok(None);
+        };
+        if current.deleted_at.is_none() {
+            return Err(CoreError::NotDeleted(*id));
+        }
+
+        let deleted = self.descendant_levels(&current).await?.concat();
+
+        let mut resp = self
+            .client
+            .query("BEGIN TRANSACTION")
+            .query("DELETE type::thing($record) RETURN BEFORE")
+            .query("DELETE category WHERE id INSIDE $descendants")
+            // still linked when its parent went to the trash along with it. Filtering keeps a
+            // purged parent from being recreated
+            .query("UPDATE category SET sub_categories -= type::thing($record) WHERE id = $parent")
+            .query("DELETE type::table($table) WHERE id INSIDE $listings")
+            .query("COMMIT TRANSACTION")
+            .bind(("record", &record))
+            .bind((
+                "descendants",
+                deleted.iter().map(record_id).collect::<Vec<_>>(),
+            ))
+            .bind(("table", Collection::CategoryListing))
+            .bind((
+                "listings",
+                std::iter::once(id)
+                    .chain(deleted.iter())
+                    .map(|id| {
+                        Thing::from((Collection::CategoryListing.to_string(), id.to_string()))
+                    })
+                    .collect::<Vec<_>>(),
+            ))
+            .bind(("parent", current.parent_id.as_ref().map(record_id)))
+            .await
+            .map_err(map_db_error)?
+            .check()
+            .map_err(map_db_error)?;
+
+        let item: Option<DatabaseEntity> = resp.take(0).map_err(map_db_error)?;
+        let res = match item {
+            Some(e) => {
+                let category = Category::try_from(e)?;
+
+                if let Some((ref redis, _ttl)) = self.redis {
+                    let mut cache_keys = vec![CacheKey::Category { id: &category.id }];
+                    if let Some(ref parent) = category.parent_id {
+                        cache_keys.push(CacheKey::Category { id: parent });
+                    }
+                    for id in deleted.iter() {
+                        cache_keys.push(CacheKey::Category { id });
+                    }
+
+                    redis_query::invalidate(&cache_keys, redis).await;
+                }
+
+                Some(DeletedCategory {
+                    category,
+                    deleted,
+                    reparented: Vec::new(),
+                })
+            }
+            None => None,
+        };
+
+        Ok(res)
+    }
+
+    #[instrument(skip(self, categories), err(Debug))]
+    async fn create_categories(
+        &self,
+        categories: &[Category],
+        caller: Option<&str>,
+    ) -> Result<Vec<Category>, CoreError> {
+        let order = hierarchy::creation_order(categories)?;
+
+        let ids: Vec<_> = categories.iter().map(|category| category.id).collect();
+        let parents: Vec<_> = categories
+            .iter()
+            .map(|category| category.parent_id)
+            .collect();
+        let snapshot = self.snapshot(&ids, &parents).await?;
+        let mut staged = snapshot.clone();
+        let mut changeset = Changeset::new(&mut staged, self.max_depth, caller);
+        for (_, category) in order.iter() {
+            changeset.create(category, category.id)?;
+        }
+        let changes = changeset.into_changes();
+
+        let mut stored = self
+            .write_batch(&snapshot, &staged, changes, caller)
+            .await?;
+
+        let mut ids = vec![Uuid::nil(); order.len()];
+        for (index, category) in order {
+            ids[index] = category.id;
+        }
+        ids.iter()
+            .map(|id| stored.remove(id).ok_or(CoreError::Unreachable))
+            .collect()
+    }
+
+    #[instrument(skip(self, updates), err(Debug))]
+    async fn update_categories(
+        &self,
+        updates: &[CategoryUpdate],
+        caller: Option<&str>,
+    ) -> Result<Vec<Option<Category>>, CoreError> {
+        let ids: Vec<_> = updates.iter().map(|update| update.id).collect();
+        let parents: Vec<_> = updates
+            .iter()
+            .map(|update| update.category.parent_id)
+            .collect();
+        let snapshot = self.snapshot(&ids, &parents).await?;
+        let mut staged = snapshot.clone();
+        let mut changeset = Changeset::new(&mut staged, self.max_depth, caller);
+        let updated = updates
+            .iter()
+            .map(|update| changeset.update(&update.id, &update.category, update.expected_version))
+            .collect::<Result<Vec<_>, _>>()?;
+        let changes = changeset.into_changes();
+
+        let stored = self
+            .write_batch(&snapshot, &staged, changes, caller)
+            .await?;
+
+        updated
+            .into_iter()
+            .map(|category| {
+                category
+                    .map(|category| {
+                        stored
+                            .get(&category.id)
+                            .cloned()
+                            .ok_or(CoreError::Unreachable)
+                    })
+                    .transpose()
+            })
+            .collect()
+    }
+
+    #[instrument(skip(self, deletions), err(Debug))]
+    async fn delete_categories(
+        &self,
+        deletions: &[CategoryDeletion],
+        strategy: DeleteStrategy,
+        caller: Option<&str>,
+    ) -> Result<Vec<Option<DeletedCategory>>, CoreError> {
+        let ids: Vec<_> = deletions.iter().map(|deletion| deletion.id).collect();
+        let snapshot = self.snapshot(&ids, &[]).await?;
+        let mut staged = snapshot.clone();
+        let mut changeset = Changeset::new(&mut staged, self.max_depth, caller);
+        let deleted = deletions
+            .iter()
+            .map(|deletion| changeset.delete(&deletion.id, strategy, deletion.expected_version))
+            .collect::<Result<Vec<_>, _>>()?;
+        let changes = changeset.into_changes();
+
+        let stored = self
+            .write_batch(&snapshot, &staged, changes, caller)
+            .await?;
+
+        deleted
+            .into_iter()
+            .map(|deleted| {
+                deleted
+                    .map(|deleted| {
+                        let category = stored
+                            .get(&deleted.category.id)
+                            .cloned()
+                            .ok_or(CoreError::Unreachable)?;
+
+                        Ok(DeletedCategory {
+                            category,
+                            ..deleted
+                        })
+                    })
+                    .transpose()
+            })
+            .collect()
+    }
 }
 
 #[derive(serde::Serialize)]
 struct InputCategory<'a> {
     name: &'a str,
+    slug: String,
+    description: Option<&'a str>,
+    translations: &'a [Translation],
     sub_categories: Vec<RecordId>,
     image_url: Option<&'a str>,
+    attributes: &'a [AttributeDefinition],
     parent_id: Option<RecordId>,
+    position: u32,
+    version: u64,
+    created_at: Option<Datetime>,
+    updated_at: Option<Datetime>,
+    created_by: Option<&'a str>,
+    updated_by: Option<&'a str>,
+    deleted_at: Option<Datetime>,
 }
 
 impl<'a> From<&'a Category> for InputCategory<'a> {
     fn from(value: &'a Category) -> Self {
         Self {
             name: &value.name,
-            sub_categories: value
-                .sub_categories
-                .iter()
-                .map(|f| RecordId::from(("category", f.to_string().as_str())))
-                .collect(),
+            slug: slugify(&value.name),
+            description: value.description.as_deref(),
+            translations: &value.translations,
+            sub_categories: value.sub_categories.iter().map(record_id).collect(),
             image_url: value.image_url.as_deref(),
-            parent_id: value
-                .parent_id
-                .as_ref()
-                .map(|f| RecordId::from(("category", f.to_string().as_str()))),
+            attributes: &value.attributes,
+            parent_id: value.parent_id.as_ref().map(record_id),
+            position: value.position,
+            version: value.version,
+            created_at: value.created_at.map(Datetime::from),
+            updated_at: value.updated_at.map(Datetime::from),
+            created_by: value.created_by.as_deref(),
+            updated_by: value.updated_by.as_deref(),
+            deleted_at: value.deleted_at.map(Datetime::from),
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+struct SiblingPosition {
+    id: Thing,
+    position: u32,
+}
+
+#[derive(serde::Serialize)]
+struct InputPatch<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    slug: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    translations: Option<&'a [Translation]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    image_url: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    attributes: Option<&'a [AttributeDefinition]>,
+    version: u64,
+    updated_at: Datetime,
+    updated_by: Option<&'a str>,
+}
+
+impl<'a> InputPatch<'a> {
+    fn new(patch: &'a CategoryPatch, version: u64, caller: Option<&'a str>) -> Self {
+        Self {
+            name: patch.name.as_deref(),
+            slug: patch.name.as_deref().map(slugify),
+            description: patch.description.as_deref(),
+            translations: patch.translations.as_deref(),
+            image_url: patch.image_url.as_deref(),
+            attributes: patch.attributes.as_deref(),
+            version,
+            updated_at: Datetime::from(Utc::now()),
+            updated_by: caller,
         }
     }
 }
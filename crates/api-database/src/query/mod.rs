@@ -1,15 +1,22 @@
 use api_core::{
     api::{CoreError, QueryCategories},
-    reexports::uuid::Uuid,
-    Category,
+    reexports::{
+        chrono::{DateTime, Utc},
+        uuid::Uuid,
+    },
+    Category, CategoryFilter, CategoryOrder, CategoryOrderField, CategoryRevision, CategoryStats,
+    CategoryTree, KeysetPage, OrderDirection, RevisionKind, SortKey, SortValue,
 };
+use std::collections::{BTreeMap, HashMap, HashSet};
+
 use meilisearch_sdk::{SearchQuery, SearchResults};
-use surrealdb::sql::Thing;
+use surrealdb::sql::{Datetime, Thing, Value};
 use tracing::{debug, error, instrument};
 
 use crate::{
     collections::Collection,
-    entity::DatabaseEntity,
+    entity::{DatabaseEntity, DatabaseTreeEntity, ListingCountEntity, RevisionEntity},
+    hierarchy::{category_stats, listing_order, sort_siblings, sort_tree, tree_order},
     map_db_error,
     redis::{cache_keys::CacheKey, redis_query},
     Client,
@@ -31,32 +38,383 @@ async fn db_get_sub_categories(db: &Client, id: Option<&Uuid>) -> Result<Vec<Cat
             let res: Option<Vec<DatabaseEntity>> =
                 res.take((0, "sub_categories")).map_err(map_db_error)?;
 
-            let categories = res
+            let mut categories = res
                 .ok_or(CoreError::Database("Database returned no items".into()))
                 .and_then(|vals: Vec<DatabaseEntity>| {
                     vals.into_iter()
+                        .filter(|val| val.deleted_at.is_none())
                         .map(Category::try_from)
                         .collect::<Result<Vec<Category>, CoreError>>()
                 })?;
+            sort_siblings(&mut categories);
             Ok(categories)
         }
         None => {
             let mut resp = db
                 .client
-                .query("SELECT * FROM type::table($table) WHERE parent_id is none or null")
+                .query(
+                    "SELECT * FROM type::table($table) WHERE (parent_id is none or null) AND deleted_at is none",
+                )
                 .bind(("table", Collection::Category))
                 .await
                 .map_err(map_db_error)?;
             let categories: Vec<DatabaseEntity> = resp.take(0).map_err(map_db_error)?;
-            let categories = categories
+            let mut categories = categories
                 .into_iter()
                 .map(Category::try_from)
                 .collect::<Result<Vec<Category>, CoreError>>()?;
+            sort_siblings(&mut categories);
             Ok(categories)
         }
     }
 }
 
+/// Builds the projection selecting `sub_categories` as `children`, `depth` levels deep
+fn tree_projection(depth: usize) -> String {
+    match depth.checked_sub(1) {
+        Some(depth) => format!(
+            "*, (SELECT {} FROM $parent.sub_categories) AS children",
+            tree_projection(depth)
+        ),
+        None => String::from("*"),
+    }
+}
+
+/// Ancestors read per round trip when no maximum depth is set
+const ANCESTOR_LEVELS: usize = 16;
+
+/// Builds the projection following `parent_id` up to `depth` levels, closest ancestor first
+fn ancestors_projection(depth: usize) -> String {
+    (1..=depth)
+        .map(|level| format!("{}.*", vec!["parent_id"; level].join(".")))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+async fn db_get_category_tree(
+    db: &Client,
+    id: Option<&Uuid>,
+    max_depth: usize,
+) -> Result<Vec<CategoryTree>, CoreError> {
+    let projection = tree_projection(max_depth);
+
+    let mut resp = match id {
+        Some(id) => db
+            .client
+            .query(format!(
+                "SELECT {projection} FROM type::thing($record) WHERE deleted_at is none"
+            ))
+            .bind((
+                "record",
+                Thing::from((Collection::Category.to_string(), id.to_string())),
+            ))
+            .await
+            .map_err(map_db_error)?,
+        None => db
+            .client
+            .query(format!(
+                "SELECT {projection} FROM type::table($table) WHERE (parent_id is none or null) AND deleted_at is none"
+            ))
+            .bind(("table", Collection::Category))
+            .await
+            .map_err(map_db_error)?,
+    };
+
+    let tree: Vec<DatabaseTreeEntity> = resp.take(0).map_err(map_db_error)?;
+    let mut tree = tree
+        .into_iter()
+        .map(CategoryTree::try_from)
+        .collect::<Result<Vec<CategoryTree>, CoreError>>()?;
+    sort_tree(&mut tree);
+
+    Ok(tree)
+}
+
+/// Every category in the trash when `deleted` is set, every other category otherwise
+pub(crate) async fn db_select_categories(
+    db: &Client,
+    deleted: bool,
+) -> Result<Vec<Category>, CoreError> {
+    let query = if deleted {
+        "SELECT * FROM type::table($table) WHERE deleted_at is not none"
+    } else {
+        "SELECT * FROM type::table($table) WHERE deleted_at is none"
+    };
+
+    let categories: Vec<DatabaseEntity> = db
+        .client
+        .query(query)
+        .bind(("table", Collection::Category))
+        .await
+        .map_err(map_db_error)?
+        .take(0)
+        .map_err(map_db_error)?;
+
+    let categories = categories
+        .into_iter()
+        .map(Category::try_from)
+        .collect::<Result<Vec<Category>, CoreError>>()?;
+
+    // the trash is listed as stored
+    Ok(if deleted {
+        categories
+    } else {
+        tree_order(categories)
+    })
+}
+
+/// `parent_id` followed `levels` times, as in `parent_id.parent_id` for the grandparent
+fn parent_path(levels: u8) -> String {
+    format!("parent_id{}", ".parent_id".repeat(usize::from(levels)))
+}
+
+/// The conditions a category has to meet to pass `filter`, binding `$name` and `$ids`
+fn filter_conditions(filter: &CategoryFilter) -> Vec<String> {
+    let mut conditions = Vec::new();
+    if !filter.include_deleted {
+        conditions.push("deleted_at is none".to_owned());
+    }
+    if filter.name_contains.is_some() {
+        conditions.push("string::contains(string::lowercase(name), $name)".to_owned());
+    }
+    match filter.has_parent {
+        Some(true) => conditions.push("(parent_id is not none and parent_id is not null)".into()),
+        Some(false) => conditions.push("(parent_id is none or null)".into()),
+        None => {}
+    }
+    match filter.is_leaf {
+        Some(true) => conditions.push("array::len(sub_categories) = 0".into()),
+        Some(false) => conditions.push("array::len(sub_categories) > 0".into()),
+        None => {}
+    }
+    // a category at `depth` has that many parents, and no parent above them
+    if let Some(depth) = filter.depth {
+        let top = parent_path(depth);
+        conditions.push(format!("({top} is none or {top} is null)"));
+        if let Some(levels) = depth.checked_sub(1) {
+            let parent = parent_path(levels);
+            conditions.push(format!("({parent} is not none and {parent} is not null)"));
+        }
+    }
+    if filter.ids.is_some() {
+        conditions.push("id INSIDE $ids".to_owned());
+    }
+
+    conditions
+}
+
+fn where_clause(conditions: &[String]) -> String {
+    if conditions.is_empty() {
+        String::new()
+    } else {
+        format!(" WHERE {}", conditions.join(" AND "))
+    }
+}
+
+fn order_field(field: CategoryOrderField) -> &'static str {
+    match field {
+        CategoryOrderField::Name => "name",
+        CategoryOrderField::CreatedAt => "created_at",
+        CategoryOrderField::UpdatedAt => "updated_at",
+        CategoryOrderField::Position => "position",
+    }
+}
+
+/// Sorts by the field of `order` then by ID, the other way round when `reversed` is set
+fn order_clause(order: &CategoryOrder, reversed: bool) -> String {
+    let ascending = (order.direction == OrderDirection::Asc) != reversed;
+    let direction = if ascending { "ASC" } else { "DESC" };
+
+    format!(
+        " ORDER BY {} {direction}, id {direction}",
+        order_field(order.field)
+    )
+}
+
+fn sort_value(value: &SortValue) -> Value {
+    match value {
+        SortValue::Name(name) => Value::from(name.as_str()),
+        SortValue::CreatedAt(at) | SortValue::UpdatedAt(at) => {
+            at.map_or(Value::None, |at| Value::from(Datetime::from(at)))
+        }
+        SortValue::Position(position) => Value::from(i64::from(*position)),
+    }
+}
+
+fn record_ids(ids: &[Uuid]) -> Vec<Thing> {
+    ids.iter()
+        .map(|id| Thing::from((Collection::Category.to_string(), id.to_string())))
+        .collect()
+}
+
+pub(crate) async fn db_select_by_ids(
+    db: &Client,
+    ids: &[Uuid],
+    include_deleted: bool,
+) -> Result<Vec<Category>, CoreError> {
+    let query = if include_deleted {
+        "SELECT * FROM type::table($table) WHERE id INSIDE $ids"
+    } else {
+        "SELECT * FROM type::table($table) WHERE id INSIDE $ids AND deleted_at is none"
+    };
+
+    let items: Vec<DatabaseEntity> = db
+        .client
+        .query(query)
+        .bind(("table", Collection::Category))
+        .bind(("ids", record_ids(ids)))
+        .await
+        .map_err(map_db_error)?
+        .take(0)
+        .map_err(map_db_error)?;
+
+    items.into_iter().map(Category::try_from).collect()
+}
+
+async fn db_find_categories(
+    db: &Client,
+    filter: &CategoryFilter,
+    order: Option<&CategoryOrder>,
+) -> Result<Vec<Category>, CoreError> {
+    let mut query = format!(
+        "SELECT * FROM type::table($table){}",
+        where_clause(&filter_conditions(filter))
+    );
+    if let Some(order) = order {
+        query.push_str(&order_clause(order, false));
+    }
+
+    let categories: Vec<DatabaseEntity> = db
+        .client
+        .query(query)
+        .bind(("table", Collection::Category))
+        .bind((
+            "name",
+            filter
+                .name_contains
+                .as_ref()
+                .map(|name| name.to_lowercase()),
+        ))
+        .bind(("ids", filter.ids.as_deref().map(record_ids)))
+        .await
+        .map_err(map_db_error)?
+        .take(0)
+        .map_err(map_db_error)?;
+
+    let categories = categories
+        .into_iter()
+        .map(Category::try_from)
+        .collect::<Result<Vec<Category>, CoreError>>()?;
+
+    // the database sorts when there is an order
+    Ok(match order {
+        Some(_) => categories,
+        None => listing_order(categories),
+    })
+}
+
+/// Seeks to the bounds of `page` through `(field, id)` comparisons
+async fn db_find_categories_page(
+    db: &Client,
+    filter: &CategoryFilter,
+    order: &CategoryOrder,
+    page: &KeysetPage,
+) -> Result<Vec<Category>, CoreError> {
+    let field = order_field(order.field);
+    let mut conditions = filter_conditions(filter);
+    for (bound, key, ahead) in [
+        ("after", &page.after, true),
+        ("before", &page.before, false),
+    ] {
+        if key.is_some() {
+            let operator = if (order.direction == OrderDirection::Asc) == ahead {
+                ">"
+            } else {
+                "<"
+            };
+            conditions.push(format!(
+                "({field} {operator} ${bound}_value OR ({field} = ${bound}_value AND id {operator} ${bound}_id))"
+            ));
+        }
+    }
+    // the end of the listing is read backwards, then put back in order
+    let query = format!(
+        "SELECT * FROM type::table($table){}{} LIMIT $limit",
+        where_clause(&conditions),
+        order_clause(order, page.from_end)
+    );
+
+    let value = |key: &Option<SortKey>| {
+        key.as_ref()
+            .map_or(Value::None, |key| sort_value(&key.value))
+    };
+    let id = |key: &Option<SortKey>| {
+        key.as_ref()
+            .map(|key| Thing::from((Collection::Category.to_string(), key.id.to_string())))
+    };
+
+    let categories: Vec<DatabaseEntity> = db
+        .client
+        .query(query)
+        .bind(("table", Collection::Category))
+        .bind((
+            "name",
+            filter
+                .name_contains
+                .as_ref()
+                .map(|name| name.to_lowercase()),
+        ))
+        .bind(("ids", filter.ids.as_deref().map(record_ids)))
+        .bind(("after_value", value(&page.after)))
+        .bind(("after_id", id(&page.after)))
+        .bind(("before_value", value(&page.before)))
+        .bind(("before_id", id(&page.before)))
+        .bind(("limit", page.limit))
+        .await
+        .map_err(map_db_error)?
+        .take(0)
+        .map_err(map_db_error)?;
+
+    let mut categories = categories
+        .into_iter()
+        .map(Category::try_from)
+        .collect::<Result<Vec<Category>, CoreError>>()?;
+    if page.from_end {
+        categories.reverse();
+    }
+
+    Ok(categories)
+}
+
+async fn db_count_categories(db: &Client, filter: &CategoryFilter) -> Result<usize, CoreError> {
+    #[derive(serde::Deserialize)]
+    struct Count {
+        count: usize,
+    }
+
+    let count: Option<Count> = db
+        .client
+        .query(format!(
+            "SELECT count() FROM type::table($table){} GROUP ALL",
+            where_clause(&filter_conditions(filter))
+        ))
+        .bind(("table", Collection::Category))
+        .bind((
+            "name",
+            filter
+                .name_contains
+                .as_ref()
+                .map(|name| name.to_lowercase()),
+        ))
+        .bind(("ids", filter.ids.as_deref().map(record_ids)))
+        .await
+        .map_err(map_db_error)?
+        .take(0)
+        .map_err(map_db_error)?;
+
+    Ok(count.map_or(0, |count| count.count))
+}
+
 async fn db_get_categories(
     db: &Client,
     wait_for_completion: bool,
@@ -68,16 +426,7 @@ async fn db_get_categories(
         if let Some(categories) = categories {
             categories
         } else {
-            let categories: Vec<DatabaseEntity> = db
-                .client
-                .select(Collection::Category)
-                .await
-                .map_err(map_db_error)?;
-
-            let categories = categories
-                .into_iter()
-                .map(Category::try_from)
-                .collect::<Result<Vec<Category>, CoreError>>()?;
+            let categories = db_select_categories(db, false).await?;
 
             if let Err(e) = redis_query::update(cache_key, redis, &categories, None).await {
                 error!(key = %cache_key, "[redis update]: {e}");
@@ -86,15 +435,7 @@ async fn db_get_categories(
             categories
         }
     } else {
-        let categories: Vec<DatabaseEntity> = db
-            .client
-            .select(Collection::Category)
-            .await
-            .map_err(map_db_error)?;
-        categories
-            .into_iter()
-            .map(Category::try_from)
-            .collect::<Result<Vec<Category>, CoreError>>()?
+        db_select_categories(db, false).await?
     };
 
     if let Some(ref client) = db.search_client {
@@ -117,7 +458,9 @@ async fn db_get_categories(
 
 impl QueryCategories for Client {
     #[instrument(skip(self), err(Debug))]
-    async fn get_categories(&self) -> Result<impl ExactSizeIterator<Item = Category>, CoreError> {
+    async fn get_categories(
+        &self,
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
         db_get_categories(self, false).await
     }
 
@@ -125,7 +468,7 @@ impl QueryCategories for Client {
     async fn get_sub_categories(
         &self,
         id: Option<&Uuid>,
-    ) -> Result<impl ExactSizeIterator<Item = Category>, CoreError> {
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
         if let Some((ref redis, _ttl)) = self.redis {
             let cache_key = CacheKey::SubCategories { parent: id };
 
@@ -147,6 +490,66 @@ impl QueryCategories for Client {
         }
     }
 
+    #[instrument(skip(self), err(Debug))]
+    async fn find_categories(
+        &self,
+        filter: &CategoryFilter,
+        order: Option<&CategoryOrder>,
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
+        // any write can add to or remove from a listing, so they are kept per generation rather
+        // than picked out by key
+        let generation = match self.redis {
+            Some((ref redis, ttl)) => redis_query::generation(redis)
+                .await
+                .map(|generation| (redis, ttl, generation)),
+            None => None,
+        };
+
+        if let Some((redis, ttl, generation)) = generation {
+            let cache_key = CacheKey::FoundCategories {
+                filter,
+                order,
+                generation,
+            };
+
+            let categories = redis_query::query::<Vec<Category>>(cache_key, redis).await;
+            if let Some(categories) = categories {
+                Ok(categories.into_iter())
+            } else {
+                let categories = db_find_categories(self, filter, order).await?;
+
+                // every write moves to a new generation, the ttl bounds how long an old one stays
+                if let Err(e) = redis_query::update(cache_key, redis, &categories, Some(ttl)).await
+                {
+                    error!(key = %cache_key, "[redis update]: {e}");
+                }
+                Ok(categories.into_iter())
+            }
+        } else {
+            let categories = db_find_categories(self, filter, order).await?;
+
+            Ok(categories.into_iter())
+        }
+    }
+
+    /// Not cached, as a cached page would go out of step with its neighbours
+    #[instrument(skip(self), err(Debug))]
+    async fn find_categories_page(
+        &self,
+        filter: &CategoryFilter,
+        order: &CategoryOrder,
+        page: &KeysetPage,
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
+        let categories = db_find_categories_page(self, filter, order, page).await?;
+
+        Ok(categories.into_iter())
+    }
+
+    #[instrument(skip(self), err(Debug))]
+    async fn count_categories(&self, filter: &CategoryFilter) -> Result<usize, CoreError> {
+        db_count_categories(self, filter).await
+    }
+
     #[instrument(skip(self), err(Debug))]
     async fn get_category_by_id(&self, id: &Uuid) -> Result<Option<Category>, CoreError> {
         let create_id = |id: &Uuid| -> Thing {
@@ -168,6 +571,8 @@ impl QueryCategories for Client {
 
                 let category: Option<DatabaseEntity> =
                     self.client.select(id).await.map_err(map_db_error)?;
+                // deleted categories are only reachable through the trash
+                let category = category.filter(|f| f.deleted_at.is_none());
                 let category = category.and_then(|f| match Category::try_from(f) {
                     Ok(cat) => Some(cat),
                     Err(e) => {
@@ -176,11 +581,14 @@ impl QueryCategories for Client {
                     }
                 });
 
-                if let Err(e) =
-                    // set ttl to 5 mins
-                    redis_query::update(cache_key, redis, category.as_ref(), None).await
-                {
-                    error!(key = %cache_key, "[redis update]: {e}");
+                // misses are left out, the ID may be taken by a category created later
+                if let Some(ref category) = category {
+                    if let Err(e) =
+                        // set ttl to 5 mins
+                        redis_query::update(cache_key, redis, Some(category), None).await
+                    {
+                        error!(key = %cache_key, "[redis update]: {e}");
+                    }
                 }
                 Ok(category)
             }
@@ -189,6 +597,7 @@ impl QueryCategories for Client {
 
             let category: Option<DatabaseEntity> =
                 self.client.select(id).await.map_err(map_db_error)?;
+            let category = category.filter(|f| f.deleted_at.is_none());
             let category = category.and_then(|f| match Category::try_from(f) {
                 Ok(cat) => Some(cat),
                 Err(e) => {
@@ -201,10 +610,337 @@ impl QueryCategories for Client {
         }
     }
 
+    /// Reads the cached categories, then the rest with a single query
+    #[instrument(skip(self), err(Debug))]
+    async fn get_categories_by_ids(
+        &self,
+        ids: &[Uuid],
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
+        let cache_keys: Vec<_> = ids.iter().map(|id| CacheKey::Category { id }).collect();
+        let cached: Vec<Option<Option<Category>>> = match self.redis {
+            Some((ref redis, _)) => redis_query::query_many(&cache_keys, redis).await,
+            None => ids.iter().map(|_| None).collect(),
+        };
+
+        let missing: Vec<_> = ids
+            .iter()
+            .zip(cached.iter())
+            .filter(|(_, cached)| cached.is_none())
+            .map(|(id, _)| *id)
+            .collect();
+        let mut fetched = HashMap::new();
+        if !missing.is_empty() {
+            let items: Vec<DatabaseEntity> = self
+                .client
+                .query(
+                    "SELECT * FROM type::table($table) WHERE id INSIDE $ids AND deleted_at is none",
+                )
+                .bind(("table", Collection::Category))
+                .bind(("ids", record_ids(&missing)))
+                .await
+                .map_err(map_db_error)?
+                .take(0)
+                .map_err(map_db_error)?;
+            fetched = items
+                .into_iter()
+                .map(|item| Category::try_from(item).map(|category| (category.id, category)))
+                .collect::<Result<HashMap<Uuid, Category>, CoreError>>()?;
+
+            if let Some((ref redis, _)) = self.redis {
+                // misses are left out, an ID may be taken by a category created later
+                let entries: Vec<_> = fetched
+                    .iter()
+                    .map(|(id, category)| (CacheKey::Category { id }, Some(category)))
+                    .collect();
+                if !entries.is_empty() {
+                    if let Err(e) = redis_query::update_many(&entries, redis, None).await {
+                        error!("[redis update]: {e}");
+                    }
+                }
+            }
+        }
+
+        let categories: Vec<_> = ids
+            .iter()
+            .zip(cached)
+            .filter_map(|(id, cached)| match cached {
+                Some(category) => category,
+                None => fetched.get(id).cloned(),
+            })
+            .collect();
+
+        Ok(categories.into_iter())
+    }
+
+    /// Resolves one slug of the path at a time, starting from the top level categories
+    #[instrument(skip(self), err(Debug))]
+    async fn get_category_by_slug(&self, path: &str) -> Result<Option<Category>, CoreError> {
+        let mut category: Option<Category> = None;
+
+        for slug in path.split('/').filter(|slug| !slug.is_empty()) {
+            let query = match category {
+                Some(_) => {
+                    "SELECT * FROM type::table($table) WHERE parent_id = $parent AND slug = $slug AND deleted_at is none LIMIT 1"
+                }
+                None => {
+                    "SELECT * FROM type::table($table) WHERE (parent_id is none or null) AND slug = $slug AND deleted_at is none LIMIT 1"
+                }
+            };
+            let parent = category.as_ref().map(|category| {
+                Thing::from((
+                    Collection::Category.to_string().as_str(),
+                    category.id.to_string().as_str(),
+                ))
+            });
+
+            let items: Vec<DatabaseEntity> = self
+                .client
+                .query(query)
+                .bind(("table", Collection::Category))
+                .bind(("parent", parent))
+                .bind(("slug", slug))
+                .await
+                .map_err(map_db_error)?
+                .take(0)
+                .map_err(map_db_error)?;
+
+            match items.into_iter().next() {
+                Some(item) => category = Some(Category::try_from(item)?),
+                None => return Ok(None),
+            }
+        }
+
+        Ok(category)
+    }
+
+    #[instrument(skip(self), err(Debug))]
+    async fn get_deleted_categories(
+        &self,
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
+        let categories = db_select_categories(self, true).await?;
+
+        Ok(categories.into_iter())
+    }
+
+    /// Reads the chain in one round trip unless it is deeper than [`ANCESTOR_LEVELS`]
+    #[instrument(skip(self), err(Debug))]
+    async fn get_ancestors(
+        &self,
+        id: &Uuid,
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
+        let query = format!(
+            "SELECT VALUE [{}] FROM type::thing($record) WHERE deleted_at is none",
+            ancestors_projection(self.max_depth.unwrap_or(ANCESTOR_LEVELS).max(1))
+        );
+        let mut ancestors = Vec::new();
+        let mut visited = HashSet::from([*id]);
+        let mut from = *id;
+
+        'chain: loop {
+            let levels: Option<Vec<Option<DatabaseEntity>>> = self
+                .client
+                .query(&query)
+                .bind((
+                    "record",
+                    Thing::from((Collection::Category.to_string(), from.to_string())),
+                ))
+                .await
+                .map_err(map_db_error)?
+                .take(0)
+                .map_err(map_db_error)?;
+            let Some(levels) = levels else {
+                break;
+            };
+
+            // stops at the top of the chain, or goes on from the topmost ancestor read
+            for level in levels {
+                let Some(parent) = level.filter(|level| level.deleted_at.is_none()) else {
+                    break 'chain;
+                };
+                let parent = Category::try_from(parent)?;
+                if !visited.insert(parent.id) {
+                    error!(id = %parent.id, "cycle detected in category ancestors");
+                    break 'chain;
+                }
+                from = parent.id;
+                ancestors.push(parent);
+            }
+        }
+        ancestors.reverse();
+
+        Ok(ancestors.into_iter())
+    }
+
+    #[instrument(skip(self), err(Debug))]
+    async fn get_category_tree(
+        &self,
+        id: Option<&Uuid>,
+        max_depth: usize,
+    ) -> Result<impl ExactSizeIterator<Item = CategoryTree> + Send, CoreError> {
+        // trees nest whole subtrees under their root, so they are kept per generation rather
+        // than picked out by key
+        let generation = match self.redis {
+            Some((ref redis, ttl)) => redis_query::generation(redis)
+                .await
+                .map(|generation| (redis, ttl, generation)),
+            None => None,
+        };
+
+        if let Some((redis, ttl, generation)) = generation {
+            let cache_key = CacheKey::CategoryTree {
+                root: id,
+                max_depth,
+                generation,
+            };
+
+            let tree = redis_query::query::<Vec<CategoryTree>>(cache_key, redis).await;
+            if let Some(tree) = tree {
+                Ok(tree.into_iter())
+            } else {
+                let tree = db_get_category_tree(self, id, max_depth).await?;
+
+                // every write moves to a new generation, the ttl bounds how long an old one stays
+                if let Err(e) = redis_query::update(cache_key, redis, &tree, Some(ttl)).await {
+                    error!(key = %cache_key, "[redis update]: {e}");
+                }
+                Ok(tree.into_iter())
+            }
+        } else {
+            let tree = db_get_category_tree(self, id, max_depth).await?;
+
+            Ok(tree.into_iter())
+        }
+    }
+
+    /// Not cached, as listing counts move with every listing written
+    #[instrument(skip(self), err(Debug))]
+    async fn get_category_stats(
+        &self,
+        ids: &[Uuid],
+    ) -> Result<impl ExactSizeIterator<Item = CategoryStats> + Send, CoreError> {
+        let requested = db_select_by_ids(self, ids, true).await?;
+        let mut parents: Vec<_> = requested
+            .iter()
+            .filter_map(|category| category.parent_id)
+            .collect();
+        let mut children: Vec<_> = requested
+            .iter()
+            .flat_map(|category| category.sub_categories.iter().copied())
+            .collect();
+        let mut subtrees: HashSet<_> = requested.iter().map(|category| category.id).collect();
+        let mut categories: BTreeMap<_, _> = requested
+            .into_iter()
+            .map(|category| (category.id, category))
+            .collect();
+
+        // visited categories are skipped, which also stops at cycles. Subtrees are walked on their
+        // own since an ancestor of one requested category may sit in the subtree of another
+        parents.retain(|id| !categories.contains_key(id));
+        while !parents.is_empty() {
+            let level = db_select_by_ids(self, &parents, true).await?;
+            parents = level
+                .iter()
+                .filter_map(|category| category.parent_id)
+                .collect();
+            categories.extend(level.into_iter().map(|category| (category.id, category)));
+            parents.retain(|id| !categories.contains_key(id));
+        }
+        children.retain(|id| subtrees.insert(*id));
+        while !children.is_empty() {
+            let level = db_select_by_ids(self, &children, false).await?;
+            children = level
+                .iter()
+                .flat_map(|category| category.sub_categories.iter().copied())
+                .collect();
+            categories.extend(level.into_iter().map(|category| (category.id, category)));
+            children.retain(|id| subtrees.insert(*id));
+        }
+
+        let listings: Vec<ListingCountEntity> = self
+            .client
+            .query("SELECT * FROM type::table($table) WHERE id INSIDE $listings")
+            .bind(("table", Collection::CategoryListing))
+            .bind((
+                "listings",
+                subtrees
+                    .iter()
+                    .map(|id| {
+                        Thing::from((Collection::CategoryListing.to_string(), id.to_string()))
+                    })
+                    .collect::<Vec<_>>(),
+            ))
+            .await
+            .map_err(map_db_error)?
+            .take(0)
+            .map_err(map_db_error)?;
+        let listings = listings
+            .into_iter()
+            .map(<(Uuid, u64)>::try_from)
+            .collect::<Result<HashMap<Uuid, u64>, CoreError>>()?;
+
+        Ok(category_stats(&categories, &listings, ids).into_iter())
+    }
+
+    #[instrument(skip(self), err(Debug))]
+    async fn get_category_history(
+        &self,
+        id: &Uuid,
+    ) -> Result<impl ExactSizeIterator<Item = CategoryRevision> + Send, CoreError> {
+        let revisions: Vec<RevisionEntity> = self
+            .client
+            .query(
+                "SELECT * FROM type::table($table) WHERE category_id = type::thing($record) ORDER BY revision",
+            )
+            .bind(("table", Collection::CategoryRevision))
+            .bind((
+                "record",
+                Thing::from((Collection::Category.to_string(), id.to_string())),
+            ))
+            .await
+            .map_err(map_db_error)?
+            .take(0)
+            .map_err(map_db_error)?;
+
+        let revisions = revisions
+            .into_iter()
+            .map(CategoryRevision::try_from)
+            .collect::<Result<Vec<CategoryRevision>, CoreError>>()?;
+
+        Ok(revisions.into_iter())
+    }
+
+    #[instrument(skip(self), err(Debug))]
+    async fn get_category_at(
+        &self,
+        id: &Uuid,
+        timestamp: &DateTime<Utc>,
+    ) -> Result<Option<Category>, CoreError> {
+        let revision: Option<RevisionEntity> = self
+            .client
+            .query(
+                "SELECT * FROM type::table($table) WHERE category_id = type::thing($record) AND recorded_at <= $timestamp ORDER BY revision DESC LIMIT 1",
+            )
+            .bind(("table", Collection::CategoryRevision))
+            .bind((
+                "record",
+                Thing::from((Collection::Category.to_string(), id.to_string())),
+            ))
+            .bind(("timestamp", Datetime::from(*timestamp)))
+            .await
+            .map_err(map_db_error)?
+            .take(0)
+            .map_err(map_db_error)?;
+
+        revision
+            .filter(|revision| revision.kind != RevisionKind::Deleted)
+            .map(|revision| Category::try_from(revision.category))
+            .transpose()
+    }
+
     async fn search(
         &self,
         query: impl AsRef<str> + Send + std::fmt::Debug,
-    ) -> Result<impl ExactSizeIterator<Item = Category>, CoreError> {
+    ) -> Result<impl ExactSizeIterator<Item = Category> + Send, CoreError> {
         if let Some(ref client) = self.search_client {
             let mut index = None;
             for _retries in 0..3 {
@@ -223,16 +959,12 @@ impl QueryCategories for Client {
                         .await
                         .map_err(|e| CoreError::Other(e.to_string()))?;
 
+                    // documents of deleted categories can linger until the index catches up
                     let search_results: Vec<Category> = results
                         .hits
                         .into_iter()
-                        .map(|hit| Category {
-                            id: hit.result.id,
-                            name: hit.result.name,
-                            sub_categories: hit.result.sub_categories,
-                            parent_id: hit.result.parent_id,
-                            image_url: hit.result.image_url,
-                        })
+                        .map(|hit| hit.result)
+                        .filter(|category| category.deleted_at.is_none())
                         .collect();
 
                     Ok(search_results.into_iter())
@@ -290,15 +1022,10 @@ impl Client {
                     let search_results: Vec<_> = results
                         .hits
                         .into_iter()
+                        .filter(|hit| hit.result.deleted_at.is_none())
                         .map(|hit| {
-                            let category = Category {
-                                id: hit.result.id,
-                                name: hit.result.name,
-                                sub_categories: hit.result.sub_categories,
-                                parent_id: hit.result.parent_id,
-                                image_url: hit.result.image_url,
-                            };
-                            let parent = if let Some(parent_id) = hit.result.parent_id {
+                            let category = hit.result;
+                            let parent = if let Some(parent_id) = category.parent_id {
                                 res.iter().find_map(|category| {
                                     if parent_id == category.id {
                                         Some(category.name.to_owned())
@@ -1,6 +1,6 @@
 use std::fmt::Display;
 
-use api_core::reexports::uuid::Uuid;
+use api_core::{reexports::uuid::Uuid, CategoryFilter, CategoryOrder};
 use redis::ToRedisArgs;
 
 #[derive(Clone, Copy)]
@@ -12,10 +12,27 @@ pub enum CacheKey<'a> {
     Category {
         id: &'a Uuid,
     },
+    CategoryTree {
+        root: Option<&'a Uuid>,
+        max_depth: usize,
+        generation: u64,
+    },
+    FoundCategories {
+        filter: &'a CategoryFilter,
+        order: Option<&'a CategoryOrder>,
+        generation: u64,
+    },
+    /// Counter bumped by every write, embedded in keys of queries over many categories
+    Generation,
     #[cfg(test)]
     TestOnly,
 }
 
+impl CacheKey<'_> {
+    /// Matches every key the cache stores categories under
+    pub const PATTERN: &'static str = "categories:*";
+}
+
 impl Display for CacheKey<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -33,6 +50,29 @@ impl Display for CacheKey<'_> {
                     }
                 ),
                 CacheKey::Category { id } => format!("id={id}"),
+                CacheKey::CategoryTree {
+                    root,
+                    max_depth,
+                    generation,
+                } => format!(
+                    "tree={}:depth={max_depth}:generation={generation}",
+                    match root {
+                        Some(id) => id.to_string(),
+                        None => {
+                            String::default()
+                        }
+                    }
+                ),
+                CacheKey::FoundCategories {
+                    filter,
+                    order,
+                    generation,
+                } => format!(
+                    "filter={}:order={}:generation={generation}",
+                    serde_json::to_string(filter).unwrap_or_default(),
+                    serde_json::to_string(order).unwrap_or_default()
+                ),
+                CacheKey::Generation => "generation".to_string(),
                 #[cfg(test)]
                 CacheKey::TestOnly => {
                     "test".to_string()
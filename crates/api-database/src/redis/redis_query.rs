@@ -33,6 +33,71 @@ pub async fn query<T: serde::de::DeserializeOwned>(
     }
 }
 
+/// Reads every one of `cache_keys` in a single round trip
+pub async fn query_many<T: serde::de::DeserializeOwned>(
+    cache_keys: &[CacheKey<'_>],
+    redis: &RedisPool,
+) -> Vec<Option<T>> {
+    let misses = || cache_keys.iter().map(|_| None).collect();
+
+    match redis.get().await {
+        Ok(mut redis) => {
+            let mut pipeline = redis::Pipeline::new();
+            for cache_key in cache_keys {
+                pipeline.get(*cache_key);
+            }
+
+            match redis.query_async_pipeline::<Vec<Vec<u8>>>(pipeline).await {
+                Ok(values) => values
+                    .iter()
+                    .zip(cache_keys)
+                    .map(|(bytes, cache_key)| {
+                        if bytes.is_empty() {
+                            return None;
+                        }
+                        bincode::deserialize::<T>(&bytes[..])
+                            .map_err(|decode_err| {
+                                error!(key = %cache_key, "[cache decode]: {decode_err}");
+                            })
+                            .ok()
+                    })
+                    .collect(),
+                Err(e) => {
+                    error!("[redis]: {e}");
+                    misses()
+                }
+            }
+        }
+        Err(e) => {
+            error!("[redis pool]: {e}");
+            misses()
+        }
+    }
+}
+
+/// Writes every entry in a single round trip
+pub async fn update_many<T: serde::Serialize>(
+    entries: &[(CacheKey<'_>, T)],
+    redis: &RedisPool,
+    ttl: Option<u64>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut pipeline = redis::Pipeline::new();
+    for (cache_key, data) in entries {
+        let bytes = bincode::serialize(data)?;
+        match ttl {
+            Some(ttl) => pipeline.pset_ex(*cache_key, bytes, ttl),
+            None => pipeline.set(*cache_key, bytes),
+        };
+    }
+
+    let mut redis = redis.get().await?;
+    if let Err(e) = redis.query_async_pipeline::<()>(pipeline).await {
+        error!("[cache update]: {e}");
+    }
+
+    Ok(())
+}
+
 pub async fn update<T: serde::Serialize>(
     cache_key: CacheKey<'_>,
     redis: &RedisPool,
@@ -58,3 +123,70 @@ pub async fn update<T: serde::Serialize>(
 
     Ok(())
 }
+
+/// The current [`CacheKey::Generation`], `None` when it cannot be read
+pub async fn generation(redis: &RedisPool) -> Option<u64> {
+    match redis.get().await {
+        Ok(mut redis) => match redis.get::<_, Option<u64>>(CacheKey::Generation).await {
+            Ok(generation) => Some(generation.unwrap_or_default()),
+            Err(e) => {
+                error!("[redis]: {e}");
+                None
+            }
+        },
+        Err(e) => {
+            error!("[redis pool]: {e}");
+            None
+        }
+    }
+}
+
+/// Removes `cache_keys` and moves to the next [`CacheKey::Generation`]
+pub async fn invalidate(cache_keys: &[CacheKey<'_>], redis: &RedisPool) {
+    match redis.get().await {
+        Ok(mut redis) => {
+            let mut pipeline = redis::Pipeline::new();
+            for cache_key in cache_keys {
+                pipeline.del(*cache_key);
+            }
+            pipeline.incr(CacheKey::Generation, 1);
+
+            if let Err(e) = redis.query_async_pipeline::<()>(pipeline).await {
+                error!("[cache invalidate]: {e}");
+            }
+        }
+        Err(e) => {
+            error!("[redis pool]: {e}");
+        }
+    }
+}
+
+/// Removes every key matching `pattern` on the node the connection lands on
+pub async fn invalidate_matching(
+    pattern: &str,
+    redis: &RedisPool,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut connection = redis.get().await?;
+
+    let mut cursor = 0_u64;
+    let mut removed = 0;
+    loop {
+        let mut cmd = redis::cmd("SCAN");
+        cmd.arg(cursor)
+            .arg("MATCH")
+            .arg(pattern)
+            .arg("COUNT")
+            .arg(100);
+        let (next, keys): (u64, Vec<String>) = connection.query_async(cmd).await?;
+
+        if !keys.is_empty() {
+            removed += connection.del::<_, usize>(keys).await?;
+        }
+        if next == 0 {
+            break;
+        }
+        cursor = next;
+    }
+
+    Ok(removed)
+}
@@ -0,0 +1,1549 @@
+use crate::MemoryClient;
+use anyhow::Result;
+use api_core::{
+    api::{CoreError, MutateCategories, QueryCategories},
+    reexports::uuid::Uuid,
+    AttributeDefinition, AttributeKind, Category, CategoryDeletion, CategoryFilter, CategoryOrder,
+    CategoryOrderField, CategoryPatch, CategoryUpdate, DeleteStrategy, KeysetPage, OrderDirection,
+    RevisionKind, Translation,
+};
+
+fn create_category_item(parent_id: Option<Uuid>) -> Category {
+    let id = Uuid::now_v7();
+    Category {
+        id,
+        // siblings may not share a name
+        name: format!("TestCategoryInput {id}"),
+        sub_categories: vec![],
+        image_url: None,
+        parent_id,
+        ..Default::default()
+    }
+}
+
+async fn sub_categories(client: &MemoryClient, id: &Uuid) -> Result<Vec<Uuid>> {
+    let category = client.get_category_by_id(id).await?;
+    Ok(category.expect("category to exist").sub_categories)
+}
+
+#[tokio::test]
+async fn memory_create_get_delete() -> Result<()> {
+    let client = MemoryClient::new();
+
+    let category = create_category_item(None);
+    let input = client.create_category(&category, None).await?;
+    assert_ne!(input.id, category.id);
+    assert_eq!(input.name, category.name);
+
+    assert_eq!(client.get_categories().await?.count(), 1);
+    assert_eq!(
+        client.get_category_by_id(&input.id).await?,
+        Some(input.clone())
+    );
+
+    let deleted = client
+        .delete_category(&input.id, DeleteStrategy::Restrict, None, None)
+        .await?
+        .map(|deleted| deleted.category);
+    assert_eq!(deleted.as_ref().map(|category| category.id), Some(input.id));
+    assert!(deleted.and_then(|category| category.deleted_at).is_some());
+    assert!(client.get_category_by_id(&input.id).await?.is_none());
+    assert!(client
+        .delete_category(&input.id, DeleteStrategy::Restrict, None, None)
+        .await?
+        .is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn memory_parent_must_exist() -> Result<()> {
+    let client = MemoryClient::new();
+
+    let orphan = create_category_item(Some(Uuid::now_v7()));
+    assert!(client.create_category(&orphan, None).await.is_err());
+
+    let parent = client
+        .create_category(&create_category_item(None), None)
+        .await?;
+    let child = client
+        .create_category(&create_category_item(Some(parent.id)), None)
+        .await?;
+
+    assert!(client
+        .update_category(&child.id, &orphan, None, None)
+        .await
+        .is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn memory_update_missing() -> Result<()> {
+    let client = MemoryClient::new();
+
+    let res = client
+        .update_category(&Uuid::now_v7(), &create_category_item(None), None, None)
+        .await?;
+    assert!(res.is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn memory_sub_categories() -> Result<()> {
+    let client = MemoryClient::new();
+
+    let parent = client
+        .create_category(&create_category_item(None), None)
+        .await?;
+    let child = client
+        .create_category(&create_category_item(Some(parent.id)), None)
+        .await?;
+
+    let roots: Vec<_> = client.get_sub_categories(None).await?.collect();
+    assert_eq!(roots.len(), 1);
+    assert_eq!(roots[0].id, parent.id);
+
+    let children: Vec<_> = client.get_sub_categories(Some(&parent.id)).await?.collect();
+    assert_eq!(children, vec![child]);
+
+    assert!(client
+        .get_sub_categories(Some(&Uuid::now_v7()))
+        .await
+        .is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn memory_parent_links() -> Result<()> {
+    let client = MemoryClient::new();
+
+    let first = client
+        .create_category(&create_category_item(None), None)
+        .await?;
+    let second = client
+        .create_category(&create_category_item(None), None)
+        .await?;
+
+    let mut child = create_category_item(Some(first.id));
+    child.sub_categories = vec![Uuid::now_v7()];
+    let child = client.create_category(&child, None).await?;
+    assert!(child.sub_categories.is_empty());
+
+    assert_eq!(sub_categories(&client, &first.id).await?, vec![child.id]);
+
+    // moving the child relinks both parents
+    let mut update = child.clone();
+    update.parent_id = Some(second.id);
+    client
+        .update_category(&child.id, &update, None, None)
+        .await?;
+    assert!(sub_categories(&client, &first.id).await?.is_empty());
+    assert_eq!(sub_categories(&client, &second.id).await?, vec![child.id]);
+
+    // updates keep the stored sub_categories
+    let grandchild = client
+        .create_category(&create_category_item(Some(child.id)), None)
+        .await?;
+    client
+        .update_category(&child.id, &update, None, None)
+        .await?;
+    assert_eq!(
+        sub_categories(&client, &child.id).await?,
+        vec![grandchild.id]
+    );
+
+    client
+        .delete_category(&grandchild.id, DeleteStrategy::Restrict, None, None)
+        .await?;
+    client
+        .delete_category(&child.id, DeleteStrategy::Restrict, None, None)
+        .await?;
+    assert!(sub_categories(&client, &second.id).await?.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn memory_delete_strategies() -> Result<()> {
+    let client = MemoryClient::new();
+
+    let root = client
+        .create_category(&create_category_item(None), None)
+        .await?;
+    let parent = client
+        .create_category(&create_category_item(Some(root.id)), None)
+        .await?;
+    let first = client
+        .create_category(&create_category_item(Some(parent.id)), None)
+        .await?;
+    let second = client
+        .create_category(&create_category_item(Some(parent.id)), None)
+        .await?;
+    let leaf = client
+        .create_category(&create_category_item(Some(second.id)), None)
+        .await?;
+
+    let res = client
+        .delete_category(&parent.id, DeleteStrategy::Restrict, None, None)
+        .await;
+    assert!(matches!(res, Err(CoreError::HasSubCategories(id)) if id == parent.id));
+    assert!(client.get_category_by_id(&parent.id).await?.is_some());
+
+    let deleted = client
+        .delete_category(&parent.id, DeleteStrategy::Reparent, None, None)
+        .await?
+        .expect("category to be deleted");
+    assert_eq!(deleted.reparented, vec![first.id, second.id]);
+    assert!(deleted.deleted.is_empty());
+    assert_eq!(
+        sub_categories(&client, &root.id).await?,
+        vec![first.id, second.id]
+    );
+    let moved = client.get_category_by_id(&first.id).await?;
+    assert_eq!(moved.and_then(|category| category.parent_id), Some(root.id));
+
+    let deleted = client
+        .delete_category(&root.id, DeleteStrategy::Cascade, None, None)
+        .await?
+        .expect("category to be deleted");
+    assert_eq!(deleted.deleted, vec![first.id, second.id, leaf.id]);
+    assert_eq!(client.get_categories().await?.len(), 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn memory_hierarchy_validation() -> Result<()> {
+    let client = MemoryClient::new().with_max_depth(3);
+
+    let root = client
+        .create_category(&create_category_item(None), None)
+        .await?;
+    let parent = client
+        .create_category(&create_category_item(Some(root.id)), None)
+        .await?;
+    let child = client
+        .create_category(&create_category_item(Some(parent.id)), None)
+        .await?;
+
+    let res = client
+        .create_category(&create_category_item(Some(child.id)), None)
+        .await;
+    assert!(matches!(res, Err(CoreError::MaxDepthExceeded(3))));
+
+    let mut update = parent.clone();
+    update.parent_id = Some(parent.id);
+    let res = client
+        .update_category(&parent.id, &update, None, None)
+        .await;
+    assert!(matches!(
+        res,
+        Err(CoreError::CyclicHierarchy { id, parent: new_parent })
+            if id == parent.id && new_parent == parent.id
+    ));
+
+    update.parent_id = Some(child.id);
+    let res = client
+        .update_category(&parent.id, &update, None, None)
+        .await;
+    assert!(matches!(res, Err(CoreError::CyclicHierarchy { .. })));
+
+    // moving a subtree counts its own levels against the limit
+    let other = client
+        .create_category(&create_category_item(None), None)
+        .await?;
+    update.parent_id = Some(other.id);
+    assert!(client
+        .update_category(&parent.id, &update, None, None)
+        .await
+        .is_ok());
+
+    let mut update = other.clone();
+    update.parent_id = Some(root.id);
+    let res = client.update_category(&other.id, &update, None, None).await;
+    assert!(matches!(res, Err(CoreError::MaxDepthExceeded(3))));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn memory_move_category() -> Result<()> {
+    let client = MemoryClient::new();
+
+    let first = client
+        .create_category(&create_category_item(None), None)
+        .await?;
+    let second = client
+        .create_category(&create_category_item(None), None)
+        .await?;
+
+    let mut children = Vec::new();
+    for parent in [first.id, first.id, second.id] {
+        let child = client
+            .create_category(&create_category_item(Some(parent)), None)
+            .await?;
+        children.push(child.id);
+    }
+
+    let moved = client
+        .move_category(&children[1], Some(&second.id), Some(0), None, None)
+        .await?
+        .expect("category to be moved");
+    assert_eq!(moved.parent_id, Some(second.id));
+    assert_eq!(sub_categories(&client, &first.id).await?, vec![children[0]]);
+    assert_eq!(
+        sub_categories(&client, &second.id).await?,
+        vec![children[1], children[2]]
+    );
+    // the sibling pushed down is renumbered like a reorder would
+    let pushed = client
+        .get_category_by_id(&children[2])
+        .await?
+        .expect("category to exist");
+    assert_eq!((pushed.position, pushed.version), (1, 2));
+    let history: Vec<_> = client.get_category_history(&children[2]).await?.collect();
+    assert_eq!(history.len(), 2);
+
+    // moving within the same parent reorders the siblings
+    client
+        .move_category(&children[1], Some(&second.id), Some(10), None, None)
+        .await?;
+    assert_eq!(
+        sub_categories(&client, &second.id).await?,
+        vec![children[2], children[1]]
+    );
+
+    let res = client
+        .move_category(&second.id, Some(&children[1]), None, None, None)
+        .await;
+    assert!(matches!(res, Err(CoreError::CyclicHierarchy { .. })));
+
+    let moved = client
+        .move_category(&children[0], None, None, None, None)
+        .await?
+        .expect("category to be moved");
+    assert!(moved.parent_id.is_none());
+    assert!(sub_categories(&client, &first.id).await?.is_empty());
+
+    assert!(client
+        .move_category(&Uuid::now_v7(), None, None, None, None)
+        .await?
+        .is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn memory_reorder_sub_categories() -> Result<()> {
+    let client = MemoryClient::new();
+
+    let parent = client
+        .create_category(&create_category_item(None), None)
+        .await?;
+    let mut children = Vec::new();
+    for _ in 0..3 {
+        let child = client
+            .create_category(&create_category_item(Some(parent.id)), None)
+            .await?;
+        assert_eq!(child.position as usize, children.len());
+        children.push(child.id);
+    }
+
+    let ordered = vec![children[2], children[0], children[1]];
+    let reordered = client
+        .reorder_sub_categories(Some(&parent.id), &ordered, Some("alice"))
+        .await?
+        .expect("subcategories to be reordered");
+    assert_eq!(
+        reordered
+            .iter()
+            .map(|category| category.id)
+            .collect::<Vec<_>>(),
+        ordered
+    );
+    assert!(reordered
+        .iter()
+        .all(|category| category.version == 2 && category.updated_by.as_deref() == Some("alice")));
+    assert_eq!(client.get_category_history(&children[0]).await?.len(), 2);
+
+    // siblings keeping their position are left untouched
+    let reordered = client
+        .reorder_sub_categories(Some(&parent.id), &ordered, None)
+        .await?
+        .expect("subcategories to be reordered");
+    assert!(reordered.iter().all(|category| category.version == 2));
+    assert_eq!(sub_categories(&client, &parent.id).await?, ordered);
+
+    let listed: Vec<_> = client
+        .get_sub_categories(Some(&parent.id))
+        .await?
+        .map(|category| category.id)
+        .collect();
+    assert_eq!(listed, ordered);
+
+    let tree: Vec<_> = client.get_category_tree(None, 1).await?.collect();
+    let branch: Vec<_> = tree[0]
+        .children
+        .iter()
+        .map(|node| node.category.id)
+        .collect();
+    assert_eq!(branch, ordered);
+
+    // every category comes before its subcategories, siblings in order
+    let all: Vec<_> = client
+        .get_categories()
+        .await?
+        .map(|category| category.id)
+        .collect();
+    assert_eq!(all, [vec![parent.id], ordered.clone()].concat());
+
+    // moving to a position renumbers the new siblings
+    client
+        .move_category(&children[1], Some(&parent.id), Some(0), None, None)
+        .await?;
+    let listed: Vec<_> = client
+        .get_sub_categories(Some(&parent.id))
+        .await?
+        .map(|category| (category.id, category.position))
+        .collect();
+    assert_eq!(
+        listed,
+        vec![(children[1], 0), (children[2], 1), (children[0], 2)]
+    );
+
+    for invalid in [
+        vec![children[0], children[1]],
+        vec![children[0], children[0], children[1], children[2]],
+        vec![children[0], children[1], parent.id],
+    ] {
+        let res = client
+            .reorder_sub_categories(Some(&parent.id), &invalid, None)
+            .await;
+        assert!(matches!(res, Err(CoreError::InvalidOrder)));
+    }
+
+    let second = client
+        .create_category(&create_category_item(None), None)
+        .await?;
+    client
+        .reorder_sub_categories(None, &[second.id, parent.id], None)
+        .await?;
+    let top_level: Vec<_> = client
+        .get_sub_categories(None)
+        .await?
+        .map(|category| category.id)
+        .collect();
+    assert_eq!(top_level, vec![second.id, parent.id]);
+
+    assert!(client
+        .reorder_sub_categories(Some(&Uuid::now_v7()), &[], None)
+        .await?
+        .is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn memory_get_categories_by_ids() -> Result<()> {
+    let client = MemoryClient::new();
+
+    let first = client
+        .create_category(&create_category_item(None), None)
+        .await?;
+    let second = client
+        .create_category(&create_category_item(None), None)
+        .await?;
+    let deleted = client
+        .create_category(&create_category_item(None), None)
+        .await?;
+    client
+        .delete_category(&deleted.id, DeleteStrategy::Restrict, None, None)
+        .await?;
+
+    let ids = [second.id, Uuid::now_v7(), deleted.id, first.id];
+    let found: Vec<_> = client
+        .get_categories_by_ids(&ids)
+        .await?
+        .map(|category| category.id)
+        .collect();
+    assert_eq!(found, vec![second.id, first.id]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn memory_category_stats() -> Result<()> {
+    let client = MemoryClient::new();
+
+    let root = client
+        .create_category(&create_category_item(None), None)
+        .await?;
+    let child = client
+        .create_category(&create_category_item(Some(root.id)), None)
+        .await?;
+    let grandchild = client
+        .create_category(&create_category_item(Some(child.id)), None)
+        .await?;
+    let deleted = client
+        .create_category(&create_category_item(Some(root.id)), None)
+        .await?;
+
+    assert_eq!(client.adjust_listing_count(&root.id, 2).await?, Some(2));
+    assert_eq!(
+        client.adjust_listing_count(&grandchild.id, 5).await?,
+        Some(5)
+    );
+    assert_eq!(client.adjust_listing_count(&deleted.id, 7).await?, Some(7));
+    // counts stop at zero
+    assert_eq!(client.adjust_listing_count(&child.id, -3).await?, Some(0));
+    assert_eq!(client.adjust_listing_count(&Uuid::now_v7(), 1).await?, None);
+    client
+        .delete_category(&deleted.id, DeleteStrategy::Restrict, None, None)
+        .await?;
+
+    let stats: Vec<_> = client
+        .get_category_stats(&[grandchild.id, root.id, Uuid::now_v7()])
+        .await?
+        .collect();
+    assert_eq!(stats.len(), 2);
+    assert_eq!(
+        (
+            stats[0].depth,
+            stats[0].child_count,
+            stats[0].descendant_count
+        ),
+        (2, 0, 0)
+    );
+    assert_eq!(
+        (stats[0].listing_count, stats[0].total_listing_count),
+        (5, 5)
+    );
+    // deleted subcategories are not counted
+    assert_eq!(
+        (
+            stats[1].depth,
+            stats[1].child_count,
+            stats[1].descendant_count
+        ),
+        (0, 1, 2)
+    );
+    assert_eq!(
+        (stats[1].listing_count, stats[1].total_listing_count),
+        (2, 7)
+    );
+
+    // deleted categories keep their count but cannot be adjusted
+    assert_eq!(client.adjust_listing_count(&deleted.id, -2).await?, None);
+
+    // purging drops the count, so a category brought back from its history starts at zero
+    client.purge_category(&deleted.id).await?;
+    client.restore_category(&deleted.id, 1, None).await?;
+    let stats: Vec<_> = client.get_category_stats(&[deleted.id]).await?.collect();
+    assert_eq!(stats[0].listing_count, 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn memory_patch_category() -> Result<()> {
+    let client = MemoryClient::new();
+
+    let mut category = create_category_item(None);
+    category.image_url = Some("https://example.com/image.png".into());
+    let category = client.create_category(&category, None).await?;
+
+    let patch = CategoryPatch {
+        name: Some("Renamed".into()),
+        ..Default::default()
+    };
+    let patched = client
+        .patch_category(&category.id, &patch, None, None)
+        .await?
+        .expect("category to exist");
+    assert_eq!(patched.name, "Renamed");
+    assert_eq!(patched.image_url, category.image_url);
+
+    assert!(client
+        .patch_category(&Uuid::now_v7(), &patch, None, None)
+        .await?
+        .is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn memory_version_conflicts() -> Result<()> {
+    let client = MemoryClient::new();
+
+    let category = client
+        .create_category(&create_category_item(None), None)
+        .await?;
+    assert_eq!(category.version, 1);
+
+    let updated = client
+        .update_category(&category.id, &category, Some(1), None)
+        .await?
+        .expect("category to exist");
+    assert_eq!(updated.version, 2);
+
+    let res = client
+        .update_category(&category.id, &category, Some(1), None)
+        .await;
+    assert!(matches!(res, Err(CoreError::VersionConflict(id)) if id == category.id));
+
+    let patch = CategoryPatch::default();
+    let res = client
+        .patch_category(&category.id, &patch, Some(1), None)
+        .await;
+    assert!(matches!(res, Err(CoreError::VersionConflict(_))));
+    let patched = client
+        .patch_category(&category.id, &patch, Some(2), None)
+        .await?
+        .expect("category to exist");
+    assert_eq!(patched.version, 3);
+
+    let res = client
+        .move_category(&category.id, None, None, Some(2), None)
+        .await;
+    assert!(matches!(res, Err(CoreError::VersionConflict(_))));
+    let moved = client
+        .move_category(&category.id, None, None, Some(3), None)
+        .await?
+        .expect("category to exist");
+    assert_eq!(moved.version, 4);
+
+    let res = client
+        .delete_category(&category.id, DeleteStrategy::Restrict, Some(3), None)
+        .await;
+    assert!(matches!(res, Err(CoreError::VersionConflict(_))));
+    assert!(client
+        .delete_category(&category.id, DeleteStrategy::Restrict, Some(4), None)
+        .await?
+        .is_some());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn memory_audit_metadata() -> Result<()> {
+    let client = MemoryClient::new();
+
+    let category = client
+        .create_category(&create_category_item(None), Some("alice"))
+        .await?;
+    assert!(category.created_at.is_some());
+    assert_eq!(category.created_at, category.updated_at);
+    assert_eq!(category.created_by.as_deref(), Some("alice"));
+    assert_eq!(category.updated_by.as_deref(), Some("alice"));
+
+    let patch = CategoryPatch {
+        name: Some("Renamed".into()),
+        ..Default::default()
+    };
+    let patched = client
+        .patch_category(&category.id, &patch, None, Some("bob"))
+        .await?
+        .expect("category to exist");
+    assert_eq!(patched.created_at, category.created_at);
+    assert!(patched.updated_at >= category.updated_at);
+    assert_eq!(patched.created_by.as_deref(), Some("alice"));
+    assert_eq!(patched.updated_by.as_deref(), Some("bob"));
+
+    let updated = client
+        .update_category(&category.id, &create_category_item(None), None, None)
+        .await?
+        .expect("category to exist");
+    assert_eq!(updated.created_at, category.created_at);
+    assert_eq!(updated.created_by.as_deref(), Some("alice"));
+    assert_eq!(updated.updated_by, None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn memory_category_history() -> Result<()> {
+    use api_core::reexports::chrono::Utc;
+
+    let client = MemoryClient::new();
+
+    let category = client
+        .create_category(&create_category_item(None), Some("alice"))
+        .await?;
+    let created_at = Utc::now();
+    let patch = CategoryPatch {
+        name: Some("Renamed".into()),
+        ..Default::default()
+    };
+    client
+        .patch_category(&category.id, &patch, None, Some("bob"))
+        .await?;
+    client
+        .delete_category(&category.id, DeleteStrategy::Restrict, None, None)
+        .await?;
+
+    let history: Vec<_> = client.get_category_history(&category.id).await?.collect();
+    let kinds: Vec<_> = history
+        .iter()
+        .map(|item| (item.revision, item.kind))
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            (1, RevisionKind::Created),
+            (2, RevisionKind::Updated),
+            (3, RevisionKind::Deleted)
+        ]
+    );
+    assert_eq!(history[1].recorded_by.as_deref(), Some("bob"));
+    assert_eq!(history[1].category.name, "Renamed");
+
+    let before = client.get_category_at(&category.id, &created_at).await?;
+    assert_eq!(
+        before.map(|category| category.name),
+        Some(category.name.clone())
+    );
+    assert!(client
+        .get_category_at(&category.id, &Utc::now())
+        .await?
+        .is_none());
+
+    let restored = client
+        .restore_category(&category.id, 1, Some("carol"))
+        .await?
+        .expect("revision to exist");
+    assert_eq!(restored.name, category.name);
+    assert_eq!(restored.version, 4);
+    assert_eq!(restored.updated_by.as_deref(), Some("carol"));
+    assert_eq!(
+        client.get_category_by_id(&category.id).await?,
+        Some(restored)
+    );
+    assert_eq!(
+        client
+            .get_category_history(&category.id)
+            .await?
+            .last()
+            .map(|item| item.kind),
+        Some(RevisionKind::Restored)
+    );
+
+    assert!(client
+        .restore_category(&category.id, 10, None)
+        .await?
+        .is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn memory_trash() -> Result<()> {
+    let client = MemoryClient::new();
+
+    let root = client
+        .create_category(&create_category_item(None), None)
+        .await?;
+    let parent = client
+        .create_category(&create_category_item(Some(root.id)), None)
+        .await?;
+    let child = client
+        .create_category(&create_category_item(Some(parent.id)), None)
+        .await?;
+
+    client
+        .delete_category(&parent.id, DeleteStrategy::Cascade, None, None)
+        .await?;
+    assert_eq!(client.get_categories().await?.len(), 1);
+    assert!(sub_categories(&client, &root.id).await?.is_empty());
+    assert_eq!(client.get_sub_categories(Some(&root.id)).await?.len(), 0);
+    assert_eq!(client.search("TestCategory").await?.len(), 1);
+    let mut trash: Vec<_> = client
+        .get_deleted_categories()
+        .await?
+        .map(|category| category.id)
+        .collect();
+    trash.sort();
+    let mut expected = vec![parent.id, child.id];
+    expected.sort();
+    assert_eq!(trash, expected);
+
+    // deleted categories cannot be changed or used as a parent
+    assert!(client
+        .patch_category(&parent.id, &CategoryPatch::default(), None, None)
+        .await?
+        .is_none());
+    assert!(client
+        .create_category(&create_category_item(Some(parent.id)), None)
+        .await
+        .is_err());
+
+    // restoring takes the subtree deleted along with it out of the trash
+    let res = client.undelete_category(&child.id, None).await;
+    assert!(res.is_err());
+    let restored = client
+        .undelete_category(&parent.id, Some("alice"))
+        .await?
+        .expect("category to be in the trash");
+    assert!(restored.category.deleted_at.is_none());
+    assert_eq!(restored.category.sub_categories, vec![child.id]);
+    assert_eq!(restored.restored, vec![child.id]);
+    assert_eq!(sub_categories(&client, &root.id).await?, vec![parent.id]);
+    assert_eq!(client.get_categories().await?.len(), 3);
+    assert!(client.undelete_category(&parent.id, None).await?.is_none());
+
+    let res = client.purge_category(&parent.id).await;
+    assert!(matches!(res, Err(CoreError::NotDeleted(id)) if id == parent.id));
+    client
+        .delete_category(&parent.id, DeleteStrategy::Cascade, None, None)
+        .await?;
+    let purged = client
+        .purge_category(&parent.id)
+        .await?
+        .expect("category to be in the trash");
+    assert_eq!(purged.deleted, vec![child.id]);
+    assert_eq!(client.get_deleted_categories().await?.len(), 0);
+    assert_eq!(client.get_categories().await?.len(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn memory_restore_out_of_trashed_parent() -> Result<()> {
+    let client = MemoryClient::new();
+
+    let root = client
+        .create_category(&create_category_item(None), None)
+        .await?;
+    let child = client
+        .create_category(&create_category_item(Some(root.id)), None)
+        .await?;
+    let parent = client
+        .create_category(&create_category_item(Some(root.id)), None)
+        .await?;
+    client
+        .move_category(&child.id, Some(&parent.id), None, None, None)
+        .await?;
+    let sibling = client
+        .create_category(&create_category_item(Some(root.id)), None)
+        .await?;
+    client
+        .delete_category(&parent.id, DeleteStrategy::Cascade, None, None)
+        .await?;
+
+    // the first revision puts it back under the root, after the siblings it has there now
+    let restored = client
+        .restore_category(&child.id, 1, None)
+        .await?
+        .expect("revision to exist");
+    assert_eq!(restored.parent_id, Some(root.id));
+    assert!(restored.position > sibling.position);
+    let order: Vec<_> = client
+        .get_sub_categories(Some(&root.id))
+        .await?
+        .map(|category| category.id)
+        .collect();
+    assert_eq!(order, vec![sibling.id, child.id]);
+
+    let purged = client
+        .purge_category(&parent.id)
+        .await?
+        .expect("category to be in the trash");
+    assert!(purged.deleted.is_empty());
+    assert_eq!(client.get_category_by_id(&child.id).await?, Some(restored));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn memory_batch_create() -> Result<()> {
+    let client = MemoryClient::new().with_max_depth(3);
+
+    // children can come before the parent they reference
+    let root = create_category_item(None);
+    let child = create_category_item(Some(root.id));
+    let grandchild = create_category_item(Some(child.id));
+    let created = client
+        .create_categories(
+            &[grandchild.clone(), child.clone(), root.clone()],
+            Some("importer"),
+        )
+        .await?;
+    let ids: Vec<_> = created.iter().map(|category| category.id).collect();
+    assert_eq!(ids, vec![grandchild.id, child.id, root.id]);
+    assert_eq!(sub_categories(&client, &root.id).await?, vec![child.id]);
+    assert_eq!(
+        sub_categories(&client, &child.id).await?,
+        vec![grandchild.id]
+    );
+    assert!(created
+        .iter()
+        .all(|category| category.created_by.as_deref() == Some("importer")));
+    assert_eq!(client.get_category_history(&root.id).await?.len(), 1);
+
+    // nil IDs get generated
+    let created = client
+        .create_categories(
+            &[Category {
+                id: Uuid::nil(),
+                ..create_category_item(Some(root.id))
+            }],
+            None,
+        )
+        .await?;
+    assert!(!created[0].id.is_nil());
+    assert_eq!(sub_categories(&client, &root.id).await?.len(), 2);
+
+    // nothing is written when one of the categories is rejected
+    let res = client
+        .create_categories(
+            &[
+                create_category_item(None),
+                create_category_item(Some(grandchild.id)),
+            ],
+            None,
+        )
+        .await;
+    assert!(matches!(res, Err(CoreError::MaxDepthExceeded(3))));
+    let res = client
+        .create_categories(&[create_category_item(None), root.clone()], None)
+        .await;
+    assert!(matches!(res, Err(CoreError::AlreadyExists(id)) if id == root.id));
+    let looped = create_category_item(None);
+    let res = client
+        .create_categories(
+            &[
+                Category {
+                    parent_id: Some(looped.id),
+                    ..looped.clone()
+                },
+                create_category_item(None),
+            ],
+            None,
+        )
+        .await;
+    assert!(matches!(res, Err(CoreError::CyclicHierarchy { .. })));
+    assert_eq!(client.get_categories().await?.len(), 4);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn memory_batch_update_delete() -> Result<()> {
+    let client = MemoryClient::new();
+
+    let first = client
+        .create_category(&create_category_item(None), None)
+        .await?;
+    let second = client
+        .create_category(&create_category_item(None), None)
+        .await?;
+    let child = client
+        .create_category(&create_category_item(Some(first.id)), None)
+        .await?;
+
+    // updates see the ones before them
+    let updates = [
+        CategoryUpdate {
+            id: second.id,
+            category: Category {
+                name: "Second".into(),
+                ..create_category_item(Some(first.id))
+            },
+            expected_version: Some(second.version),
+        },
+        CategoryUpdate {
+            id: child.id,
+            category: create_category_item(Some(second.id)),
+            expected_version: None,
+        },
+        CategoryUpdate {
+            id: Uuid::now_v7(),
+            category: create_category_item(None),
+            expected_version: None,
+        },
+    ];
+    let updated = client.update_categories(&updates, None).await?;
+    assert_eq!(updated.len(), 3);
+    assert_eq!(updated[0].as_ref().map(|c| c.name.as_str()), Some("Second"));
+    assert!(updated[2].is_none());
+    assert_eq!(sub_categories(&client, &first.id).await?, vec![second.id]);
+    assert_eq!(sub_categories(&client, &second.id).await?, vec![child.id]);
+
+    let stale = [
+        CategoryUpdate {
+            id: first.id,
+            category: create_category_item(None),
+            expected_version: None,
+        },
+        CategoryUpdate {
+            id: second.id,
+            category: create_category_item(None),
+            expected_version: Some(second.version),
+        },
+    ];
+    let res = client.update_categories(&stale, None).await;
+    assert!(matches!(res, Err(CoreError::VersionConflict(id)) if id == second.id));
+    assert_eq!(client.get_category_history(&first.id).await?.len(), 1);
+
+    // a failed delete keeps the others
+    let deletions = |ids: &[Uuid]| {
+        ids.iter()
+            .map(|id| CategoryDeletion {
+                id: *id,
+                expected_version: None,
+            })
+            .collect::<Vec<_>>()
+    };
+    let res = client
+        .delete_categories(
+            &deletions(&[child.id, first.id]),
+            DeleteStrategy::Restrict,
+            None,
+        )
+        .await;
+    assert!(matches!(res, Err(CoreError::HasSubCategories(id)) if id == first.id));
+    assert_eq!(client.get_categories().await?.len(), 3);
+
+    let current = client
+        .get_category_by_id(&child.id)
+        .await?
+        .expect("category to exist");
+    let stale = [CategoryDeletion {
+        id: child.id,
+        expected_version: Some(current.version + 1),
+    }];
+    let res = client
+        .delete_categories(&stale, DeleteStrategy::Restrict, None)
+        .await;
+    assert!(matches!(res, Err(CoreError::VersionConflict(id)) if id == child.id));
+    assert_eq!(client.get_categories().await?.len(), 3);
+
+    // categories deleted along with an earlier one are skipped
+    let deleted = client
+        .delete_categories(
+            &deletions(&[first.id, child.id]),
+            DeleteStrategy::Cascade,
+            None,
+        )
+        .await?;
+    assert_eq!(
+        deleted[0].as_ref().map(|deleted| deleted.deleted.clone()),
+        Some(vec![second.id, child.id])
+    );
+    assert!(deleted[1].is_none());
+    assert_eq!(client.get_deleted_categories().await?.len(), 3);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn memory_find_categories() -> Result<()> {
+    let client = MemoryClient::new();
+
+    let parent = client
+        .create_category(&create_category_item(None), None)
+        .await?;
+    let child = client
+        .create_category(&create_category_item(Some(parent.id)), None)
+        .await?;
+    let grandchild = client
+        .create_category(&create_category_item(Some(child.id)), None)
+        .await?;
+    let deleted = client
+        .create_category(&create_category_item(Some(parent.id)), None)
+        .await?;
+    client
+        .delete_category(&deleted.id, DeleteStrategy::Restrict, None, None)
+        .await?;
+
+    let find = |filter: CategoryFilter, order: Option<CategoryOrder>| {
+        let client = &client;
+        async move {
+            let found: Vec<_> = client
+                .find_categories(&filter, order.as_ref())
+                .await?
+                .map(|category| category.id)
+                .collect();
+            Result::<_>::Ok(found)
+        }
+    };
+
+    // without an order, like `get_categories`
+    assert_eq!(
+        find(CategoryFilter::default(), None).await?,
+        vec![parent.id, child.id, grandchild.id]
+    );
+    assert_eq!(
+        find(
+            CategoryFilter {
+                depth: Some(2),
+                ..Default::default()
+            },
+            None
+        )
+        .await?,
+        vec![grandchild.id]
+    );
+    assert_eq!(
+        find(
+            CategoryFilter {
+                has_parent: Some(true),
+                is_leaf: Some(false),
+                ..Default::default()
+            },
+            None
+        )
+        .await?,
+        vec![child.id]
+    );
+    assert_eq!(
+        find(
+            CategoryFilter {
+                ids: Some(vec![parent.id, deleted.id]),
+                include_deleted: true,
+                ..Default::default()
+            },
+            None
+        )
+        .await?,
+        vec![parent.id, deleted.id]
+    );
+
+    let filter = CategoryFilter {
+        name_contains: Some("testcategory".into()),
+        ..Default::default()
+    };
+    let order = CategoryOrder {
+        field: CategoryOrderField::CreatedAt,
+        direction: OrderDirection::Desc,
+    };
+    let found: Vec<_> = client
+        .find_categories(&filter, Some(&order))
+        .await?
+        .collect();
+    assert_eq!(found.len(), 3);
+    assert!(found
+        .windows(2)
+        .all(|pair| (pair[0].created_at, pair[0].id) > (pair[1].created_at, pair[1].id)));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn memory_find_categories_page() -> Result<()> {
+    let client = MemoryClient::new();
+
+    let parent = client
+        .create_category(&create_category_item(None), None)
+        .await?;
+    let mut children = Vec::new();
+    for _ in 0..4 {
+        let child = client
+            .create_category(&create_category_item(Some(parent.id)), None)
+            .await?;
+        children.push(child);
+    }
+
+    let filter = CategoryFilter {
+        has_parent: Some(true),
+        ..Default::default()
+    };
+    let order = CategoryOrder {
+        field: CategoryOrderField::Position,
+        direction: OrderDirection::Asc,
+    };
+    assert_eq!(client.count_categories(&filter).await?, 4);
+
+    let page = |after: Option<&Category>, before: Option<&Category>, from_end| KeysetPage {
+        after: after.map(|category| order.key(category)),
+        before: before.map(|category| order.key(category)),
+        limit: 2,
+        from_end,
+    };
+
+    let found: Vec<_> = client
+        .find_categories_page(&filter, &order, &page(Some(&children[0]), None, false))
+        .await?
+        .map(|category| category.id)
+        .collect();
+    assert_eq!(found, [children[1].id, children[2].id]);
+
+    let found: Vec<_> = client
+        .find_categories_page(&filter, &order, &page(None, Some(&children[3]), true))
+        .await?
+        .map(|category| category.id)
+        .collect();
+    assert_eq!(found, [children[1].id, children[2].id]);
+
+    let found: Vec<_> = client
+        .find_categories_page(&filter, &order, &page(None, None, true))
+        .await?
+        .map(|category| category.id)
+        .collect();
+    assert_eq!(found, [children[2].id, children[3].id]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn memory_search() -> Result<()> {
+    let client = MemoryClient::new();
+
+    let mut category = create_category_item(None);
+    category.name = "Electronics".into();
+    client.create_category(&category, None).await?;
+
+    assert_eq!(client.search("electro").await?.count(), 1);
+    assert_eq!(client.search("shoes").await?.count(), 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn memory_category_tree() -> Result<()> {
+    let client = MemoryClient::new();
+
+    let parent = client
+        .create_category(&create_category_item(None), None)
+        .await?;
+    let child = client
+        .create_category(&create_category_item(Some(parent.id)), None)
+        .await?;
+    let parent = client
+        .get_category_by_id(&parent.id)
+        .await?
+        .expect("parent to exist");
+
+    let tree: Vec<_> = client.get_category_tree(None, 5).await?.collect();
+    assert_eq!(tree.len(), 1);
+    assert_eq!(tree[0].category, parent);
+    assert_eq!(tree[0].children.len(), 1);
+    assert_eq!(tree[0].children[0].category, child);
+
+    let tree: Vec<_> = client
+        .get_category_tree(Some(&parent.id), 0)
+        .await?
+        .collect();
+    assert!(tree[0].children.is_empty());
+
+    assert_eq!(
+        client
+            .get_category_tree(Some(&Uuid::now_v7()), 1)
+            .await?
+            .len(),
+        0
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn memory_ancestors() -> Result<()> {
+    let client = MemoryClient::new();
+
+    let root = client
+        .create_category(&create_category_item(None), None)
+        .await?;
+    let parent = client
+        .create_category(&create_category_item(Some(root.id)), None)
+        .await?;
+    let child = client
+        .create_category(&create_category_item(Some(parent.id)), None)
+        .await?;
+
+    let ancestors: Vec<_> = client
+        .get_ancestors(&child.id)
+        .await?
+        .map(|category| category.id)
+        .collect();
+    assert_eq!(ancestors, vec![root.id, parent.id]);
+
+    assert_eq!(client.get_ancestors(&root.id).await?.len(), 0);
+
+    // cycles are rejected before they reach the ancestor walk
+    let mut cyclic = root.clone();
+    cyclic.parent_id = Some(child.id);
+    let res = client.update_category(&root.id, &cyclic, None, None).await;
+    assert!(matches!(res, Err(CoreError::CyclicHierarchy { .. })));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn memory_taxonomy_import() -> Result<()> {
+    use api_core::taxonomy::{self, Format};
+
+    let client = MemoryClient::new();
+    let apparel = client
+        .create_category(
+            &Category {
+                name: "Apparel".into(),
+                ..Default::default()
+            },
+            None,
+        )
+        .await?;
+
+    let categories = taxonomy::read(
+        Format::Google,
+        "Apparel > Shoes\nApparel > Shoes > Boots\nToys\n".as_bytes(),
+    )?;
+
+    let diff = taxonomy::import(&client, &categories, true, None).await?;
+    assert_eq!(diff.unchanged, [apparel.id]);
+    assert_eq!(diff.created.len(), 3);
+    assert_eq!(client.get_categories().await?.len(), 1);
+
+    let diff = taxonomy::import(&client, &categories, false, Some("importer")).await?;
+    assert_eq!(diff.created.len(), 3);
+    assert_eq!(client.get_categories().await?.len(), 4);
+    let boots = client
+        .get_category_by_id(&diff.created[1].id)
+        .await?
+        .expect("boots to exist");
+    assert_eq!(boots.name, "Boots");
+    assert_eq!(boots.parent_id, Some(diff.created[0].id));
+    assert_eq!(
+        sub_categories(&client, &apparel.id).await?,
+        [diff.created[0].id]
+    );
+
+    // importing again changes nothing
+    let diff = taxonomy::import(&client, &categories, false, None).await?;
+    assert!(diff.created.is_empty() && diff.updated.is_empty());
+    assert_eq!(diff.unchanged.len(), 4);
+
+    let mut buf = Vec::new();
+    taxonomy::export(&client, Format::Google, &mut buf).await?;
+    // top level categories come in the order they are stored
+    let exported = String::from_utf8(buf)?;
+    let mut lines: Vec<_> = exported.lines().collect();
+    lines.sort();
+    assert_eq!(
+        lines,
+        [
+            "Apparel",
+            "Apparel > Shoes",
+            "Apparel > Shoes > Boots",
+            "Toys"
+        ]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn memory_slugs() -> Result<()> {
+    let client = MemoryClient::new();
+
+    let named = |name: &str, parent_id: Option<Uuid>| Category {
+        name: name.into(),
+        parent_id,
+        ..Default::default()
+    };
+
+    let apparel = client
+        .create_category(&named("Apparel & Accessories", None), None)
+        .await?;
+    assert_eq!(apparel.slug, "apparel-accessories");
+    let shoes = client
+        .create_category(&named("Shoes", Some(apparel.id)), None)
+        .await?;
+    let toys = client.create_category(&named("Toys", None), None).await?;
+
+    // siblings may not share a slug, categories elsewhere in the tree may
+    let res = client
+        .create_category(&named("apparel  accessories", None), None)
+        .await;
+    assert!(matches!(
+        res,
+        Err(CoreError::DuplicateSlug { ref slug, parent_id: None }) if slug == "apparel-accessories"
+    ));
+    let toy_shoes = client
+        .create_category(&named("Shoes", Some(toys.id)), None)
+        .await?;
+    // a name needs something to make a slug from
+    let res = client.create_category(&named("!!!", None), None).await;
+    assert!(matches!(res, Err(CoreError::EmptySlug)));
+
+    let res = client
+        .update_category(&toys.id, &named("Apparel & Accessories", None), None, None)
+        .await;
+    assert!(matches!(res, Err(CoreError::DuplicateSlug { .. })));
+    let res = client
+        .patch_category(
+            &toys.id,
+            &CategoryPatch {
+                name: Some("Apparel Accessories".into()),
+                ..Default::default()
+            },
+            None,
+            None,
+        )
+        .await;
+    assert!(matches!(res, Err(CoreError::DuplicateSlug { .. })));
+    let res = client
+        .move_category(&toy_shoes.id, Some(&apparel.id), None, None, None)
+        .await;
+    assert!(
+        matches!(res, Err(CoreError::DuplicateSlug { parent_id, .. }) if parent_id == Some(apparel.id))
+    );
+
+    // renaming a category keeps its slug in step
+    let renamed = client
+        .patch_category(
+            &toy_shoes.id,
+            &CategoryPatch {
+                name: Some("Toy Shoes".into()),
+                ..Default::default()
+            },
+            None,
+            None,
+        )
+        .await?
+        .expect("category to exist");
+    assert_eq!(renamed.slug, "toy-shoes");
+
+    let found = client
+        .get_category_by_slug("apparel-accessories/shoes")
+        .await?
+        .expect("category to exist");
+    assert_eq!(found.id, shoes.id);
+    assert!(client
+        .get_category_by_slug("/toys/toy-shoes/")
+        .await?
+        .is_some());
+    assert!(client.get_category_by_slug("shoes").await?.is_none());
+    assert!(client.get_category_by_slug("").await?.is_none());
+
+    // a slug is free again once its category is in the trash
+    client
+        .delete_category(&shoes.id, DeleteStrategy::Restrict, None, None)
+        .await?;
+    assert!(client
+        .get_category_by_slug("apparel-accessories/shoes")
+        .await?
+        .is_none());
+    client
+        .create_category(&named("Shoes", Some(apparel.id)), None)
+        .await?;
+    let res = client.undelete_category(&shoes.id, None).await;
+    assert!(matches!(res, Err(CoreError::DuplicateSlug { .. })));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn memory_translations() -> Result<()> {
+    let client = MemoryClient::new();
+
+    let category = client
+        .create_category(
+            &Category {
+                name: "Shoes".into(),
+                translations: vec![Translation {
+                    locale: "fr".into(),
+                    name: "Chaussures".into(),
+                    description: None,
+                }],
+                ..Default::default()
+            },
+            None,
+        )
+        .await?;
+    assert_eq!(client.search("chauss").await?.count(), 1);
+
+    let patch = CategoryPatch {
+        description: Some("Footwear".into()),
+        translations: Some(vec![Translation {
+            locale: "de".into(),
+            name: "Schuhe".into(),
+            description: Some("Stiefel und Sandalen".into()),
+        }]),
+        ..Default::default()
+    };
+    let patched = client
+        .patch_category(&category.id, &patch, None, None)
+        .await?
+        .expect("category to exist");
+    assert_eq!(patched.name, "Shoes");
+    assert_eq!(patched.localised_name(&["de-AT"]), "Schuhe");
+    assert_eq!(patched.localised_description(&["fr"]), Some("Footwear"));
+
+    // translations are replaced as a whole
+    assert_eq!(client.search("chauss").await?.count(), 0);
+    assert_eq!(client.search("sandalen").await?.count(), 1);
+    assert_eq!(client.search("footwear").await?.count(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn memory_attributes() -> Result<()> {
+    let client = MemoryClient::new();
+
+    let brand = AttributeDefinition {
+        key: "brand".into(),
+        kind: AttributeKind::Text,
+        ..Default::default()
+    };
+    let invalid = AttributeDefinition {
+        key: "condition".into(),
+        kind: AttributeKind::Select,
+        ..Default::default()
+    };
+
+    let res = client
+        .create_category(
+            &Category {
+                attributes: vec![invalid.clone()],
+                ..create_category_item(None)
+            },
+            None,
+        )
+        .await;
+    assert!(matches!(res, Err(CoreError::InvalidAttribute { ref key, .. }) if key == "condition"));
+    assert_eq!(client.get_categories().await?.len(), 0);
+
+    let category = client
+        .create_category(
+            &Category {
+                attributes: vec![brand.clone()],
+                ..create_category_item(None)
+            },
+            None,
+        )
+        .await?;
+    assert_eq!(category.attributes, [brand]);
+
+    let res = client
+        .patch_category(
+            &category.id,
+            &CategoryPatch {
+                attributes: Some(vec![invalid]),
+                ..Default::default()
+            },
+            None,
+            None,
+        )
+        .await;
+    assert!(matches!(res, Err(CoreError::InvalidAttribute { .. })));
+
+    let patched = client
+        .patch_category(
+            &category.id,
+            &CategoryPatch {
+                attributes: Some(Vec::new()),
+                ..Default::default()
+            },
+            None,
+            None,
+        )
+        .await?
+        .expect("category to exist");
+    assert!(patched.attributes.is_empty());
+
+    Ok(())
+}
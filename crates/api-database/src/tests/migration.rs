@@ -0,0 +1,38 @@
+use surrealdb::sql::Thing;
+
+use crate::migration::{backfill_slugs, Backfill, SlugEntity};
+
+fn stored(id: &str, name: &str, slug: Option<&str>, parent: Option<&str>) -> SlugEntity {
+    SlugEntity {
+        id: Thing::from(("category", id)),
+        name: name.into(),
+        slug: slug.map(Into::into),
+        parent_id: parent.map(|parent| Thing::from(("category", parent))),
+        deleted_at: None,
+    }
+}
+
+#[test]
+fn backfill_slugs_once_per_sibling() {
+    let categories = [
+        stored("a", "Shoes", Some("shoes"), None),
+        stored("b", "shoes", None, None),
+        stored("c", "Café", Some("cafe"), None),
+        stored("d", "!!!", None, None),
+        stored("e", "Shoes", None, Some("a")),
+    ];
+
+    let slug = |id: &str, slug: &str| Backfill {
+        id: Thing::from(("category", id)),
+        slug: slug.into(),
+    };
+    assert_eq!(
+        backfill_slugs(&categories),
+        [
+            slug("b", "shoes-2"),
+            slug("c", "café"),
+            slug("d", "d"),
+            slug("e", "shoes")
+        ]
+    );
+}
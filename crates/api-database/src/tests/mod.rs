@@ -1,8 +1,10 @@
+mod memory;
+mod migration;
 mod mutation;
 mod query;
 mod redis;
 
-use crate::Client;
+use crate::{Client, DatabaseAuth};
 use anyhow::Result;
 
 async fn create_client(
@@ -31,8 +33,10 @@ async fn create_client(
 
     let client = Client::try_new(
         &db_host,
-        &username,
-        &password,
+        DatabaseAuth::Root {
+            username: &username,
+            password: &password,
+        },
         with_ns.unwrap_or(&db_namespace),
         &db_name,
         if with_redis {
@@ -1,18 +1,21 @@
 use super::create_client;
 use anyhow::Result;
 use api_core::{
-    api::{MutateCategories, QueryCategories},
+    api::{CoreError, MutateCategories, QueryCategories},
     reexports::uuid::Uuid,
-    Category,
+    Category, CategoryDeletion, CategoryPatch, CategoryUpdate, DeleteStrategy, RevisionKind,
 };
 
 fn create_category_item() -> Category {
+    let id = Uuid::now_v7();
     Category {
-        id: Uuid::now_v7(),
-        name: "TestCategoryInput".into(),
+        id,
+        // siblings may not share a name
+        name: format!("TestCategoryInput {id}"),
         sub_categories: vec![],
         image_url: None,
         parent_id: None,
+        ..Default::default()
     }
 }
 
@@ -31,30 +34,64 @@ async fn create_category() -> Result<()> {
     let base_count = all_categories.count();
     let category = create_category_item();
 
-    let input = client.create_category(&category).await?;
+    let input = client.create_category(&category, None).await?;
 
     let updated_categories = client.get_categories().await?;
 
     assert_eq!(base_count + 1, updated_categories.count());
     check_similarities(&input, &category);
 
-    client.delete_category(&input.id).await?;
+    client
+        .delete_category(&input.id, DeleteStrategy::Restrict, None, None)
+        .await?;
     Ok(())
 }
 
+#[cfg(feature = "kv-mem")]
+#[tokio::test]
+async fn embedded_engine() -> Result<()> {
+    use crate::{Client, DatabaseAuth};
+
+    let client = Client::try_new("mem://", DatabaseAuth::None, "test", "test", None, None).await?;
+
+    let input = client
+        .create_category(&create_category_item(), None)
+        .await?;
+    assert_eq!(client.get_category_by_id(&input.id).await?, Some(input));
+
+    Ok(())
+}
+
+#[cfg(not(feature = "kv-mem"))]
+#[tokio::test]
+async fn embedded_engine_not_compiled() {
+    use crate::{Client, ClientError, DatabaseAuth};
+
+    let res = Client::try_new("mem://", DatabaseAuth::None, "test", "test", None, None).await;
+    assert!(matches!(
+        res,
+        Err(ClientError::UnsupportedEngine {
+            feature: "kv-mem",
+            ..
+        })
+    ));
+}
+
 #[tokio::test]
 async fn create_get_by_id() -> Result<()> {
     let category = create_category_item();
 
     let client = create_client(Some("test-mutation-update"), false, false).await?;
 
-    let input = client.create_category(&category).await?;
+    let input = client.create_category(&category, None).await?;
     let id = input.id;
 
     let get_by_id = client.get_category_by_id(&input.id).await?;
     assert_eq!(get_by_id, Some(input));
 
-    client.delete_category(&id).await?;
+    client
+        .delete_category(&id, DeleteStrategy::Restrict, None, None)
+        .await?;
 
     Ok(())
 }
@@ -65,7 +102,7 @@ async fn update_category() -> Result<()> {
 
     let client = create_client(Some("test-mutation-update"), false, false).await?;
 
-    let input = client.create_category(&category).await?;
+    let input = client.create_category(&category, None).await?;
 
     let mut update = input.clone();
     update.name = "FooBar".to_string();
@@ -73,14 +110,49 @@ async fn update_category() -> Result<()> {
 
     // This ID does exist
     let update_res = client
-        .update_category(&input.id, &update)
+        .update_category(&input.id, &update, None, None)
         .await?
         .expect("category to exist in db");
 
     assert_eq!(&update_res.id, &input.id);
     check_similarities(&update, &update_res);
 
-    client.delete_category(&input.id).await?;
+    client
+        .delete_category(&input.id, DeleteStrategy::Restrict, None, None)
+        .await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn patch_category() -> Result<()> {
+    let mut category = create_category_item();
+    category.image_url = Some("https://example.com/image.png".into());
+
+    let client = create_client(Some("test-mutation-patch"), false, false).await?;
+
+    let input = client.create_category(&category, None).await?;
+
+    let patch = CategoryPatch {
+        name: Some("FooBar".into()),
+        ..Default::default()
+    };
+    let patched = client
+        .patch_category(&input.id, &patch, None, None)
+        .await?
+        .expect("category to exist in db");
+
+    assert_eq!(patched.name, "FooBar");
+    assert_eq!(patched.image_url, input.image_url);
+
+    assert!(client
+        .patch_category(&Uuid::now_v7(), &patch, None, None)
+        .await?
+        .is_none());
+
+    client
+        .delete_category(&input.id, DeleteStrategy::Restrict, None, None)
+        .await?;
 
     Ok(())
 }
@@ -94,18 +166,235 @@ async fn delete_category() -> Result<()> {
 
     let base_count = all_categories.count();
 
-    let input = client.create_category(&category).await?;
+    let input = client.create_category(&category, None).await?;
     // delete and check count
     let deleted_category = client
-        .delete_category(&input.id)
+        .delete_category(&input.id, DeleteStrategy::Restrict, None, None)
         .await?
         .expect("category to be deleted");
 
-    assert_eq!(input, deleted_category);
+    assert_eq!(input.id, deleted_category.category.id);
+    assert!(deleted_category.category.deleted_at.is_some());
+    assert!(client.get_category_by_id(&input.id).await?.is_none());
 
     let final_count = client.get_categories().await?.count();
     assert_eq!(base_count, final_count);
 
-    client.delete_category(&input.id).await?;
+    client.purge_category(&input.id).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn restore_category() -> Result<()> {
+    let client = create_client(Some("test-mutation-restore"), false, false).await?;
+
+    let input = client
+        .create_category(&create_category_item(), None)
+        .await?;
+    client
+        .delete_category(&input.id, DeleteStrategy::Restrict, None, None)
+        .await?;
+
+    let kinds: Vec<_> = client
+        .get_category_history(&input.id)
+        .await?
+        .map(|revision| (revision.revision, revision.kind))
+        .collect();
+    assert_eq!(
+        kinds,
+        [(1, RevisionKind::Created), (2, RevisionKind::Deleted)]
+    );
+
+    let restored = client
+        .restore_category(&input.id, 1, None)
+        .await?
+        .expect("revision to exist");
+    check_similarities(&input, &restored);
+    assert_eq!(restored.version, 3);
+
+    client
+        .delete_category(&input.id, DeleteStrategy::Restrict, None, None)
+        .await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn undelete_and_purge_category() -> Result<()> {
+    let client = create_client(Some("test-mutation-trash"), false, false).await?;
+
+    let parent = client
+        .create_category(&create_category_item(), None)
+        .await?;
+    let child = client
+        .create_category(
+            &Category {
+                parent_id: Some(parent.id),
+                ..create_category_item()
+            },
+            None,
+        )
+        .await?;
+
+    client
+        .delete_category(&parent.id, DeleteStrategy::Cascade, None, None)
+        .await?;
+    let trash: Vec<_> = client
+        .get_deleted_categories()
+        .await?
+        .map(|category| category.id)
+        .collect();
+    assert!(trash.contains(&parent.id) && trash.contains(&child.id));
+
+    let restored = client
+        .undelete_category(&parent.id, None)
+        .await?
+        .expect("category to be in the trash");
+    assert_eq!(restored.category.sub_categories, vec![child.id]);
+    assert_eq!(restored.restored, vec![child.id]);
+    assert!(client.get_category_by_id(&child.id).await?.is_some());
+
+    assert!(client.purge_category(&parent.id).await.is_err());
+    client
+        .delete_category(&parent.id, DeleteStrategy::Cascade, None, None)
+        .await?;
+    let purged = client
+        .purge_category(&parent.id)
+        .await?
+        .expect("category to be purged");
+    assert_eq!(purged.deleted, vec![child.id]);
+    assert!(client
+        .get_deleted_categories()
+        .await?
+        .all(|category| category.id != parent.id && category.id != child.id));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn category_stats() -> Result<()> {
+    let client = create_client(Some("test-mutation-stats"), false, false).await?;
+
+    let root = client
+        .create_category(&create_category_item(), None)
+        .await?;
+    let mut children = Vec::new();
+    for parent in [root.id, root.id] {
+        let child = client
+            .create_category(
+                &Category {
+                    parent_id: Some(parent),
+                    ..create_category_item()
+                },
+                None,
+            )
+            .await?;
+        children.push(child);
+    }
+    let grandchild = client
+        .create_category(
+            &Category {
+                parent_id: Some(children[0].id),
+                ..create_category_item()
+            },
+            None,
+        )
+        .await?;
+
+    assert_eq!(client.adjust_listing_count(&root.id, 2).await?, Some(2));
+    assert_eq!(
+        client.adjust_listing_count(&grandchild.id, 5).await?,
+        Some(5)
+    );
+    assert_eq!(
+        client.adjust_listing_count(&children[1].id, 7).await?,
+        Some(7)
+    );
+    client
+        .delete_category(&children[1].id, DeleteStrategy::Restrict, None, None)
+        .await?;
+    assert_eq!(client.adjust_listing_count(&children[1].id, 1).await?, None);
+
+    // the grandchild's parent is read as an ancestor before the root's subtree is walked
+    let stats: Vec<_> = client
+        .get_category_stats(&[grandchild.id, root.id])
+        .await?
+        .collect();
+    assert_eq!(stats.len(), 2);
+    assert_eq!((stats[0].depth, stats[0].total_listing_count), (2, 5));
+    assert_eq!(
+        (
+            stats[1].child_count,
+            stats[1].descendant_count,
+            stats[1].total_listing_count
+        ),
+        (1, 2, 7)
+    );
+
+    client
+        .delete_category(&root.id, DeleteStrategy::Cascade, None, None)
+        .await?;
+    for id in [root.id, children[1].id] {
+        client.purge_category(&id).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn batch_mutations() -> Result<()> {
+    let client = create_client(Some("test-mutation-batch"), false, false).await?;
+
+    let parent = create_category_item();
+    let child = Category {
+        parent_id: Some(parent.id),
+        ..create_category_item()
+    };
+    let created = client
+        .create_categories(&[child.clone(), parent.clone()], None)
+        .await?;
+    assert_eq!(created[0].id, child.id);
+    assert_eq!(created[1].sub_categories, vec![child.id]);
+    assert_eq!(client.get_category_history(&child.id).await?.len(), 1);
+
+    let res = client
+        .create_categories(&[create_category_item(), parent.clone()], None)
+        .await;
+    assert!(matches!(res, Err(CoreError::AlreadyExists(id)) if id == parent.id));
+
+    let updates = [CategoryUpdate {
+        id: child.id,
+        category: Category {
+            name: "Renamed".into(),
+            ..create_category_item()
+        },
+        expected_version: Some(1),
+    }];
+    let updated = client.update_categories(&updates, None).await?;
+    let updated = updated[0].clone().expect("category to exist");
+    assert_eq!(updated.name, "Renamed");
+    assert_eq!(updated.parent_id, None);
+    let parent = client
+        .get_category_by_id(&parent.id)
+        .await?
+        .expect("category to exist");
+    assert!(parent.sub_categories.is_empty());
+
+    let deleted = client
+        .delete_categories(
+            &[parent.id, child.id].map(|id| CategoryDeletion {
+                id,
+                expected_version: None,
+            }),
+            DeleteStrategy::Restrict,
+            None,
+        )
+        .await?;
+    assert!(deleted.iter().all(|deleted| deleted
+        .as_ref()
+        .is_some_and(|deleted| deleted.category.deleted_at.is_some())));
+    for id in [parent.id, child.id] {
+        client.purge_category(&id).await?;
+    }
+
     Ok(())
 }
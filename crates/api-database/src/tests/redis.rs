@@ -1,7 +1,7 @@
 use anyhow::Result;
 
 use crate::redis::{
-    redis_query::{query, update},
+    redis_query::{generation, invalidate, query, update},
     PoolLike, PooledConnectionLike, RedisPool,
 };
 
@@ -132,3 +132,65 @@ async fn redis_encode_error() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn redis_invalidate_drops_trees() -> Result<()> {
+    let pool = client().await;
+    let root = api_core::reexports::uuid::Uuid::now_v7();
+    let tree_key = |generation| crate::redis::cache_keys::CacheKey::CategoryTree {
+        root: Some(&root),
+        max_depth: 2,
+        generation,
+    };
+
+    let before = generation(&pool).await.expect("redis test");
+    update(tree_key(before), &pool, vec![root.to_string()], Some(1000))
+        .await
+        .expect("redis test");
+    assert!(query::<Vec<String>>(tree_key(before), &pool)
+        .await
+        .is_some());
+
+    invalidate(&[crate::redis::cache_keys::CacheKey::AllCategories], &pool).await;
+    let after = generation(&pool).await.expect("redis test");
+    assert!(after > before);
+    assert!(query::<Vec<String>>(tree_key(after), &pool).await.is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn redis_invalidate_drops_listings() -> Result<()> {
+    let pool = client().await;
+    let filter = api_core::CategoryFilter {
+        name_contains: Some(api_core::reexports::uuid::Uuid::now_v7().to_string()),
+        ..Default::default()
+    };
+    let found_key = |generation| crate::redis::cache_keys::CacheKey::FoundCategories {
+        filter: &filter,
+        order: None,
+        generation,
+    };
+
+    let before = generation(&pool).await.expect("redis test");
+    update(
+        found_key(before),
+        &pool,
+        vec!["listed".to_string()],
+        Some(1000),
+    )
+    .await
+    .expect("redis test");
+    assert!(query::<Vec<String>>(found_key(before), &pool)
+        .await
+        .is_some());
+
+    invalidate(&[crate::redis::cache_keys::CacheKey::AllCategories], &pool).await;
+    let after = generation(&pool).await.expect("redis test");
+    assert!(after > before);
+    assert!(query::<Vec<String>>(found_key(after), &pool)
+        .await
+        .is_none());
+
+    Ok(())
+}
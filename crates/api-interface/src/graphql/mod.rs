@@ -2,13 +2,24 @@ pub(crate) mod mutation;
 pub(crate) mod query;
 pub(crate) mod subscription;
 
-use api_database::Client;
+use std::sync::Arc;
+
 use async_graphql::Context;
 use tracing::error;
 
-pub(crate) fn extract_db<'a>(context: &'a Context) -> async_graphql::Result<&'a Client> {
-    context.data::<Client>().map_err(|db| {
+pub(crate) fn extract_db<'a, D: Send + Sync + 'static>(
+    context: &'a Context,
+) -> async_graphql::Result<&'a D> {
+    context.data::<Arc<D>>().map(Arc::as_ref).map_err(|db| {
         error!("{}", db.message);
         "Internal database error".into()
     })
 }
+
+/// Identity of whoever sends the request, recorded in `createdBy`/`updatedBy`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Caller(pub String);
+
+pub(crate) fn extract_caller<'a>(context: &'a Context) -> Option<&'a str> {
+    context.data_opt::<Caller>().map(|caller| caller.0.as_str())
+}
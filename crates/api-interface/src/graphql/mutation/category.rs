@@ -1,27 +1,45 @@
+use std::marker::PhantomData;
+
 use api_core::{
     api::{MutateCategories, Uuid},
-    Category,
+    Category, CategoryDeletion, CategoryPatch, CategoryUpdate, DeleteStrategy, RestoredCategory,
 };
-use api_database::Client;
-use async_graphql::{Context, Object};
+use async_graphql::{Context, InputObject, Object};
 use tracing::instrument;
 
-use crate::graphql::subscription::{broker::SimpleBroker, CategoryChanged};
+use crate::graphql::{
+    extract_caller, extract_db,
+    subscription::{broker::SimpleBroker, CategoryChanged},
+};
+
+pub struct CategoryMutation<D>(PhantomData<D>);
 
-#[derive(Default, Debug)]
-pub struct CategoryMutation;
+/// A category created as part of a batch
+#[derive(InputObject)]
+struct BatchCategoryInput {
+    /// ID of the new category, generated when left out
+    id: Option<Uuid>,
+    #[graphql(flatten)]
+    category: Category,
+}
+
+impl<D> Default for CategoryMutation<D> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
 
 #[Object]
-impl CategoryMutation {
-    #[instrument(skip(ctx), err(Debug))]
+impl<D: MutateCategories + Send + Sync + 'static> CategoryMutation<D> {
+    #[instrument(skip(self, ctx), err(Debug))]
     async fn create_category(
         &self,
         ctx: &Context<'_>,
         input: Category,
     ) -> async_graphql::Result<Category> {
-        let database = ctx.data::<Client>()?;
+        let database = extract_db::<D>(ctx)?;
 
-        match database.create_category(&input).await {
+        match database.create_category(&input, extract_caller(ctx)).await {
             Ok(category) => {
                 SimpleBroker::publish(CategoryChanged {
                     mutation_type: super::MutationType::Created,
@@ -34,16 +52,21 @@ impl CategoryMutation {
         }
     }
 
-    #[instrument(skip(ctx), err(Debug))]
+    /// Replaces a category, unless it changed since `expectedVersion`
+    #[instrument(skip(self, ctx), err(Debug))]
     async fn update_category(
         &self,
         ctx: &Context<'_>,
         id: Uuid,
         input: Category,
+        expected_version: Option<u64>,
     ) -> async_graphql::Result<Option<Category>> {
-        let database = ctx.data::<Client>()?;
+        let database = extract_db::<D>(ctx)?;
 
-        match database.update_category(&id, &input).await {
+        match database
+            .update_category(&id, &input, expected_version, extract_caller(ctx))
+            .await
+        {
             Ok(category) => {
                 SimpleBroker::publish(CategoryChanged {
                     mutation_type: super::MutationType::Updated,
@@ -55,18 +78,57 @@ impl CategoryMutation {
         }
     }
 
-    #[instrument(skip(ctx), err(Debug))]
-    async fn delete_category(
+    /// Updates only the fields present in `input`, unless it changed since `expectedVersion`
+    #[instrument(skip(self, ctx), err(Debug))]
+    async fn patch_category(
+        &self,
+        ctx: &Context<'_>,
+        id: Uuid,
+        input: CategoryPatch,
+        expected_version: Option<u64>,
+    ) -> async_graphql::Result<Option<Category>> {
+        let database = extract_db::<D>(ctx)?;
+
+        match database
+            .patch_category(&id, &input, expected_version, extract_caller(ctx))
+            .await
+        {
+            Ok(category) => {
+                SimpleBroker::publish(CategoryChanged {
+                    mutation_type: super::MutationType::Updated,
+                    id,
+                });
+                Ok(category)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Moves a category and its subtree under `newParentId`, at `position` among its siblings
+    #[instrument(skip(self, ctx), err(Debug))]
+    async fn move_category(
         &self,
         ctx: &Context<'_>,
         id: Uuid,
+        new_parent_id: Option<Uuid>,
+        position: Option<usize>,
+        expected_version: Option<u64>,
     ) -> async_graphql::Result<Option<Category>> {
-        let database = ctx.data::<Client>()?;
+        let database = extract_db::<D>(ctx)?;
 
-        match database.delete_category(&id).await {
+        match database
+            .move_category(
+                &id,
+                new_parent_id.as_ref(),
+                position,
+                expected_version,
+                extract_caller(ctx),
+            )
+            .await
+        {
             Ok(category) => {
                 SimpleBroker::publish(CategoryChanged {
-                    mutation_type: super::MutationType::Deleted,
+                    mutation_type: super::MutationType::Updated,
                     id,
                 });
                 Ok(category)
@@ -74,4 +136,240 @@ impl CategoryMutation {
             Err(e) => Err(e.into()),
         }
     }
+
+    /// Puts the subcategories of `parentId` in the order of `orderedIds`
+    #[instrument(skip(self, ctx), err(Debug))]
+    async fn reorder_sub_categories(
+        &self,
+        ctx: &Context<'_>,
+        parent_id: Option<Uuid>,
+        ordered_ids: Vec<Uuid>,
+    ) -> async_graphql::Result<Option<Vec<Category>>> {
+        let database = extract_db::<D>(ctx)?;
+
+        match database
+            .reorder_sub_categories(parent_id.as_ref(), &ordered_ids, extract_caller(ctx))
+            .await
+        {
+            Ok(categories) => {
+                for category in categories.iter().flatten() {
+                    SimpleBroker::publish(CategoryChanged {
+                        mutation_type: super::MutationType::Updated,
+                        id: category.id,
+                    });
+                }
+                Ok(categories)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Adds `delta` to the listings filed under a category, returning the new count
+    #[instrument(skip(self, ctx), err(Debug))]
+    async fn adjust_listing_count(
+        &self,
+        ctx: &Context<'_>,
+        id: Uuid,
+        delta: i64,
+    ) -> async_graphql::Result<Option<u64>> {
+        let database = extract_db::<D>(ctx)?;
+
+        Ok(database.adjust_listing_count(&id, delta).await?)
+    }
+
+    /// Moves a category to the trash, handling its subcategories according to `strategy`
+    #[instrument(skip(self, ctx), err(Debug))]
+    async fn delete_category(
+        &self,
+        ctx: &Context<'_>,
+        id: Uuid,
+        #[graphql(default)] strategy: DeleteStrategy,
+        expected_version: Option<u64>,
+    ) -> async_graphql::Result<Option<Category>> {
+        let database = extract_db::<D>(ctx)?;
+
+        match database
+            .delete_category(&id, strategy, expected_version, extract_caller(ctx))
+            .await
+        {
+            Ok(Some(deleted)) => {
+                for id in std::iter::once(deleted.category.id).chain(deleted.deleted) {
+                    SimpleBroker::publish(CategoryChanged {
+                        mutation_type: super::MutationType::Deleted,
+                        id,
+                    });
+                }
+                for id in deleted.reparented {
+                    SimpleBroker::publish(CategoryChanged {
+                        mutation_type: super::MutationType::Updated,
+                        id,
+                    });
+                }
+                Ok(Some(deleted.category))
+            }
+            Ok(None) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Takes a category out of the trash, or back to its content at `revision` when set
+    #[instrument(skip(self, ctx), err(Debug))]
+    async fn restore_category(
+        &self,
+        ctx: &Context<'_>,
+        id: Uuid,
+        revision: Option<u64>,
+    ) -> async_graphql::Result<Option<Category>> {
+        let database = extract_db::<D>(ctx)?;
+
+        let restored = match revision {
+            Some(revision) => database
+                .restore_category(&id, revision, extract_caller(ctx))
+                .await
+                .map(|category| {
+                    category.map(|category| RestoredCategory {
+                        category,
+                        restored: Vec::new(),
+                    })
+                }),
+            None => database.undelete_category(&id, extract_caller(ctx)).await,
+        };
+
+        match restored {
+            Ok(Some(restored)) => {
+                for id in std::iter::once(restored.category.id).chain(restored.restored) {
+                    SimpleBroker::publish(CategoryChanged {
+                        mutation_type: super::MutationType::Updated,
+                        id,
+                    });
+                }
+                Ok(Some(restored.category))
+            }
+            Ok(None) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Permanently removes a category from the trash along with its subcategories
+    #[instrument(skip(self, ctx), err(Debug))]
+    async fn purge_category(
+        &self,
+        ctx: &Context<'_>,
+        id: Uuid,
+    ) -> async_graphql::Result<Option<Category>> {
+        let database = extract_db::<D>(ctx)?;
+
+        match database.purge_category(&id).await {
+            Ok(Some(purged)) => {
+                for id in std::iter::once(purged.category.id).chain(purged.deleted) {
+                    SimpleBroker::publish(CategoryChanged {
+                        mutation_type: super::MutationType::Deleted,
+                        id,
+                    });
+                }
+                Ok(Some(purged.category))
+            }
+            Ok(None) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Creates every category in one go, or none when one is rejected
+    #[instrument(skip(self, ctx, input), err(Debug))]
+    async fn create_categories(
+        &self,
+        ctx: &Context<'_>,
+        input: Vec<BatchCategoryInput>,
+    ) -> async_graphql::Result<Vec<Category>> {
+        let database = extract_db::<D>(ctx)?;
+
+        let categories: Vec<_> = input
+            .into_iter()
+            .map(|input| Category {
+                id: input.id.unwrap_or_default(),
+                ..input.category
+            })
+            .collect();
+
+        match database
+            .create_categories(&categories, extract_caller(ctx))
+            .await
+        {
+            Ok(categories) => {
+                for category in categories.iter() {
+                    SimpleBroker::publish(CategoryChanged {
+                        mutation_type: super::MutationType::Created,
+                        id: category.id,
+                    });
+                }
+
+                Ok(categories)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Replaces every category in `input` in one go, or none when one fails
+    #[instrument(skip(self, ctx, input), err(Debug))]
+    async fn update_categories(
+        &self,
+        ctx: &Context<'_>,
+        input: Vec<CategoryUpdate>,
+    ) -> async_graphql::Result<Vec<Option<Category>>> {
+        let database = extract_db::<D>(ctx)?;
+
+        match database
+            .update_categories(&input, extract_caller(ctx))
+            .await
+        {
+            Ok(categories) => {
+                for category in categories.iter().flatten() {
+                    SimpleBroker::publish(CategoryChanged {
+                        mutation_type: super::MutationType::Updated,
+                        id: category.id,
+                    });
+                }
+                Ok(categories)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Moves every category in `input` to the trash in one go, or none when one fails
+    #[instrument(skip(self, ctx, input), err(Debug))]
+    async fn delete_categories(
+        &self,
+        ctx: &Context<'_>,
+        input: Vec<CategoryDeletion>,
+        #[graphql(default)] strategy: DeleteStrategy,
+    ) -> async_graphql::Result<Vec<Option<Category>>> {
+        let database = extract_db::<D>(ctx)?;
+
+        match database
+            .delete_categories(&input, strategy, extract_caller(ctx))
+            .await
+        {
+            Ok(deleted) => Ok(deleted
+                .into_iter()
+                .map(|deleted| {
+                    deleted.map(|deleted| {
+                        for id in std::iter::once(deleted.category.id).chain(deleted.deleted) {
+                            SimpleBroker::publish(CategoryChanged {
+                                mutation_type: super::MutationType::Deleted,
+                                id,
+                            });
+                        }
+                        for id in deleted.reparented {
+                            SimpleBroker::publish(CategoryChanged {
+                                mutation_type: super::MutationType::Updated,
+                                id,
+                            });
+                        }
+                        deleted.category
+                    })
+                })
+                .collect()),
+            Err(e) => Err(e.into()),
+        }
+    }
 }
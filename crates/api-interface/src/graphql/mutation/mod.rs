@@ -1,11 +1,18 @@
 use std::fmt::Display;
 
+use api_core::api::MutateCategories;
 use async_graphql::Enum;
 
 pub(crate) mod category;
 
-#[derive(async_graphql::MergedObject, Default)]
-pub struct Mutation(category::CategoryMutation);
+#[derive(async_graphql::MergedObject)]
+pub struct Mutation<D: MutateCategories + Send + Sync + 'static>(category::CategoryMutation<D>);
+
+impl<D: MutateCategories + Send + Sync + 'static> Default for Mutation<D> {
+    fn default() -> Self {
+        Self(category::CategoryMutation::default())
+    }
+}
 
 #[derive(Enum, Eq, PartialEq, Copy, Clone, Debug)]
 pub(crate) enum MutationType {
@@ -1,13 +1,31 @@
-use api_core::{api::QueryCategories, reexports::uuid::Uuid, Category};
+use std::marker::PhantomData;
+
+use api_core::{
+    api::QueryCategories,
+    reexports::{
+        chrono::{DateTime, Utc},
+        uuid::Uuid,
+    },
+    AttributeDefinition, Category, CategoryFilter, CategoryOrder, CategoryOrderField,
+    CategoryRevision, CategoryTree, OrderDirection,
+};
 use async_graphql::{Context, Object, SimpleObject};
 use tracing::instrument;
 
 use crate::graphql::{extract_db, query::Params};
 
-use super::{pagination::paginate, ConnectionResult};
+use super::{
+    pagination::{paginate, paginate_keyset, CursorMode},
+    ConnectionResult,
+};
+
+pub struct CategoryQuery<D>(PhantomData<D>);
 
-#[derive(Default, Debug)]
-pub struct CategoryQuery;
+impl<D> Default for CategoryQuery<D> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
 
 #[derive(SimpleObject)]
 pub struct SearchResult {
@@ -16,8 +34,10 @@ pub struct SearchResult {
 }
 
 #[Object]
-impl CategoryQuery {
-    #[instrument(skip(ctx), err(Debug))]
+impl<D: QueryCategories + Send + Sync + 'static> CategoryQuery<D> {
+    /// Lists the categories matching `filter`
+    #[allow(clippy::too_many_arguments)]
+    #[instrument(skip(self, ctx), err(Debug))]
     async fn categories(
         &self,
         ctx: &Context<'_>,
@@ -25,17 +45,44 @@ impl CategoryQuery {
         #[graphql(validator(min_length = 1, max_length = 100))] before: Option<String>,
         #[graphql(validator(minimum = 1, maximum = 100))] first: Option<i32>,
         #[graphql(validator(minimum = 1, maximum = 100))] last: Option<i32>,
+        filter: Option<CategoryFilter>,
+        order_by: Option<CategoryOrder>,
+        #[graphql(default)] include_deleted: bool,
+        #[graphql(default)] cursor_mode: CursorMode,
     ) -> ConnectionResult<Category> {
         let p = Params::new(after, before, first, last)?;
 
-        let database = extract_db(ctx)?;
+        let database = extract_db::<D>(ctx)?;
+
+        let filter = CategoryFilter {
+            include_deleted,
+            ..filter.unwrap_or_default()
+        };
+
+        if cursor_mode == CursorMode::Keyset {
+            // keys need a total order, which the tree order listings default to is not
+            let order = order_by.unwrap_or(CategoryOrder {
+                field: CategoryOrderField::CreatedAt,
+                direction: OrderDirection::Asc,
+            });
+            let total_count = database.count_categories(&filter).await?;
 
-        let categories = database.get_categories().await?;
+            let (filter, order_ref) = (&filter, &order);
+            return paginate_keyset(p, 100, order, total_count, |page| async move {
+                let categories = database
+                    .find_categories_page(filter, order_ref, &page)
+                    .await?;
+                Ok(categories.collect())
+            })
+            .await;
+        }
+        let categories = database.find_categories(&filter, order_by.as_ref()).await?;
 
         paginate(categories, p, 100).await
     }
 
-    #[instrument(skip(ctx), err(Debug))]
+    #[allow(clippy::too_many_arguments)]
+    #[instrument(skip(self, ctx), err(Debug))]
     async fn sub_categories(
         &self,
         ctx: &Context<'_>,
@@ -44,28 +91,133 @@ impl CategoryQuery {
         #[graphql(validator(min_length = 1, max_length = 100))] before: Option<String>,
         #[graphql(validator(minimum = 1, maximum = 100))] first: Option<i32>,
         #[graphql(validator(minimum = 1, maximum = 100))] last: Option<i32>,
+        #[graphql(default)] include_deleted: bool,
     ) -> ConnectionResult<Category> {
         let p = Params::new(after, before, first, last)?;
 
-        let database = extract_db(ctx)?;
+        let database = extract_db::<D>(ctx)?;
 
-        let categories = database.get_sub_categories(parent_id.as_ref()).await?;
+        let mut categories: Vec<_> = database
+            .get_sub_categories(parent_id.as_ref())
+            .await?
+            .collect();
+        if include_deleted {
+            categories.extend(
+                database
+                    .get_deleted_categories()
+                    .await?
+                    .filter(|category| category.parent_id == parent_id),
+            );
+        }
 
-        paginate(categories, p, 100).await
+        paginate(categories.into_iter(), p, 100).await
     }
 
-    #[instrument(skip(ctx), err(Debug))]
+    #[instrument(skip(self, ctx), err(Debug))]
     async fn category_by_id(
         &self,
         ctx: &Context<'_>,
         id: Uuid,
+        #[graphql(default)] include_deleted: bool,
+    ) -> async_graphql::Result<Option<Category>> {
+        let database = extract_db::<D>(ctx)?;
+
+        let category = database.get_category_by_id(&id).await?;
+        if category.is_none() && include_deleted {
+            let mut deleted = database.get_deleted_categories().await?;
+            return Ok(deleted.find(|category| category.id == id));
+        }
+
+        Ok(category)
+    }
+
+    /// Finds a category by the slugs on its path from the top level, as in `apparel/shoes`
+    #[instrument(skip(self, ctx), err(Debug))]
+    async fn category_by_slug(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(validator(min_length = 1, max_length = 1000))] path: String,
+    ) -> async_graphql::Result<Option<Category>> {
+        let database = extract_db::<D>(ctx)?;
+
+        let category = database.get_category_by_slug(&path).await?;
+
+        Ok(category)
+    }
+
+    #[instrument(skip(self, ctx), err(Debug))]
+    async fn ancestors(&self, ctx: &Context<'_>, id: Uuid) -> async_graphql::Result<Vec<Category>> {
+        let database = extract_db::<D>(ctx)?;
+
+        let ancestors = database.get_ancestors(&id).await?;
+
+        Ok(ancestors.collect())
+    }
+
+    /// Attributes listings in the category fill in, inherited ones included
+    #[instrument(skip(self, ctx), err(Debug))]
+    async fn effective_attributes(
+        &self,
+        ctx: &Context<'_>,
+        id: Uuid,
+    ) -> async_graphql::Result<Option<Vec<AttributeDefinition>>> {
+        let database = extract_db::<D>(ctx)?;
+
+        let Some(category) = database.get_category_by_id(&id).await? else {
+            return Ok(None);
+        };
+        let ancestors: Vec<_> = database.get_ancestors(&id).await?.collect();
+
+        Ok(Some(category.effective_attributes(&ancestors)))
+    }
+
+    #[instrument(skip(self, ctx), err(Debug))]
+    async fn category_tree(
+        &self,
+        ctx: &Context<'_>,
+        root_id: Option<Uuid>,
+        #[graphql(default = 3, validator(maximum = 10))] max_depth: u8,
+    ) -> async_graphql::Result<Vec<CategoryTree>> {
+        let database = extract_db::<D>(ctx)?;
+
+        let tree = database
+            .get_category_tree(root_id.as_ref(), usize::from(max_depth))
+            .await?;
+
+        Ok(tree.collect())
+    }
+
+    /// Every recorded change to a category, oldest first
+    #[instrument(skip(self, ctx), err(Debug))]
+    async fn category_history(
+        &self,
+        ctx: &Context<'_>,
+        id: Uuid,
+    ) -> async_graphql::Result<Vec<CategoryRevision>> {
+        let database = extract_db::<D>(ctx)?;
+
+        let history = database.get_category_history(&id).await?;
+
+        Ok(history.collect())
+    }
+
+    /// The category as it was at `timestamp`, nothing if it did not exist then
+    #[instrument(skip(self, ctx), err(Debug))]
+    async fn category_at(
+        &self,
+        ctx: &Context<'_>,
+        id: Uuid,
+        timestamp: DateTime<Utc>,
     ) -> async_graphql::Result<Option<Category>> {
-        let database = extract_db(ctx)?;
+        let database = extract_db::<D>(ctx)?;
 
-        database.get_category_by_id(&id).await.map_err(|e| e.into())
+        database
+            .get_category_at(&id, &timestamp)
+            .await
+            .map_err(|e| e.into())
     }
 
-    #[instrument(skip(ctx), err(Debug))]
+    #[instrument(skip(self, ctx), err(Debug))]
     async fn search(
         &self,
         ctx: &Context<'_>,
@@ -77,14 +229,14 @@ impl CategoryQuery {
     ) -> ConnectionResult<Category> {
         let p = Params::new(after, before, first, last)?;
 
-        let database = extract_db(ctx)?;
+        let database = extract_db::<D>(ctx)?;
 
         let categories = database.search(&query).await?;
 
         paginate(categories, p, 100).await
     }
 
-    #[instrument(skip(ctx), err(Debug))]
+    #[instrument(skip(self, ctx), err(Debug))]
     async fn search_with_parent_name(
         &self,
         ctx: &Context<'_>,
@@ -96,13 +248,34 @@ impl CategoryQuery {
     ) -> ConnectionResult<SearchResult> {
         let p = Params::new(after, before, first, last)?;
 
-        let database = extract_db(ctx)?;
-        let res = database.search_with_parent_name(&query).await?;
-        let mapped = res.into_iter().map(|(category, parent_name)| SearchResult {
-            category,
-            parent_name,
-        });
+        let database = extract_db::<D>(ctx)?;
+        let categories: Vec<_> = database.search(&query).await?.collect();
+
+        let parents = futures_util::future::try_join_all(
+            categories
+                .iter()
+                .filter_map(|category| category.parent_id.as_ref())
+                .map(|parent_id| database.get_category_by_id(parent_id)),
+        )
+        .await?;
+
+        let mapped: Vec<_> = categories
+            .into_iter()
+            .map(|category| {
+                let parent_name = category.parent_id.and_then(|parent_id| {
+                    parents
+                        .iter()
+                        .flatten()
+                        .find(|parent| parent.id == parent_id)
+                        .map(|parent| parent.name.to_owned())
+                });
+                SearchResult {
+                    category,
+                    parent_name,
+                }
+            })
+            .collect();
 
-        paginate(mapped, p, 100).await
+        paginate(mapped.into_iter(), p, 100).await
     }
 }
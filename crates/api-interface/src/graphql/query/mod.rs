@@ -1,10 +1,17 @@
+use api_core::api::QueryCategories;
 use async_graphql::connection::{Connection, EmptyFields};
 
 pub(crate) mod category;
 pub(crate) mod pagination;
 
-#[derive(async_graphql::MergedObject, Default)]
-pub struct Query(category::CategoryQuery);
+#[derive(async_graphql::MergedObject)]
+pub struct Query<D: QueryCategories + Send + Sync + 'static>(category::CategoryQuery<D>);
+
+impl<D: QueryCategories + Send + Sync + 'static> Default for Query<D> {
+    fn default() -> Self {
+        Self(category::CategoryQuery::default())
+    }
+}
 
 pub(crate) type ConnectionResult<T> = async_graphql::Result<
     Connection<pagination::Base64Cursor, T, pagination::ConnectionFields, EmptyFields>,
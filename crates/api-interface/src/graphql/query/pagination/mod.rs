@@ -1,8 +1,9 @@
-use std::convert::Infallible;
+use std::future::Future;
 
+use api_core::{api::CoreError, Category, CategoryOrder, CategoryOrderField, KeysetPage, SortKey};
 use async_graphql::{
     connection::{self, Connection, CursorType, Edge},
-    SimpleObject,
+    Enum, SimpleObject,
 };
 use base64::{prelude::BASE64_URL_SAFE_NO_PAD, Engine};
 
@@ -11,7 +12,7 @@ use super::{ConnectionResult, Params};
 /// Base64 invalid states, used by `Base64Cursor`.
 pub enum Base64CursorError {
     /// Invalid cursor. This can happen if the base64 string is valid, but its contents don't
-    /// conform to the `name:index` pattern.
+    /// conform to the `Cursor:index` or `Key:sort key` patterns.
     Invalid,
     /// Decoding error. If this happens, the string isn't valid base64.
     DecodeError(base64::DecodeError),
@@ -19,27 +20,42 @@ pub enum Base64CursorError {
 
 impl std::fmt::Display for Base64CursorError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Invalid cursor")
+        match self {
+            Self::Invalid => write!(f, "Invalid cursor"),
+            Self::DecodeError(e) => write!(f, "Invalid cursor: {e}"),
+        }
     }
 }
 
+/// How the cursors of a connection point into the result set
+#[derive(Enum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CursorMode {
+    /// Cursors hold the index of an item
+    #[default]
+    Offset,
+    /// Cursors hold the sort key of an item, so pages stay in place while the data changes
+    Keyset,
+}
+
 /// Base64 cursor implementation
-pub struct Base64Cursor {
-    name: &'static str,
-    index: usize,
+pub enum Base64Cursor {
+    /// Index of the item in the result set
+    Offset(usize),
+    /// Sort key of the item
+    Keyset(SortKey),
 }
 
 impl Base64Cursor {
-    const fn new(index: usize) -> Self {
-        Self {
-            name: "Cursor",
-            index,
-        }
-    }
-
     /// Returns a base64 string representation of the cursor
     fn encode(&self) -> String {
-        BASE64_URL_SAFE_NO_PAD.encode(format!("{}:{}", self.name, self.index))
+        let cursor = match self {
+            Self::Offset(index) => format!("Cursor:{index}"),
+            Self::Keyset(key) => {
+                format!("Key:{}", serde_json::to_string(key).unwrap_or_default())
+            }
+        };
+
+        BASE64_URL_SAFE_NO_PAD.encode(cursor)
     }
 
     /// Decodes a base64 string into a cursor result
@@ -49,26 +65,33 @@ impl Base64Cursor {
             .map_err(Base64CursorError::DecodeError)?;
 
         let cursor = String::from_utf8(bytes).map_err(|_| Base64CursorError::Invalid)?;
-        let index = cursor
-            .split(':')
-            .last()
-            .map(|s| s.parse::<usize>())
-            .ok_or(Base64CursorError::Invalid)?
-            .map_err(|_| Base64CursorError::Invalid)?;
-
-        Ok(Self::new(index))
+        match cursor.split_once(':') {
+            Some(("Cursor", index)) => index
+                .parse::<usize>()
+                .map(Self::Offset)
+                .map_err(|_| Base64CursorError::Invalid),
+            Some(("Key", key)) => serde_json::from_str(key)
+                .map(Self::Keyset)
+                .map_err(|_| Base64CursorError::Invalid),
+            _ => Err(Base64CursorError::Invalid),
+        }
     }
 
-    /// Increment and return the index. Uses saturating_add to avoid overflow
-    /// issues.
-    const fn increment(&self) -> usize {
-        self.index.saturating_add(1)
+    /// The index of an offset cursor
+    fn index(&self) -> async_graphql::Result<usize> {
+        match self {
+            Self::Offset(index) => Ok(*index),
+            Self::Keyset(_) => Err("keyset cursors need `cursorMode: KEYSET`".into()),
+        }
     }
-}
 
-impl From<Base64Cursor> for usize {
-    fn from(cursor: Base64Cursor) -> Self {
-        cursor.index
+    /// The sort key of a keyset cursor, which has to come from a result set sorted by `field`
+    fn into_key(self, field: CategoryOrderField) -> async_graphql::Result<SortKey> {
+        match self {
+            Self::Keyset(key) if key.value.field() == field => Ok(key),
+            Self::Keyset(_) => Err("the cursor was made for another `orderBy` field".into()),
+            Self::Offset(_) => Err("offset cursors need `cursorMode: OFFSET`".into()),
+        }
     }
 }
 
@@ -99,7 +122,7 @@ pub async fn paginate<T: async_graphql::OutputType, I: ExactSizeIterator<Item =
     p: Params,
     default_page_size: usize,
 ) -> ConnectionResult<T> {
-    connection::query::<_, _, Base64Cursor, _, _, ConnectionFields, _, _, _, Infallible>(
+    connection::query::<_, _, Base64Cursor, _, _, ConnectionFields, _, _, _, async_graphql::Error>(
         p.after,
         p.before,
         p.first,
@@ -108,8 +131,14 @@ pub async fn paginate<T: async_graphql::OutputType, I: ExactSizeIterator<Item =
             let iter_len = iter.len();
 
             let (start, end) = {
-                let after = after.map(|a| a.increment()).unwrap_or(0);
-                let before: usize = before.map(|b| b.into()).unwrap_or(iter_len);
+                let after = match after {
+                    Some(after) => after.index()?.saturating_add(1),
+                    None => 0,
+                };
+                let before = match before {
+                    Some(before) => before.index()?,
+                    None => iter_len,
+                };
 
                 // Calculate start/end based on the provided first/last. Note that async-graphql disallows
                 // providing both (returning an error), so we can safely assume we have, at most, one of
@@ -134,7 +163,65 @@ pub async fn paginate<T: async_graphql::OutputType, I: ExactSizeIterator<Item =
             connection.edges.extend(
                 (start..end)
                     .zip(iter.skip(start))
-                    .map(|(cursor, node)| Edge::new(Base64Cursor::new(cursor), node)),
+                    .map(|(cursor, node)| Edge::new(Base64Cursor::Offset(cursor), node)),
+            );
+            Ok(connection)
+        },
+    )
+    .await
+}
+
+/// Creates a new Relay-compliant connection from a single page read by `fetch`
+pub async fn paginate_keyset<F, Fut>(
+    p: Params,
+    default_page_size: usize,
+    order: CategoryOrder,
+    total_count: usize,
+    fetch: F,
+) -> ConnectionResult<Category>
+where
+    F: FnOnce(KeysetPage) -> Fut,
+    Fut: Future<Output = Result<Vec<Category>, CoreError>>,
+{
+    connection::query::<_, _, Base64Cursor, _, _, ConnectionFields, _, _, _, async_graphql::Error>(
+        p.after,
+        p.before,
+        p.first,
+        p.last,
+        |after, before, first, last| async move {
+            let limit = first.or(last).unwrap_or(default_page_size);
+            let page = KeysetPage {
+                after: after.map(|a| a.into_key(order.field)).transpose()?,
+                before: before.map(|b| b.into_key(order.field)).transpose()?,
+                // one more than the page holds tells whether the result set goes on
+                limit: limit.saturating_add(1),
+                from_end: first.is_none() && last.is_some(),
+            };
+
+            let mut nodes = fetch(page.clone()).await?;
+            let more = nodes.len() > limit;
+            if more {
+                if page.from_end {
+                    nodes.remove(0);
+                } else {
+                    nodes.pop();
+                }
+            }
+
+            let (has_previous_page, has_next_page) = if page.from_end {
+                (more, page.before.is_some())
+            } else {
+                (page.after.is_some(), more)
+            };
+            let mut connection = Connection::with_additional_fields(
+                has_previous_page,
+                has_next_page,
+                ConnectionFields { total_count },
+            );
+            connection.edges.extend(
+                nodes
+                    .into_iter()
+                    .map(|node| Edge::new(Base64Cursor::Keyset(order.key(&node)), node)),
             );
             Ok(connection)
         },
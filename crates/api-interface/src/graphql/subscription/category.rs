@@ -1,16 +1,23 @@
-use api_core::{api::QueryCategories, Category};
-use async_graphql::{Context, Object, Subscription};
+use std::marker::PhantomData;
+
+use api_core::{api::QueryCategories, graphql::CategoryLoader, Category};
+use async_graphql::{dataloader::DataLoader, Context, Object, Subscription};
 use futures_util::{Stream, StreamExt};
 
-use crate::graphql::{extract_db, mutation::MutationType, subscription::CategoryChanged};
+use crate::graphql::{mutation::MutationType, subscription::CategoryChanged};
 
 use super::broker::SimpleBroker;
 
-#[derive(Default)]
-pub struct CategorySubscription;
+pub struct CategorySubscription<D>(PhantomData<D>);
+
+impl<D> Default for CategorySubscription<D> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
 
 #[Subscription]
-impl CategorySubscription {
+impl<D: QueryCategories + Send + Sync + 'static> CategorySubscription<D> {
     async fn categories(
         &self,
         mutation_type: Option<MutationType>,
@@ -36,10 +43,10 @@ impl CategoryChanged {
         self.id.to_string()
     }
 
+    /// Looked up through the category loader
     async fn category(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<Category>> {
-        let database = extract_db(ctx)?;
-        let category = database.get_category_by_id(&self.id).await?;
+        let loader = ctx.data::<DataLoader<CategoryLoader>>()?;
 
-        Ok(category)
+        Ok(loader.load_one(self.id).await?)
     }
 }
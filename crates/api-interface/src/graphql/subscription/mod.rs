@@ -1,11 +1,19 @@
 pub(crate) mod broker;
 pub(crate) mod category;
-use api_core::reexports::uuid::Uuid;
+use api_core::{api::QueryCategories, reexports::uuid::Uuid};
 
 use super::mutation::MutationType;
 
-#[derive(async_graphql::MergedSubscription, Default)]
-pub struct Subscription(category::CategorySubscription);
+#[derive(async_graphql::MergedSubscription)]
+pub struct Subscription<D: QueryCategories + Send + Sync + 'static>(
+    category::CategorySubscription<D>,
+);
+
+impl<D: QueryCategories + Send + Sync + 'static> Default for Subscription<D> {
+    fn default() -> Self {
+        Self(category::CategorySubscription::default())
+    }
+}
 
 #[derive(Debug, Clone)]
 pub(crate) struct CategoryChanged {
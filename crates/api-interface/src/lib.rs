@@ -1,5 +1,10 @@
-use api_database::Client;
-use async_graphql::{extensions::ExtensionFactory, Schema, SchemaBuilder};
+use std::sync::Arc;
+
+use api_core::{
+    api::{MutateCategories, QueryCategories},
+    graphql::{CategoryLoader, CategoryResolver, CategoryStatsLoader, SubCategoryLoader},
+};
+use async_graphql::{dataloader::DataLoader, extensions::ExtensionFactory, Schema, SchemaBuilder};
 use thiserror::Error;
 use tracing::{info, instrument, trace};
 
@@ -7,11 +12,14 @@ use self::graphql::{mutation::Mutation, query::Query, subscription::Subscription
 
 pub mod graphql;
 
+pub use api_core::graphql::Locales;
+pub use api_database::{Client, DatabaseAuth};
+pub use graphql::Caller;
+
 #[derive(Debug, Clone, Copy)]
 pub struct DatabaseCredentials<'a> {
     pub db_dsn: &'a str,
-    pub db_user: &'a str,
-    pub db_pass: &'a str,
+    pub db_auth: DatabaseAuth<'a>,
     pub db_ns: &'a str,
     pub db: &'a str,
 }
@@ -24,8 +32,14 @@ pub struct RedisConfig<'a> {
     pub ttl: u64,
 }
 
-pub struct ApiSchemaBuilder {
-    builder: SchemaBuilder<Query, Mutation, Subscription>,
+/// The GraphQL schema served by the API, backed by storage `D`
+pub type ApiSchema<D = Client> = Schema<Query<D>, Mutation<D>, Subscription<D>>;
+
+pub struct ApiSchemaBuilder<D = Client>
+where
+    D: QueryCategories + MutateCategories + Send + Sync + 'static,
+{
+    builder: SchemaBuilder<Query<D>, Mutation<D>, Subscription<D>>,
 }
 
 #[derive(Error, Debug)]
@@ -34,35 +48,72 @@ pub enum SchemaError {
     DatabaseError(#[from] api_database::ClientError),
 }
 
-impl ApiSchemaBuilder {
+/// Connects the database client the schema is backed by
+#[instrument(skip_all, fields(db.url = %database.db_dsn), name = "client.init")]
+pub async fn connect(
+    database: DatabaseCredentials<'_>,
+    redis: Option<RedisConfig<'_>>,
+    meilisearch: Option<(&str, Option<&str>)>,
+) -> Result<Client, api_database::ClientError> {
+    trace!("creating database client");
+    let db_client = Client::try_new(
+        database.db_dsn,
+        database.db_auth,
+        database.db_ns,
+        database.db,
+        redis.map(|f| (f.redis_dsn, f.clustered, f.pool_size, f.ttl)),
+        meilisearch,
+    )
+    .await?;
+
+    info!("database database client created");
+
+    Ok(db_client)
+}
+
+impl ApiSchemaBuilder<Client> {
     #[instrument(skip_all, fields(db.url = %database.db_dsn), name = "schema.init")]
     pub async fn new(
         database: DatabaseCredentials<'_>,
         redis: Option<RedisConfig<'_>>,
         meilisearch: Option<(&str, Option<&str>)>,
     ) -> Result<Self, SchemaError> {
-        trace!("creating database client");
-        let db_client = Client::try_new(
-            database.db_dsn,
-            database.db_user,
-            database.db_pass,
-            database.db_ns,
-            database.db,
-            redis.map(|f| (f.redis_dsn, f.clustered, f.pool_size, f.ttl)),
-            meilisearch,
-        )
-        .await?;
+        let db_client = connect(database, redis, meilisearch).await?;
+
+        Ok(Self::with_database(db_client))
+    }
+}
 
-        info!("database database client created");
+impl<D> ApiSchemaBuilder<D>
+where
+    D: QueryCategories + MutateCategories + Send + Sync + 'static,
+{
+    /// Creates a schema backed by any storage implementing the category traits
+    pub fn with_database(database: D) -> Self {
+        trace!("attaching database to schema");
+        let database = Arc::new(database);
+        let resolver: Arc<dyn CategoryResolver> = Arc::clone(&database) as _;
+        // no cache, so loaders shared by every request only batch and never serve stale data
+        let stats = DataLoader::new(
+            CategoryStatsLoader::new(Arc::clone(&resolver)),
+            tokio::spawn,
+        );
+        let categories = DataLoader::new(CategoryLoader::new(Arc::clone(&resolver)), tokio::spawn);
+        let sub_categories =
+            DataLoader::new(SubCategoryLoader::new(Arc::clone(&resolver)), tokio::spawn);
 
         let schema_build = Schema::build(
             Query::default(),
             Mutation::default(),
             Subscription::default(),
         )
-        .data(db_client);
+        .data(database)
+        .data(resolver)
+        .data(stats)
+        .data(categories)
+        .data(sub_categories);
 
-        let builder = Self {
+        Self {
             builder: {
                 #[cfg(debug_assertions)]
                 {
@@ -74,9 +125,7 @@ impl ApiSchemaBuilder {
                     schema_build.disable_introspection()
                 }
             },
-        };
-
-        Ok(builder)
+        }
     }
 
     #[instrument(skip(self, extension), name = "schema.ext")]
@@ -88,7 +137,7 @@ impl ApiSchemaBuilder {
     }
 
     #[instrument(skip(self), name = "schema.build")]
-    pub fn build(self) -> Schema<Query, Mutation, Subscription> {
+    pub fn build(self) -> ApiSchema<D> {
         trace!("building schema");
         self.builder.finish()
     }
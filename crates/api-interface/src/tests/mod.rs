@@ -5,7 +5,8 @@ use async_graphql::{
     Request, ServerResult,
 };
 
-use crate::{ApiSchemaBuilder, DatabaseCredentials};
+use crate::{ApiSchema, ApiSchemaBuilder};
+use api_database::MemoryClient;
 use async_trait::async_trait;
 
 mod mutation;
@@ -34,31 +35,10 @@ impl Extension for TestExtension {
     }
 }
 
-async fn init_schema() -> async_graphql::Schema<
-    crate::graphql::query::Query,
-    crate::graphql::mutation::Mutation,
-    crate::graphql::subscription::Subscription,
-> {
-    dotenvy::dotenv().ok();
-    let db_host = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL");
-    let db_host = db_host.replace("http://", "");
+type TestSchema = ApiSchema<MemoryClient>;
 
-    let username = std::env::var("TEST_DATABASE_USERNAME").expect("TEST_DATABASE_USERNAME");
-    let password = std::env::var("TEST_DATABASE_PASSWORD").expect("TEST_DATABASE_PASSWORD");
-    let db_namespace = std::env::var("TEST_DATABASE_NAMESPACE").expect("TEST_DATABASE_NAMESPACE");
-    let db_name = std::env::var("TEST_DATABASE_NAME").expect("TEST_DATABASE_NAME");
-
-    let database_credentials = DatabaseCredentials {
-        db_dsn: &db_host,
-        db_user: &username,
-        db_pass: &password,
-        db_ns: &db_namespace,
-        db: &db_name,
-    };
-
-    ApiSchemaBuilder::new(database_credentials, None, None)
-        .await
-        .expect("schema created successfully")
+async fn init_schema() -> TestSchema {
+    ApiSchemaBuilder::with_database(MemoryClient::new())
         .with_extension(DummyExtension)
         .build()
 }
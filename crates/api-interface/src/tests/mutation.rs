@@ -1,14 +1,8 @@
 use core::panic;
 
-use async_graphql::Schema;
+use super::TestSchema;
 
-use crate::graphql::{mutation::Mutation, query::Query, subscription::Subscription};
-
-async fn execute_mutation(
-    query: &str,
-    schema: &Schema<Query, Mutation, Subscription>,
-    mutation: &str,
-) -> String {
+async fn execute_mutation(query: &str, schema: &TestSchema, mutation: &str) -> String {
     let res = schema.execute(query).await;
 
     dbg!(query);
@@ -95,3 +89,587 @@ async fn gql_mutation() {
     let id_3 = execute_mutation(&delete_mutation, &schema, "deleteCategory").await;
     assert_eq!(&id, &id_3);
 }
+
+#[tokio::test]
+async fn gql_mutation_delete_strategy() -> Result<(), Box<dyn std::error::Error>> {
+    use std::sync::Arc;
+
+    use api_core::{
+        api::{MutateCategories, QueryCategories},
+        Category,
+    };
+    use api_database::MemoryClient;
+
+    use crate::ApiSchemaBuilder;
+
+    let database = Arc::new(MemoryClient::new());
+    let schema = ApiSchemaBuilder::with_database(Arc::clone(&database)).build();
+
+    let parent = database
+        .create_category(
+            &Category {
+                id: Default::default(),
+                name: "Electronics".into(),
+                sub_categories: vec![],
+                image_url: None,
+                parent_id: None,
+                ..Default::default()
+            },
+            None,
+        )
+        .await?;
+    let child = database
+        .create_category(
+            &Category {
+                name: "Phones".into(),
+                parent_id: Some(parent.id),
+                ..parent.clone()
+            },
+            None,
+        )
+        .await?;
+
+    let delete_mutation = |strategy: &str| {
+        format!(
+            r#"
+            mutation {{
+              deleteCategory(id: "{}", strategy: {strategy}) {{
+                name
+              }}
+            }}
+            "#,
+            parent.id
+        )
+    };
+
+    let res = schema.execute(delete_mutation("RESTRICT")).await;
+    assert_eq!(res.errors.len(), 1);
+    assert!(database.get_category_by_id(&parent.id).await?.is_some());
+
+    let res = schema.execute(delete_mutation("CASCADE")).await;
+    assert!(res.errors.is_empty());
+    assert_eq!(
+        res.data,
+        async_graphql::value!({ "deleteCategory": { "name": "Electronics" } })
+    );
+    assert!(database.get_category_by_id(&child.id).await?.is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn gql_mutation_move_category() -> Result<(), Box<dyn std::error::Error>> {
+    use std::sync::Arc;
+
+    use api_core::{
+        api::{MutateCategories, QueryCategories},
+        Category,
+    };
+    use api_database::MemoryClient;
+
+    use crate::ApiSchemaBuilder;
+
+    let database = Arc::new(MemoryClient::new());
+    let schema = ApiSchemaBuilder::with_database(Arc::clone(&database)).build();
+
+    let category = Category {
+        id: Default::default(),
+        name: "Electronics".into(),
+        sub_categories: vec![],
+        image_url: None,
+        parent_id: None,
+        ..Default::default()
+    };
+    let parent = database.create_category(&category, None).await?;
+    let sibling = database
+        .create_category(
+            &Category {
+                name: "Phones".into(),
+                parent_id: Some(parent.id),
+                ..category.clone()
+            },
+            None,
+        )
+        .await?;
+    let moved = database
+        .create_category(
+            &Category {
+                name: "Laptops".into(),
+                ..category.clone()
+            },
+            None,
+        )
+        .await?;
+
+    let res = schema
+        .execute(format!(
+            r#"
+            mutation {{
+              moveCategory(id: "{}", newParentId: "{}", position: 0) {{
+                parentId
+              }}
+            }}
+            "#,
+            moved.id, parent.id
+        ))
+        .await;
+
+    assert!(res.errors.is_empty());
+    assert_eq!(
+        res.data,
+        async_graphql::value!({ "moveCategory": { "parentId": parent.id.to_string() } })
+    );
+
+    let parent = database
+        .get_category_by_id(&parent.id)
+        .await?
+        .expect("parent to exist");
+    assert_eq!(parent.sub_categories, vec![moved.id, sibling.id]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn gql_mutation_reorder_sub_categories() -> Result<(), Box<dyn std::error::Error>> {
+    use std::sync::Arc;
+
+    use api_core::{
+        api::{MutateCategories, QueryCategories},
+        Category,
+    };
+    use api_database::MemoryClient;
+
+    use crate::ApiSchemaBuilder;
+
+    let database = Arc::new(MemoryClient::new());
+    let schema = ApiSchemaBuilder::with_database(Arc::clone(&database)).build();
+
+    let category = Category {
+        name: "Electronics".into(),
+        ..Default::default()
+    };
+    let parent = database.create_category(&category, None).await?;
+    let mut children = vec![];
+    for name in ["Phones", "Laptops", "Cameras"] {
+        let child = Category {
+            name: name.into(),
+            parent_id: Some(parent.id),
+            ..Default::default()
+        };
+        children.push(database.create_category(&child, None).await?.id);
+    }
+    children.reverse();
+
+    let ordered = children
+        .iter()
+        .map(|id| format!(r#""{id}""#))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let res = schema
+        .execute(format!(
+            r#"
+            mutation {{
+              reorderSubCategories(parentId: "{}", orderedIds: [{ordered}]) {{
+                position
+                version
+              }}
+            }}
+            "#,
+            parent.id
+        ))
+        .await;
+
+    assert!(res.errors.is_empty());
+    assert_eq!(
+        res.data,
+        async_graphql::value!({ "reorderSubCategories": [
+            { "position": 0, "version": 2 },
+            { "position": 1, "version": 1 },
+            { "position": 2, "version": 2 },
+        ] })
+    );
+
+    let sub_categories: Vec<_> = database
+        .get_sub_categories(Some(&parent.id))
+        .await?
+        .map(|category| category.id)
+        .collect();
+    assert_eq!(sub_categories, children);
+
+    // leaving a subcategory out is refused
+    let res = schema
+        .execute(format!(
+            r#"
+            mutation {{
+              reorderSubCategories(parentId: "{}", orderedIds: ["{}"]) {{
+                id
+              }}
+            }}
+            "#,
+            parent.id, children[0]
+        ))
+        .await;
+
+    assert!(!res.errors.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn gql_mutation_patch_category() -> Result<(), Box<dyn std::error::Error>> {
+    use std::sync::Arc;
+
+    use api_core::{api::MutateCategories, Category};
+    use api_database::MemoryClient;
+
+    use crate::ApiSchemaBuilder;
+
+    let database = Arc::new(MemoryClient::new());
+    let schema = ApiSchemaBuilder::with_database(Arc::clone(&database)).build();
+
+    let category = database
+        .create_category(
+            &Category {
+                id: Default::default(),
+                name: "Electronics".into(),
+                sub_categories: vec![],
+                image_url: Some("https://example.com/image.png".into()),
+                parent_id: None,
+                ..Default::default()
+            },
+            None,
+        )
+        .await?;
+
+    let res = schema
+        .execute(format!(
+            r#"
+            mutation {{
+              patchCategory(id: "{}", input: {{ name: "Gadgets" }}) {{
+                name
+                imageUrl
+              }}
+            }}
+            "#,
+            category.id
+        ))
+        .await;
+
+    assert!(res.errors.is_empty());
+    assert_eq!(
+        res.data,
+        async_graphql::value!({
+            "patchCategory": {
+                "name": "Gadgets",
+                "imageUrl": "https://example.com/image.png"
+            }
+        })
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn gql_mutation_version_conflict() -> Result<(), Box<dyn std::error::Error>> {
+    use std::sync::Arc;
+
+    use api_core::{api::MutateCategories, Category};
+    use api_database::MemoryClient;
+
+    use crate::ApiSchemaBuilder;
+
+    let database = Arc::new(MemoryClient::new());
+    let schema = ApiSchemaBuilder::with_database(Arc::clone(&database)).build();
+
+    let category = database
+        .create_category(
+            &Category {
+                name: "Electronics".into(),
+                ..Default::default()
+            },
+            None,
+        )
+        .await?;
+
+    let rename = |version: u64| {
+        format!(
+            r#"
+            mutation {{
+              patchCategory(id: "{}", input: {{ name: "Gadgets" }}, expectedVersion: {version}) {{
+                version
+              }}
+            }}
+            "#,
+            category.id
+        )
+    };
+
+    let res = schema.execute(rename(category.version)).await;
+    assert!(res.errors.is_empty());
+    assert_eq!(
+        res.data,
+        async_graphql::value!({ "patchCategory": { "version": 2 } })
+    );
+
+    let res = schema.execute(rename(category.version)).await;
+    assert_eq!(res.errors.len(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn gql_mutation_records_caller() -> Result<(), Box<dyn std::error::Error>> {
+    use async_graphql::Request;
+
+    use crate::Caller;
+
+    let schema = super::init_schema().await;
+
+    let res = schema
+        .execute(
+            Request::new(
+                r#"
+                mutation {
+                  createCategory(input: { name: "Electronics", subCategories: [] }) {
+                    createdBy
+                    updatedBy
+                  }
+                }
+                "#,
+            )
+            .data(Caller("moderator".into())),
+        )
+        .await;
+    assert!(res.errors.is_empty());
+    assert_eq!(
+        res.data,
+        async_graphql::value!({
+            "createCategory": { "createdBy": "moderator", "updatedBy": "moderator" }
+        })
+    );
+
+    let res = schema
+        .execute(
+            r#"
+            mutation {
+              createCategory(input: { name: "Books", subCategories: [] }) {
+                createdBy
+              }
+            }
+            "#,
+        )
+        .await;
+    assert!(res.errors.is_empty());
+    assert_eq!(
+        res.data,
+        async_graphql::value!({ "createCategory": { "createdBy": null } })
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn gql_mutation_restore_category() -> Result<(), Box<dyn std::error::Error>> {
+    use std::sync::Arc;
+
+    use api_core::{api::MutateCategories, Category, DeleteStrategy};
+    use api_database::MemoryClient;
+
+    use crate::ApiSchemaBuilder;
+
+    let database = Arc::new(MemoryClient::new());
+    let schema = ApiSchemaBuilder::with_database(Arc::clone(&database)).build();
+
+    let category = database
+        .create_category(
+            &Category {
+                name: "Electronics".into(),
+                ..Default::default()
+            },
+            None,
+        )
+        .await?;
+    database
+        .delete_category(&category.id, DeleteStrategy::Restrict, None, None)
+        .await?;
+
+    let res = schema
+        .execute(format!(
+            r#"
+            mutation {{
+              restoreCategory(id: "{}", revision: 1) {{
+                name
+                version
+              }}
+            }}
+            "#,
+            category.id
+        ))
+        .await;
+    assert!(res.errors.is_empty());
+    assert_eq!(
+        res.data,
+        async_graphql::value!({ "restoreCategory": { "name": "Electronics", "version": 3 } })
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn gql_mutation_trash() -> Result<(), Box<dyn std::error::Error>> {
+    use std::sync::Arc;
+
+    use api_core::{
+        api::{MutateCategories, QueryCategories},
+        Category,
+    };
+    use api_database::MemoryClient;
+
+    use crate::ApiSchemaBuilder;
+
+    let database = Arc::new(MemoryClient::new());
+    let schema = ApiSchemaBuilder::with_database(Arc::clone(&database)).build();
+
+    let category = database
+        .create_category(
+            &Category {
+                name: "Electronics".into(),
+                ..Default::default()
+            },
+            None,
+        )
+        .await?;
+
+    let execute = |query: &str| schema.execute(query.replace("$id", &category.id.to_string()));
+
+    let res = execute(r#"mutation { deleteCategory(id: "$id") { name } }"#).await;
+    assert!(res.errors.is_empty());
+
+    let query = r#"
+        query {
+          categoryById(id: "$id") { name }
+          trash: categoryById(id: "$id", includeDeleted: true) { name }
+          categories(first: 10, includeDeleted: true) { edges { node { name } } }
+        }
+    "#;
+    let res = execute(query).await;
+    assert!(res.errors.is_empty());
+    assert_eq!(
+        res.data,
+        async_graphql::value!({
+            "categoryById": null,
+            "trash": { "name": "Electronics" },
+            "categories": { "edges": [{ "node": { "name": "Electronics" } }] },
+        })
+    );
+
+    let res = execute(r#"mutation { restoreCategory(id: "$id") { name } }"#).await;
+    assert!(res.errors.is_empty());
+    assert!(database.get_category_by_id(&category.id).await?.is_some());
+
+    let res = execute(r#"mutation { purgeCategory(id: "$id") { name } }"#).await;
+    assert_eq!(res.errors.len(), 1);
+
+    execute(r#"mutation { deleteCategory(id: "$id") { name } }"#).await;
+    let res = execute(r#"mutation { purgeCategory(id: "$id") { name } }"#).await;
+    assert!(res.errors.is_empty());
+    assert_eq!(
+        res.data,
+        async_graphql::value!({ "purgeCategory": { "name": "Electronics" } })
+    );
+    assert_eq!(database.get_deleted_categories().await?.len(), 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn gql_mutation_batch() -> Result<(), Box<dyn std::error::Error>> {
+    use std::sync::Arc;
+
+    use api_core::api::QueryCategories;
+    use api_database::MemoryClient;
+
+    use crate::ApiSchemaBuilder;
+
+    let database = Arc::new(MemoryClient::new());
+    let schema = ApiSchemaBuilder::with_database(Arc::clone(&database)).build();
+
+    let parent = uuid::Uuid::now_v7();
+    let query = r#"
+        mutation {
+          createCategories(input: [
+            { name: "Phones", parentId: "$parent" },
+            { id: "$parent", name: "Electronics" },
+          ]) { name parentId }
+        }
+    "#;
+    let res = schema
+        .execute(query.replace("$parent", &parent.to_string()))
+        .await;
+    assert!(res.errors.is_empty());
+    assert_eq!(
+        res.data,
+        async_graphql::value!({
+            "createCategories": [
+                { "name": "Phones", "parentId": parent.to_string() },
+                { "name": "Electronics", "parentId": null },
+            ]
+        })
+    );
+    let child = database
+        .get_sub_categories(Some(&parent))
+        .await?
+        .collect::<Vec<_>>()[0]
+        .id;
+
+    let query = r#"
+        mutation {
+          updateCategories(input: [
+            { id: "$child", category: { name: "Mobile phones", parentId: "$parent" } },
+            { id: "$parent", category: { name: "Electronics" }, expectedVersion: 9 },
+          ]) { name }
+        }
+    "#;
+    let execute = |query: &str| {
+        schema.execute(
+            query
+                .replace("$parent", &parent.to_string())
+                .replace("$child", &child.to_string()),
+        )
+    };
+    let res = execute(query).await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        database
+            .get_category_by_id(&child)
+            .await?
+            .map(|category| category.name),
+        Some("Phones".into())
+    );
+
+    let query = r#"
+        mutation {
+          deleteCategories(input: [{ id: "$child" }, { id: "$parent", expectedVersion: 9 }]) {
+            name
+          }
+        }
+    "#;
+    let res = execute(query).await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(database.get_categories().await?.len(), 2);
+
+    let res = execute(
+        r#"mutation { deleteCategories(input: [{ id: "$child" }, { id: "$parent" }]) { name } }"#,
+    )
+    .await;
+    assert!(res.errors.is_empty());
+    assert_eq!(
+        res.data,
+        async_graphql::value!({
+            "deleteCategories": [{ "name": "Phones" }, { "name": "Electronics" }]
+        })
+    );
+    assert_eq!(database.get_categories().await?.len(), 0);
+
+    Ok(())
+}
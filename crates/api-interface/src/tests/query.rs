@@ -104,8 +104,7 @@ async fn gql_search_ok() -> Result<(), Box<dyn std::error::Error>> {
         )
         .await;
 
-    // search client not configured
-    assert!(!res.errors.is_empty());
+    assert!(res.errors.is_empty());
 
     let res_name = schema
         .execute(
@@ -130,8 +129,878 @@ async fn gql_search_ok() -> Result<(), Box<dyn std::error::Error>> {
         )
         .await;
 
-    // search client not configured
-    assert_eq!(&res.errors[0].message, &res_name.errors[0].message);
+    assert!(res_name.errors.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn gql_query_shared_database() -> Result<(), Box<dyn std::error::Error>> {
+    use std::sync::Arc;
+
+    use api_core::{api::MutateCategories, Category};
+    use api_database::MemoryClient;
+
+    use crate::ApiSchemaBuilder;
+
+    let database = Arc::new(MemoryClient::new());
+    let schema = ApiSchemaBuilder::with_database(Arc::clone(&database)).build();
+
+    let category = database
+        .create_category(
+            &Category {
+                id: Default::default(),
+                name: "Shared".into(),
+                sub_categories: vec![],
+                image_url: None,
+                parent_id: None,
+                ..Default::default()
+            },
+            None,
+        )
+        .await?;
+
+    let res = schema
+        .execute(format!(
+            r#"
+           query {{
+             categoryById(id: "{}") {{
+               name
+             }}
+           }}
+           "#,
+            category.id
+        ))
+        .await;
+
+    assert!(res.errors.is_empty());
+    assert_eq!(
+        res.data,
+        async_graphql::value!({ "categoryById": { "name": "Shared" } })
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn gql_query_category_tree_ok() -> Result<(), Box<dyn std::error::Error>> {
+    use std::sync::Arc;
+
+    use api_core::{api::MutateCategories, Category};
+    use api_database::MemoryClient;
+
+    use crate::ApiSchemaBuilder;
+
+    let database = Arc::new(MemoryClient::new());
+    let schema = ApiSchemaBuilder::with_database(Arc::clone(&database)).build();
+
+    let mut root = Category {
+        id: Default::default(),
+        name: "Electronics".into(),
+        sub_categories: vec![],
+        image_url: None,
+        parent_id: None,
+        ..Default::default()
+    };
+    root = database.create_category(&root, None).await?;
+
+    let child = database
+        .create_category(
+            &Category {
+                name: "Phones".into(),
+                parent_id: Some(root.id),
+                ..root.clone()
+            },
+            None,
+        )
+        .await?;
+
+    root.sub_categories.push(child.id);
+    database
+        .update_category(&root.id, &root, None, None)
+        .await?;
+
+    let query = |max_depth: u8| {
+        format!(
+            r#"
+           query {{
+             categoryTree(rootId: "{}", maxDepth: {max_depth}) {{
+               category {{
+                 name
+               }}
+               children {{
+                 category {{
+                   name
+                 }}
+               }}
+             }}
+           }}
+           "#,
+            root.id
+        )
+    };
+
+    let res = schema.execute(query(1)).await;
+    assert!(res.errors.is_empty());
+    assert_eq!(
+        res.data,
+        async_graphql::value!({
+            "categoryTree": [{
+                "category": { "name": "Electronics" },
+                "children": [{ "category": { "name": "Phones" } }]
+            }]
+        })
+    );
+
+    let res = schema.execute(query(0)).await;
+    assert!(res.errors.is_empty());
+    assert_eq!(
+        res.data,
+        async_graphql::value!({
+            "categoryTree": [{
+                "category": { "name": "Electronics" },
+                "children": []
+            }]
+        })
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn gql_query_breadcrumb_ok() -> Result<(), Box<dyn std::error::Error>> {
+    use std::sync::Arc;
+
+    use api_core::{api::MutateCategories, Category};
+    use api_database::MemoryClient;
+
+    use crate::ApiSchemaBuilder;
+
+    let database = Arc::new(MemoryClient::new());
+    let schema = ApiSchemaBuilder::with_database(Arc::clone(&database)).build();
+
+    let mut parent_id = None;
+    for name in ["Electronics", "Phones", "Accessories"] {
+        let category = database
+            .create_category(
+                &Category {
+                    id: Default::default(),
+                    name: name.into(),
+                    sub_categories: vec![],
+                    image_url: None,
+                    parent_id,
+                    ..Default::default()
+                },
+                None,
+            )
+            .await?;
+        parent_id = Some(category.id);
+    }
+
+    let res = schema
+        .execute(format!(
+            r#"
+           query {{
+             categoryById(id: "{}") {{
+               breadcrumb
+               ancestors {{
+                 name
+               }}
+             }}
+           }}
+           "#,
+            parent_id.expect("category to be created")
+        ))
+        .await;
+
+    assert!(res.errors.is_empty());
+    assert_eq!(
+        res.data,
+        async_graphql::value!({
+            "categoryById": {
+                "breadcrumb": "Electronics > Phones > Accessories",
+                "ancestors": [{ "name": "Electronics" }, { "name": "Phones" }]
+            }
+        })
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn gql_query_category_stats() -> Result<(), Box<dyn std::error::Error>> {
+    use std::sync::Arc;
+
+    use api_core::{api::MutateCategories, Category};
+    use api_database::MemoryClient;
+
+    use crate::ApiSchemaBuilder;
+
+    let database = Arc::new(MemoryClient::new());
+    let schema = ApiSchemaBuilder::with_database(Arc::clone(&database)).build();
+
+    let mut ids = Vec::new();
+    for name in ["Electronics", "Phones", "Accessories"] {
+        let category = database
+            .create_category(
+                &Category {
+                    name: name.into(),
+                    parent_id: ids.last().copied(),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await?;
+        ids.push(category.id);
+    }
+    let (electronics, accessories) = (ids[0], ids[2]);
+
+    let res = schema
+        .execute(format!(
+            r#"
+           mutation {{
+             top: adjustListingCount(id: "{electronics}", delta: 3)
+             leaf: adjustListingCount(id: "{accessories}", delta: 4)
+             removed: adjustListingCount(id: "{accessories}", delta: -1)
+           }}
+           "#
+        ))
+        .await;
+    assert!(res.errors.is_empty());
+    assert_eq!(
+        res.data,
+        async_graphql::value!({ "top": 3, "leaf": 4, "removed": 3 })
+    );
+
+    let res = schema
+        .execute(format!(
+            r#"
+           query {{
+             top: categoryById(id: "{electronics}") {{
+               childCount
+               descendantCount
+               isLeaf
+               depth
+               listingCount
+               own: listingCount(includeDescendants: false)
+             }}
+             leaf: categoryById(id: "{accessories}") {{
+               childCount
+               descendantCount
+               isLeaf
+               depth
+               listingCount
+             }}
+           }}
+           "#
+        ))
+        .await;
+    assert!(res.errors.is_empty());
+    assert_eq!(
+        res.data,
+        async_graphql::value!({
+            "top": {
+                "childCount": 1,
+                "descendantCount": 2,
+                "isLeaf": false,
+                "depth": 0,
+                "listingCount": 6,
+                "own": 3
+            },
+            "leaf": {
+                "childCount": 0,
+                "descendantCount": 0,
+                "isLeaf": true,
+                "depth": 2,
+                "listingCount": 3
+            }
+        })
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn gql_query_category_relationships() -> Result<(), Box<dyn std::error::Error>> {
+    use std::sync::Arc;
+
+    use api_core::{api::MutateCategories, Category};
+    use api_database::MemoryClient;
+
+    use crate::ApiSchemaBuilder;
+
+    let database = Arc::new(MemoryClient::new());
+    let schema = ApiSchemaBuilder::with_database(Arc::clone(&database)).build();
+
+    let electronics = database
+        .create_category(
+            &Category {
+                name: "Electronics".into(),
+                ..Default::default()
+            },
+            None,
+        )
+        .await?;
+    let mut children = Vec::new();
+    for name in ["Phones", "Laptops"] {
+        let category = database
+            .create_category(
+                &Category {
+                    name: name.into(),
+                    parent_id: Some(electronics.id),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await?;
+        children.push(category.id);
+    }
+
+    let res = schema
+        .execute(format!(
+            r#"
+           query {{
+             categoryById(id: "{}") {{
+               name
+               parent {{
+                 name
+               }}
+               subCategoryIds
+               subCategories {{
+                 name
+                 parent {{
+                   name
+                 }}
+               }}
+               children {{
+                 name
+                 children {{
+                   name
+                 }}
+               }}
+             }}
+           }}
+           "#,
+            electronics.id
+        ))
+        .await;
+
+    assert!(res.errors.is_empty());
+    assert_eq!(
+        res.data,
+        async_graphql::value!({
+            "categoryById": {
+                "name": "Electronics",
+                "parent": null,
+                "subCategoryIds": [children[0].to_string(), children[1].to_string()],
+                "subCategories": [
+                    { "name": "Phones", "parent": { "name": "Electronics" } },
+                    { "name": "Laptops", "parent": { "name": "Electronics" } }
+                ],
+                "children": [
+                    { "name": "Phones", "children": [] },
+                    { "name": "Laptops", "children": [] }
+                ]
+            }
+        })
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn gql_query_categories_order_by() -> Result<(), Box<dyn std::error::Error>> {
+    use std::sync::Arc;
+
+    use api_core::{api::MutateCategories, Category, CategoryPatch};
+    use api_database::MemoryClient;
+
+    use crate::ApiSchemaBuilder;
+
+    let database = Arc::new(MemoryClient::new());
+    let schema = ApiSchemaBuilder::with_database(Arc::clone(&database)).build();
+
+    let mut ids = vec![];
+    for name in ["First", "Second", "Third"] {
+        let category = database
+            .create_category(
+                &Category {
+                    name: name.into(),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await?;
+        ids.push(category.id);
+    }
+
+    let patch = CategoryPatch {
+        image_url: Some("https://example.com/first.png".into()),
+        ..Default::default()
+    };
+    database.patch_category(&ids[0], &patch, None, None).await?;
+
+    let query = |order_by: &str| {
+        format!(
+            r#"
+            query {{
+              categories(first: 3, orderBy: {order_by}) {{
+                edges {{
+                  node {{
+                    name
+                  }}
+                }}
+              }}
+            }}
+            "#
+        )
+    };
+
+    let res = schema
+        .execute(query("{ field: CREATED_AT, direction: DESC }"))
+        .await;
+    assert!(res.errors.is_empty());
+    assert_eq!(
+        res.data,
+        async_graphql::value!({ "categories": { "edges": [
+            { "node": { "name": "Third" } },
+            { "node": { "name": "Second" } },
+            { "node": { "name": "First" } },
+        ] } })
+    );
+
+    let res = schema.execute(query("{ field: UPDATED_AT }")).await;
+    assert!(res.errors.is_empty());
+    assert_eq!(
+        res.data,
+        async_graphql::value!({ "categories": { "edges": [
+            { "node": { "name": "Second" } },
+            { "node": { "name": "Third" } },
+            { "node": { "name": "First" } },
+        ] } })
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn gql_query_categories_filter() -> Result<(), Box<dyn std::error::Error>> {
+    use std::sync::Arc;
+
+    use api_core::{api::MutateCategories, Category};
+    use api_database::MemoryClient;
+
+    use crate::ApiSchemaBuilder;
+
+    let database = Arc::new(MemoryClient::new());
+    let schema = ApiSchemaBuilder::with_database(Arc::clone(&database)).build();
+
+    let apparel = database
+        .create_category(
+            &Category {
+                name: "Apparel".into(),
+                ..Default::default()
+            },
+            None,
+        )
+        .await?;
+    for name in ["Shoes", "Shirts", "Hats"] {
+        let category = Category {
+            name: name.into(),
+            parent_id: Some(apparel.id),
+            ..Default::default()
+        };
+        database.create_category(&category, None).await?;
+    }
+
+    let query = |arguments: &str| {
+        format!(
+            r#"
+            query {{
+              categories(first: 10, {arguments}) {{
+                edges {{
+                  node {{
+                    name
+                  }}
+                }}
+              }}
+            }}
+            "#
+        )
+    };
+
+    let res = schema
+        .execute(query(
+            r#"filter: { nameContains: "sh", isLeaf: true }, orderBy: { field: NAME }"#,
+        ))
+        .await;
+    assert!(res.errors.is_empty());
+    assert_eq!(
+        res.data,
+        async_graphql::value!({ "categories": { "edges": [
+            { "node": { "name": "Shirts" } },
+            { "node": { "name": "Shoes" } },
+        ] } })
+    );
+
+    let res = schema
+        .execute(query(
+            "filter: { depth: 1 }, orderBy: { field: POSITION, direction: DESC }",
+        ))
+        .await;
+    assert!(res.errors.is_empty());
+    assert_eq!(
+        res.data,
+        async_graphql::value!({ "categories": { "edges": [
+            { "node": { "name": "Hats" } },
+            { "node": { "name": "Shirts" } },
+            { "node": { "name": "Shoes" } },
+        ] } })
+    );
+
+    let res = schema
+        .execute(query(&format!(
+            r#"filter: {{ hasParent: false, ids: ["{}"] }}"#,
+            apparel.id
+        )))
+        .await;
+    assert!(res.errors.is_empty());
+    assert_eq!(
+        res.data,
+        async_graphql::value!({ "categories": { "edges": [
+            { "node": { "name": "Apparel" } },
+        ] } })
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn gql_query_categories_keyset() -> Result<(), Box<dyn std::error::Error>> {
+    use std::sync::Arc;
+
+    use api_core::{api::MutateCategories, Category};
+    use api_database::MemoryClient;
+
+    use crate::ApiSchemaBuilder;
+
+    let database = Arc::new(MemoryClient::new());
+    let schema = ApiSchemaBuilder::with_database(Arc::clone(&database)).build();
+
+    for name in ["Books", "Music", "Toys"] {
+        let category = Category {
+            name: name.into(),
+            ..Default::default()
+        };
+        database.create_category(&category, None).await?;
+    }
+
+    let query = |arguments: &str| {
+        format!(
+            r#"
+            query {{
+              categories(cursorMode: KEYSET, orderBy: {{ field: NAME }}, {arguments}) {{
+                totalCount
+                pageInfo {{
+                  hasNextPage
+                  endCursor
+                }}
+                edges {{
+                  node {{
+                    name
+                  }}
+                }}
+              }}
+            }}
+            "#
+        )
+    };
+
+    let res = schema.execute(query("first: 2")).await;
+    assert!(res.errors.is_empty());
+    let data = res.data.into_json()?;
+    assert_eq!(data["categories"]["totalCount"], 3);
+    assert_eq!(data["categories"]["pageInfo"]["hasNextPage"], true);
+    assert_eq!(data["categories"]["edges"][1]["node"]["name"], "Music");
+    let cursor = data["categories"]["pageInfo"]["endCursor"]
+        .as_str()
+        .expect("a cursor")
+        .to_owned();
+
+    // a category sorted before the cursor does not shift the next page
+    let category = Category {
+        name: "Art".into(),
+        ..Default::default()
+    };
+    database.create_category(&category, None).await?;
+
+    let res = schema
+        .execute(query(&format!(r#"first: 2, after: "{cursor}""#)))
+        .await;
+    assert!(res.errors.is_empty());
+    let data = res.data.into_json()?;
+    assert_eq!(data["categories"]["totalCount"], 4);
+    assert_eq!(data["categories"]["pageInfo"]["hasNextPage"], false);
+    assert_eq!(
+        data["categories"]["edges"],
+        serde_json::json!([{ "node": { "name": "Toys" } }])
+    );
+
+    let res = schema.execute(query("last: 1")).await;
+    assert!(res.errors.is_empty());
+    let data = res.data.into_json()?;
+    assert_eq!(
+        data["categories"]["edges"],
+        serde_json::json!([{ "node": { "name": "Toys" } }])
+    );
+
+    // keyset cursors only go with the order they were made for
+    let res = schema
+        .execute(format!(
+            r#"query {{ categories(first: 1, after: "{cursor}") {{ totalCount }} }}"#
+        ))
+        .await;
+    assert!(!res.errors.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn gql_query_category_history() -> Result<(), Box<dyn std::error::Error>> {
+    use std::sync::Arc;
+
+    use api_core::{api::MutateCategories, Category, CategoryPatch};
+    use api_database::MemoryClient;
+
+    use crate::ApiSchemaBuilder;
+
+    let database = Arc::new(MemoryClient::new());
+    let schema = ApiSchemaBuilder::with_database(Arc::clone(&database)).build();
+
+    let category = database
+        .create_category(
+            &Category {
+                name: "Electronics".into(),
+                ..Default::default()
+            },
+            None,
+        )
+        .await?;
+    let patch = CategoryPatch {
+        name: Some("Gadgets".into()),
+        ..Default::default()
+    };
+    database
+        .patch_category(&category.id, &patch, None, None)
+        .await?;
+
+    let res = schema
+        .execute(format!(
+            r#"
+            query {{
+              categoryHistory(id: "{}") {{
+                revision
+                kind
+                category {{
+                  name
+                }}
+              }}
+            }}
+            "#,
+            category.id
+        ))
+        .await;
+    assert!(res.errors.is_empty());
+    assert_eq!(
+        res.data,
+        async_graphql::value!({ "categoryHistory": [
+            { "revision": 1, "kind": "CREATED", "category": { "name": "Electronics" } },
+            { "revision": 2, "kind": "UPDATED", "category": { "name": "Gadgets" } },
+        ] })
+    );
+
+    let res = schema
+        .execute(format!(
+            r#"
+            query {{
+              categoryAt(id: "{}", timestamp: "2000-01-01T00:00:00Z") {{
+                name
+              }}
+            }}
+            "#,
+            category.id
+        ))
+        .await;
+    assert!(res.errors.is_empty());
+    assert_eq!(res.data, async_graphql::value!({ "categoryAt": null }));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn gql_query_category_by_slug() -> Result<(), Box<dyn std::error::Error>> {
+    use std::sync::Arc;
+
+    use api_core::{api::MutateCategories, Category};
+    use api_database::MemoryClient;
+
+    use crate::ApiSchemaBuilder;
+
+    let database = Arc::new(MemoryClient::new());
+    let schema = ApiSchemaBuilder::with_database(Arc::clone(&database)).build();
+
+    let mut parent_id = None;
+    for name in ["Apparel & Accessories", "Shoes"] {
+        let category = database
+            .create_category(
+                &Category {
+                    name: name.into(),
+                    parent_id,
+                    ..Default::default()
+                },
+                None,
+            )
+            .await?;
+        parent_id = Some(category.id);
+    }
+
+    let res = schema
+        .execute(
+            r#"
+           query {
+             shoes: categoryBySlug(path: "apparel-accessories/shoes") {
+               name
+               slug
+             }
+             missing: categoryBySlug(path: "shoes") {
+               name
+             }
+           }
+           "#,
+        )
+        .await;
+
+    assert!(res.errors.is_empty());
+    assert_eq!(
+        res.data,
+        async_graphql::value!({
+            "shoes": { "name": "Shoes", "slug": "shoes" },
+            "missing": null
+        })
+    );
+
+    let res = schema
+        .execute(
+            r#"
+           mutation {
+             createCategory(input: { name: "apparel  & accessories", subCategories: [] }) {
+               id
+             }
+           }
+           "#,
+        )
+        .await;
+    assert_eq!(
+        res.errors[0].message,
+        "a category with the slug `apparel-accessories` already exists under the same parent"
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn gql_query_effective_attributes() -> Result<(), Box<dyn std::error::Error>> {
+    use std::sync::Arc;
+
+    use api_core::{api::MutateCategories, AttributeDefinition, AttributeKind, Category};
+    use api_database::MemoryClient;
+
+    use crate::ApiSchemaBuilder;
+
+    let database = Arc::new(MemoryClient::new());
+    let schema = ApiSchemaBuilder::with_database(Arc::clone(&database)).build();
+
+    let apparel = database
+        .create_category(
+            &Category {
+                name: "Apparel".into(),
+                attributes: vec![AttributeDefinition {
+                    key: "size".into(),
+                    kind: AttributeKind::Select,
+                    required: true,
+                    allowed_values: vec!["S".into(), "M".into()],
+                }],
+                ..Default::default()
+            },
+            None,
+        )
+        .await?;
+
+    let res = schema
+        .execute(format!(
+            r#"
+           mutation {{
+             createCategory(input: {{
+               name: "Shoes",
+               parentId: "{}",
+               attributes: [
+                 {{ key: "brand", kind: TEXT }},
+                 {{ key: "size", kind: NUMBER, required: true }}
+               ]
+             }}) {{
+               id
+             }}
+           }}
+           "#,
+            apparel.id
+        ))
+        .await;
+    assert!(res.errors.is_empty());
+    let shoes = res.data.into_json()?["createCategory"]["id"]
+        .as_str()
+        .map(ToOwned::to_owned)
+        .expect("category to be created");
+
+    let res = schema
+        .execute(format!(
+            r#"
+           query {{
+             effectiveAttributes(id: "{shoes}") {{
+               key
+               kind
+               required
+               allowedValues
+             }}
+           }}
+           "#
+        ))
+        .await;
+    assert!(res.errors.is_empty());
+    assert_eq!(
+        res.data,
+        async_graphql::value!({
+            "effectiveAttributes": [
+                { "key": "size", "kind": "NUMBER", "required": true, "allowedValues": [] },
+                { "key": "brand", "kind": "TEXT", "required": false, "allowedValues": [] }
+            ]
+        })
+    );
+
+    let res = schema
+        .execute(
+            r#"
+           mutation {
+             createCategory(input: { name: "Toys", attributes: [{ key: "age", kind: SELECT }] }) {
+               id
+             }
+           }
+           "#,
+        )
+        .await;
+    assert_eq!(
+        res.errors[0].message,
+        "invalid attribute `age`: a selection needs allowed values"
+    );
 
     Ok(())
 }
@@ -0,0 +1,253 @@
+//! Manages categories straight from the database, for when the GraphQL API is not an option.
+//! Connections are configured from the same environment variables as the API server
+
+mod output;
+
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail, Result};
+use api_categories::state::AppState;
+use api_core::{
+    api::{MutateCategories, QueryCategories, Uuid},
+    taxonomy::{self, Format},
+    Category, DeleteStrategy,
+};
+use clap::{Parser, Subcommand};
+use tracing_subscriber::EnvFilter;
+
+use self::output::{Maintenance, Output};
+
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    /// How results are printed
+    #[arg(long, short, value_enum, default_value_t, global = true)]
+    output: Output,
+    /// Recorded as the author of the changes made
+    #[arg(long, env = "CATEGORIES_CALLER", global = true)]
+    caller: Option<String>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Lists the top level categories, or the subcategories of a parent
+    List {
+        /// Lists the subcategories of this category
+        #[arg(long, conflicts_with = "all")]
+        parent: Option<Uuid>,
+        /// Lists every category
+        #[arg(long)]
+        all: bool,
+    },
+    /// Shows a single category
+    Get { id: Uuid },
+    /// Shows the subtree under a category, or the whole tree
+    Tree {
+        root: Option<Uuid>,
+        /// Levels of subcategories to walk
+        #[arg(long, default_value_t = 5)]
+        depth: usize,
+    },
+    /// Creates a category
+    Create {
+        name: String,
+        #[arg(long)]
+        parent: Option<Uuid>,
+        #[arg(long)]
+        image_url: Option<String>,
+    },
+    /// Moves a category, along with its subcategories, under another parent
+    Move {
+        id: Uuid,
+        /// The new parent, the category moves to the top level when left out
+        #[arg(long)]
+        parent: Option<Uuid>,
+        /// Position among the new parent's subcategories, last when left out
+        #[arg(long)]
+        position: Option<usize>,
+    },
+    /// Moves a category to the trash
+    Delete {
+        id: Uuid,
+        /// What happens to the subcategories: restrict, cascade or reparent
+        #[arg(long, value_parser = parse_strategy, default_value = "restrict")]
+        strategy: DeleteStrategy,
+    },
+    /// Creates and updates categories from a taxonomy file
+    Import {
+        /// Read from stdin when left out
+        file: Option<PathBuf>,
+        /// json, csv or google. Guessed from the file extension when left out
+        #[arg(long, short)]
+        format: Option<Format>,
+        /// Shows what would change without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Writes every category to a taxonomy file
+    Export {
+        /// Written to stdout when left out
+        file: Option<PathBuf>,
+        /// json, csv or google. Guessed from the file extension when left out
+        #[arg(long, short)]
+        format: Option<Format>,
+    },
+    /// Rebuilds the search index from the database
+    ReindexSearch,
+    /// Removes every cached category
+    FlushCache,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+
+    // the environment is parsed like the server's, which warns about every variable left unset
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| "error".into()))
+        .with_writer(io::stderr)
+        .init();
+
+    let cli = Cli::parse();
+    let state = AppState::try_from_env()?;
+    let client = api_interface::connect(
+        state.database_credentials(),
+        Some(state.redis_credentials()),
+        Some(state.meilisearch_credentials()),
+    )
+    .await?;
+    let caller = cli.caller.as_deref();
+
+    match cli.command {
+        Command::List { parent, all } => {
+            let categories: Vec<_> = if all {
+                client.get_categories().await?.collect()
+            } else {
+                client.get_sub_categories(parent.as_ref()).await?.collect()
+            };
+            output::print(cli.output, categories.as_slice())
+        }
+        Command::Get { id } => {
+            let category = client
+                .get_category_by_id(&id)
+                .await?
+                .ok_or_else(|| not_found(&id))?;
+            output::print(cli.output, &category)
+        }
+        Command::Tree { root, depth } => {
+            let tree: Vec<_> = client
+                .get_category_tree(root.as_ref(), depth)
+                .await?
+                .collect();
+            output::print(cli.output, tree.as_slice())
+        }
+        Command::Create {
+            name,
+            parent,
+            image_url,
+        } => {
+            let category = Category {
+                name,
+                parent_id: parent,
+                image_url,
+                ..Default::default()
+            };
+            let category = client.create_category(&category, caller).await?;
+            output::print(cli.output, &category)
+        }
+        Command::Move {
+            id,
+            parent,
+            position,
+        } => {
+            let category = client
+                .move_category(&id, parent.as_ref(), position, None, caller)
+                .await?
+                .ok_or_else(|| not_found(&id))?;
+            output::print(cli.output, &category)
+        }
+        Command::Delete { id, strategy } => {
+            let deleted = client
+                .delete_category(&id, strategy, None, caller)
+                .await?
+                .ok_or_else(|| not_found(&id))?;
+            output::print(cli.output, &deleted)
+        }
+        Command::Import {
+            file,
+            format,
+            dry_run,
+        } => {
+            let format = format_of(format, file.as_deref())?;
+            let categories = match file {
+                Some(path) => taxonomy::read(format, BufReader::new(File::open(path)?))?,
+                None => taxonomy::read(format, io::stdin().lock())?,
+            };
+
+            let diff = taxonomy::import(&client, &categories, dry_run, caller).await?;
+            if dry_run {
+                eprintln!("dry run, nothing was written");
+            }
+            output::print(cli.output, &diff)
+        }
+        Command::Export { file, format } => {
+            let format = format_of(format, file.as_deref())?;
+            match file {
+                Some(path) => {
+                    let mut writer = BufWriter::new(File::create(path)?);
+                    taxonomy::export(&client, format, &mut writer).await?;
+                    writer.flush()?;
+                }
+                None => taxonomy::export(&client, format, io::stdout().lock()).await?,
+            }
+            Ok(())
+        }
+        Command::ReindexSearch => {
+            let indexed = client.reindex_search().await?;
+            output::print(cli.output, &Maintenance::Indexed(indexed))
+        }
+        Command::FlushCache => {
+            let removed = client.flush_cache().await?;
+            output::print(cli.output, &Maintenance::Removed(removed))
+        }
+    }
+}
+
+fn not_found(id: &Uuid) -> anyhow::Error {
+    anyhow!("category {id} does not exist")
+}
+
+fn parse_strategy(strategy: &str) -> Result<DeleteStrategy, String> {
+    match strategy.to_lowercase().as_str() {
+        "restrict" => Ok(DeleteStrategy::Restrict),
+        "cascade" => Ok(DeleteStrategy::Cascade),
+        "reparent" => Ok(DeleteStrategy::Reparent),
+        _ => Err(format!("unknown delete strategy `{strategy}`")),
+    }
+}
+
+/// Falls back to the file extension when no format is given. Stdin and stdout default to JSON
+fn format_of(format: Option<Format>, file: Option<&Path>) -> Result<Format> {
+    if let Some(format) = format {
+        return Ok(format);
+    }
+    let Some(file) = file else {
+        return Ok(Format::default());
+    };
+
+    match file.extension().and_then(|extension| extension.to_str()) {
+        Some("json") => Ok(Format::Json),
+        Some("csv") => Ok(Format::Csv),
+        Some("txt") => Ok(Format::Google),
+        _ => bail!(
+            "cannot tell the format of {}, pass it with --format",
+            file.display()
+        ),
+    }
+}
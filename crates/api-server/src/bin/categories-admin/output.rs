@@ -0,0 +1,167 @@
+use std::io::{self, Write};
+
+use anyhow::Result;
+use api_core::{taxonomy::ImportDiff, Category, CategoryTree, DeletedCategory};
+use clap::ValueEnum;
+use serde::Serialize;
+
+#[derive(Debug, Default, Clone, Copy, ValueEnum)]
+pub enum Output {
+    /// Aligned columns, for reading
+    #[default]
+    Table,
+    /// Pretty printed JSON, for scripts
+    Json,
+}
+
+/// The result of a maintenance command
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Maintenance {
+    /// Number of categories added to the search index
+    Indexed(usize),
+    /// Number of cache entries removed
+    Removed(usize),
+}
+
+/// Something that can be printed as a table as well as JSON
+pub trait Render: Serialize {
+    fn table(&self, out: &mut dyn Write) -> io::Result<()>;
+}
+
+pub fn print<T: Render + ?Sized>(output: Output, value: &T) -> Result<()> {
+    let mut out = io::stdout().lock();
+
+    match output {
+        Output::Json => {
+            serde_json::to_writer_pretty(&mut out, value)?;
+            writeln!(out)?;
+        }
+        Output::Table => value.table(&mut out)?,
+    }
+
+    Ok(())
+}
+
+const CATEGORY_HEADERS: [&str; 5] = ["ID", "NAME", "PARENT", "SUBCATEGORIES", "VERSION"];
+
+fn category_row(category: &Category) -> Vec<String> {
+    vec![
+        category.id.to_string(),
+        category.name.to_owned(),
+        optional(category.parent_id),
+        category.sub_categories.len().to_string(),
+        category.version.to_string(),
+    ]
+}
+
+fn optional(id: Option<impl ToString>) -> String {
+    id.map_or_else(|| String::from("-"), |id| id.to_string())
+}
+
+/// Writes `rows` under `headers`, padding every column to its widest cell
+fn table(out: &mut dyn Write, headers: &[&str], rows: &[Vec<String>]) -> io::Result<()> {
+    let mut widths: Vec<_> = headers.iter().map(|header| header.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut line = |cells: Vec<&str>| {
+        let line: Vec<_> = cells
+            .into_iter()
+            .zip(widths.iter())
+            .map(|(cell, width)| format!("{cell:width$}"))
+            .collect();
+        writeln!(out, "{}", line.join("  ").trim_end())
+    };
+
+    line(headers.to_vec())?;
+    for row in rows {
+        line(row.iter().map(String::as_str).collect())?;
+    }
+
+    Ok(())
+}
+
+impl Render for Category {
+    fn table(&self, out: &mut dyn Write) -> io::Result<()> {
+        table(out, &CATEGORY_HEADERS, &[category_row(self)])
+    }
+}
+
+impl Render for [Category] {
+    fn table(&self, out: &mut dyn Write) -> io::Result<()> {
+        let rows: Vec<_> = self.iter().map(category_row).collect();
+        table(out, &CATEGORY_HEADERS, &rows)
+    }
+}
+
+impl Render for [CategoryTree] {
+    fn table(&self, out: &mut dyn Write) -> io::Result<()> {
+        let mut rows = Vec::new();
+        // subcategories are indented under their parent
+        let mut pending: Vec<_> = self.iter().rev().map(|node| (0, node)).collect();
+        while let Some((depth, node)) = pending.pop() {
+            rows.push(vec![
+                format!("{}{}", "  ".repeat(depth), node.category.name),
+                node.category.id.to_string(),
+            ]);
+            pending.extend(node.children.iter().rev().map(|child| (depth + 1, child)));
+        }
+
+        table(out, &["NAME", "ID"], &rows)
+    }
+}
+
+impl Render for DeletedCategory {
+    fn table(&self, out: &mut dyn Write) -> io::Result<()> {
+        self.category.table(out)?;
+        writeln!(
+            out,
+            "\n{} subcategories deleted along with it, {} moved to its parent",
+            self.deleted.len(),
+            self.reparented.len()
+        )
+    }
+}
+
+impl Render for ImportDiff {
+    fn table(&self, out: &mut dyn Write) -> io::Result<()> {
+        let created = self
+            .created
+            .iter()
+            .map(|category| ("create", category));
+        let updated = self.updated.iter().map(|(_, after)| ("update", after));
+        let rows: Vec<_> = created
+            .chain(updated)
+            .map(|(change, category)| {
+                vec![
+                    change.to_owned(),
+                    category.id.to_string(),
+                    category.name.to_owned(),
+                    optional(category.parent_id),
+                ]
+            })
+            .collect();
+
+        table(out, &["CHANGE", "ID", "NAME", "PARENT"], &rows)?;
+        writeln!(
+            out,
+            "\n{} created, {} updated, {} unchanged",
+            self.created.len(),
+            self.updated.len(),
+            self.unchanged.len()
+        )
+    }
+}
+
+impl Render for Maintenance {
+    fn table(&self, out: &mut dyn Write) -> io::Result<()> {
+        match self {
+            Maintenance::Indexed(count) => writeln!(out, "{count} categories indexed"),
+            Maintenance::Removed(count) => writeln!(out, "{count} cache entries removed"),
+        }
+    }
+}
@@ -0,0 +1,91 @@
+mod routes;
+pub mod state;
+pub mod telemetry;
+
+#[cfg(test)]
+mod tests;
+
+use std::future::ready;
+
+use anyhow::Result;
+use async_graphql::extensions::Tracing;
+use async_graphql_axum::GraphQLSubscription;
+use axum::{
+    http::{header, HeaderName, HeaderValue, Method},
+    middleware,
+    routing::get,
+    Extension, Router,
+};
+use tower_http::{cors::CorsLayer, trace::TraceLayer};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+use crate::routes::{
+    graphql_handler, handler,
+    middleware::{graphql::Metrics, track_metrics},
+    CallerHeader,
+};
+
+const SUBSCRIPTION_ENDPOINT: &str = "/ws";
+
+pub async fn create_router(state: state::AppState) -> Result<Router> {
+    let schema_builder = api_interface::ApiSchemaBuilder::new(
+        state.database_credentials(),
+        Some(state.redis_credentials()),
+        Some(state.meilisearch_credentials()),
+    )
+    .await?
+    .with_extension(Tracing)
+    .with_extension(Metrics);
+
+    let schema = schema_builder.build();
+    let caller_header = state
+        .caller_header
+        .as_deref()
+        .map(HeaderName::try_from)
+        .transpose()?;
+
+    let router = Router::new()
+        .route("/", get(handler).post(graphql_handler))
+        .route(
+            "/metrics",
+            get(move || ready(state.metrics_handle.render())),
+        )
+        .route_service(
+            SUBSCRIPTION_ENDPOINT,
+            GraphQLSubscription::new(schema.clone()),
+        )
+        .route_layer(middleware::from_fn(track_metrics))
+        .layer(Extension(CallerHeader(caller_header)))
+        .layer(TraceLayer::new_for_http().make_span_with(make_span))
+        .layer(
+            CorsLayer::new()
+                .allow_origin(state.frontend_url.parse::<HeaderValue>()?)
+                .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION])
+                .allow_methods([Method::GET, Method::POST]),
+        )
+        .with_state(schema);
+
+    Ok(router)
+}
+
+fn make_span(request: &axum::http::Request<axum::body::Body>) -> tracing::Span {
+    let headers = request.headers();
+
+    let header_map: std::collections::HashMap<String, String> = headers
+        .iter()
+        .map(|(key, value)| {
+            (
+                key.to_string(),
+                String::from_utf8_lossy(value.as_bytes()).to_string(),
+            )
+        })
+        .collect();
+
+    let parent_ctx = opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.extract(&header_map)
+    });
+
+    let span = tracing::info_span!("categories.request", ?headers);
+    span.set_parent(parent_ctx);
+    span
+}
@@ -1,6 +1,18 @@
 pub mod middleware;
 
-use axum::response::IntoResponse;
+use api_interface::{ApiSchema, Caller, Locales};
+use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, HeaderName},
+    response::IntoResponse,
+    Extension,
+};
+
+/// Header identifying the user behind a request. Only read when configured, for an
+/// authenticating proxy that sets it and strips it from client requests
+#[derive(Clone, Default)]
+pub struct CallerHeader(pub Option<HeaderName>);
 
 pub async fn handler() -> impl IntoResponse {
     #[cfg(debug_assertions)]
@@ -23,3 +35,29 @@ pub async fn handler() -> impl IntoResponse {
         )
     }
 }
+
+pub async fn graphql_handler(
+    State(schema): State<ApiSchema>,
+    Extension(CallerHeader(caller_header)): Extension<CallerHeader>,
+    headers: HeaderMap,
+    request: GraphQLRequest,
+) -> GraphQLResponse {
+    let mut request = request.into_inner();
+
+    if let Some(caller) = caller_header
+        .and_then(|name| headers.get(name))
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty())
+    {
+        request = request.data(Caller(caller.to_owned()));
+    }
+    // names and descriptions are resolved in the preferred locales
+    if let Some(accept_language) = headers
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+    {
+        request = request.data(Locales::from_accept_language(accept_language));
+    }
+
+    schema.execute(request).await.into()
+}
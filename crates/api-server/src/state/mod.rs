@@ -1,7 +1,7 @@
 pub mod env;
 
 use anyhow::{Ok, Result};
-use api_interface::{DatabaseCredentials, RedisConfig};
+use api_interface::{DatabaseAuth, DatabaseCredentials, RedisConfig};
 use metrics_exporter_prometheus::PrometheusHandle;
 use tracing::{error, instrument, warn};
 
@@ -13,6 +13,8 @@ pub struct AppState {
     database_dsn: String,
     database_username: String,
     database_password: String,
+    database_auth: String,
+    database_scope: String,
     database_namespace: String,
     database_name: String,
     pub frontend_url: String,
@@ -23,6 +25,7 @@ pub struct AppState {
     cache_ttl: u64,
     meilisearch_host: String,
     meilisearch_api_key: Option<String>,
+    pub caller_header: Option<String>,
 }
 
 impl AppState {
@@ -32,13 +35,25 @@ impl AppState {
         let otel_collector_endpoint =
             env::extract_variable("OPENTELEMETRY_COLLECTOR_HOST", "http://localhost:4318");
 
-        let (dsn, db_name, db_user, db_pass, db_ns, redis_host, redis_is_cluster) = {
+        let (
+            dsn,
+            db_name,
+            db_user,
+            db_pass,
+            db_auth,
+            db_scope,
+            db_ns,
+            redis_host,
+            redis_is_cluster,
+        ) = {
             if cfg!(test) {
                 (
                     "TEST_DATABASE_URL",
                     "TEST_DATABASE_NAME",
                     "TEST_DATABASE_USERNAME",
                     "TEST_DATABASE_PASSWORD",
+                    "TEST_DATABASE_AUTH",
+                    "TEST_DATABASE_SCOPE",
                     "TEST_DATABASE_NAMESPACE",
                     "TEST_REDIS_HOST",
                     "TEST_REDIS_CLUSTER",
@@ -49,6 +64,8 @@ impl AppState {
                     "DATABASE_NAME",
                     "DATABASE_USERNAME",
                     "DATABASE_PASSWORD",
+                    "DATABASE_AUTH",
+                    "DATABASE_SCOPE",
                     "DATABASE_NAMESPACE",
                     "REDIS_HOST",
                     "REDIS_CLUSTER",
@@ -63,6 +80,15 @@ impl AppState {
 
         let database_username = env::extract_variable(db_user, "");
         let database_password = env::extract_variable(db_pass, "");
+        let database_auth = env::extract_variable(db_auth, "root").to_lowercase();
+        if !["none", "root", "namespace", "database", "scope"].contains(&database_auth.as_str()) {
+            warn!(
+                val = database_auth,
+                default = "root",
+                "database auth level invalid"
+            );
+        }
+        let database_scope = env::extract_variable(db_scope, "");
         let database_namespace = env::extract_variable(db_ns, "");
         let database_name = env::extract_variable(db_name, "");
         let frontend_url = env::extract_variable("FRONTEND_URL", "http://localhost:5173");
@@ -79,6 +105,13 @@ impl AppState {
             Some(meilisearch_api_key)
         };
 
+        let caller_header = env::extract_variable("CALLER_HEADER", "");
+        let caller_header = if caller_header.is_empty() {
+            None
+        } else {
+            Some(caller_header)
+        };
+
         let metrics_handle = setup_metrics_recorder()?;
 
         Ok(AppState {
@@ -87,6 +120,8 @@ impl AppState {
             database_dsn,
             database_username,
             database_password,
+            database_auth,
+            database_scope,
             database_name,
             database_namespace,
             frontend_url,
@@ -94,6 +129,7 @@ impl AppState {
             redis_dsn,
             meilisearch_host,
             meilisearch_api_key,
+            caller_header,
             redis_clustered: redis_clustered.parse().unwrap_or_else(|_| {
                 warn!("REDIS_CLUSTER is not a boolean value");
                 false
@@ -114,10 +150,22 @@ impl AppState {
     }
 
     pub fn database_credentials(&self) -> DatabaseCredentials {
+        let username = &self.database_username;
+        let password = &self.database_password;
+
         DatabaseCredentials {
             db_dsn: &self.database_dsn,
-            db_user: &self.database_username,
-            db_pass: &self.database_password,
+            db_auth: match self.database_auth.as_str() {
+                "none" => DatabaseAuth::None,
+                "namespace" => DatabaseAuth::Namespace { username, password },
+                "database" => DatabaseAuth::Database { username, password },
+                "scope" => DatabaseAuth::Scope {
+                    scope: &self.database_scope,
+                    username,
+                    password,
+                },
+                _ => DatabaseAuth::Root { username, password },
+            },
             db_ns: &self.database_namespace,
             db: &self.database_name,
         }